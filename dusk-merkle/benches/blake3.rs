@@ -9,58 +9,33 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use rand::rngs::StdRng;
 use rand::{RngCore, SeedableRng};
 
-use blake3::{Hash as Blake3Hash, Hasher};
+use dusk_merkle::blake3::{Item, Tree};
 
-use dusk_merkle::{Aggregate, Tree};
-
-const EMPTY_HASH: Item = Item([0; 32]);
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Item([u8; 32]);
-
-impl From<Blake3Hash> for Item {
-    fn from(h: Blake3Hash) -> Self {
-        Self(h.into())
-    }
-}
+const H: usize = 32;
 
-impl<const A: usize> Aggregate<A> for Item {
-    const EMPTY_SUBTREE: Self = EMPTY_HASH;
+const NS: &[u64] = &[10, 100, 1000, 10000];
 
-    fn aggregate(items: [&Self; A]) -> Self {
-        let mut hasher = Hasher::new();
-        for item in items {
-            hasher.update(&item.0);
-        }
-        hasher.finalize().into()
-    }
+fn bench_blake3_insert(c: &mut Criterion) {
+    bench_insert::<2>(c);
+    bench_insert::<4>(c);
 }
 
-impl Item {
-    #[must_use]
-    pub fn new(bytes: [u8; 32]) -> Self {
-        Item(bytes)
-    }
+fn bench_blake3_root(c: &mut Criterion) {
+    bench_root::<2>(c);
+    bench_root::<4>(c);
 }
 
-const H: usize = 32;
-const A: usize = 2;
-
-type Blake3Tree = Tree<Item, H, A>;
-
-const NS: &[u64] = &[10, 100, 1000, 10000];
-
-fn bench_blake3_insert(c: &mut Criterion) {
+fn bench_insert<const A: usize>(c: &mut Criterion) {
     let rng = &mut StdRng::seed_from_u64(0xbeef);
 
-    let mut group = c.benchmark_group("blake3_insert_n");
+    let mut group = c.benchmark_group(format!("blake3_insert_n_arity_{A}"));
     for n in NS {
         group.bench_with_input(
             BenchmarkId::from_parameter(*n),
             n,
             |b, &size| {
                 b.iter(|| {
-                    let mut tree = Blake3Tree::new();
+                    let mut tree = Tree::<H, A>::new();
                     insert_random_n(rng, &mut tree, size);
                 });
             },
@@ -68,17 +43,17 @@ fn bench_blake3_insert(c: &mut Criterion) {
     }
 }
 
-fn bench_blake3_root(c: &mut Criterion) {
+fn bench_root<const A: usize>(c: &mut Criterion) {
     let rng = &mut StdRng::seed_from_u64(0xbeef);
 
-    let mut group = c.benchmark_group("blake3_root_n");
+    let mut group = c.benchmark_group(format!("blake3_root_n_arity_{A}"));
     for n in NS {
         group.bench_with_input(
             BenchmarkId::from_parameter(*n),
             n,
             |b, &size| {
                 b.iter(|| {
-                    let mut tree = Blake3Tree::new();
+                    let mut tree = Tree::<H, A>::new();
                     insert_random_n(rng, &mut tree, size);
                     let _root = *tree.root();
                 });
@@ -87,14 +62,16 @@ fn bench_blake3_root(c: &mut Criterion) {
     }
 }
 
-fn insert_random_n<Rng: RngCore>(rng: &mut Rng, tree: &mut Blake3Tree, n: u64) {
+fn insert_random_n<Rng: RngCore, const A: usize>(
+    rng: &mut Rng,
+    tree: &mut Tree<H, A>,
+    n: u64,
+) {
     let cap = tree.capacity();
 
     let mut hash_bytes = [0u8; 32];
     rng.fill_bytes(&mut hash_bytes);
-    let mut hasher = Hasher::new();
-    hasher.update(&hash_bytes);
-    let hash: Item = hasher.finalize().into();
+    let hash = Item::leaf(&hash_bytes);
 
     for _ in 0..n {
         let pos = rng.next_u64() % cap;
@@ -0,0 +1,252 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#[cfg(feature = "rand")]
+use rand_core::RngCore;
+
+/// A strategy for choosing the position at which an item is inserted in a
+/// tree, used with [`Tree::insert_alloc`].
+///
+/// [`Tree::insert_alloc`]: crate::Tree::insert_alloc
+pub trait PositionAllocator<T> {
+    /// Returns the position at which the given `item` should be inserted in a
+    /// tree with the given `capacity`, or `None` if no position is available.
+    ///
+    /// Returned positions must be strictly smaller than the `capacity`.
+    fn allocate(&mut self, item: &T, capacity: u64) -> Option<u64>;
+}
+
+/// Allocates positions sequentially, starting from a given position.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SequentialAllocator {
+    next: u64,
+}
+
+impl SequentialAllocator {
+    /// Create a new allocator starting at position `0`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::starting_at(0)
+    }
+
+    /// Create a new allocator starting at the given `position`.
+    #[must_use]
+    pub const fn starting_at(position: u64) -> Self {
+        Self { next: position }
+    }
+
+    /// Returns the next position the allocator will return.
+    #[must_use]
+    pub const fn next_position(&self) -> u64 {
+        self.next
+    }
+}
+
+impl<T> PositionAllocator<T> for SequentialAllocator {
+    fn allocate(&mut self, _: &T, capacity: u64) -> Option<u64> {
+        if self.next >= capacity {
+            return None;
+        }
+
+        let position = self.next;
+        self.next += 1;
+        Some(position)
+    }
+}
+
+/// Allocates positions uniformly at random within the capacity of the tree.
+///
+/// Positions are not checked for occupancy, meaning an allocated position may
+/// already hold an item. The probability of this happening depends on the
+/// ratio between the number of items and the capacity of the tree.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone)]
+pub struct RandomAllocator<R> {
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<R: RngCore> RandomAllocator<R> {
+    /// Create a new allocator drawing positions from the given `rng`.
+    pub const fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<T, R: RngCore> PositionAllocator<T> for RandomAllocator<R> {
+    fn allocate(&mut self, _: &T, capacity: u64) -> Option<u64> {
+        if capacity == 0 {
+            return None;
+        }
+        Some(random_below(|| self.rng.next_u64(), capacity))
+    }
+}
+
+/// Allocates positions derived from the items themselves, using a function
+/// mapping each item to a `u64` - e.g. the first bytes of its hash.
+///
+/// The same item will always be allocated the same position, so inserting it
+/// twice leaves the tree unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct HashAllocator<F> {
+    derive: F,
+}
+
+impl<F> HashAllocator<F> {
+    /// Create a new allocator using the given function to derive positions
+    /// from items.
+    pub const fn new(derive: F) -> Self {
+        Self { derive }
+    }
+}
+
+impl<T, F> PositionAllocator<T> for HashAllocator<F>
+where
+    F: Fn(&T) -> u64,
+{
+    fn allocate(&mut self, item: &T, capacity: u64) -> Option<u64> {
+        if capacity == 0 {
+            return None;
+        }
+        Some((self.derive)(item) % capacity)
+    }
+}
+
+/// Allocates positions derived by hashing a fixed key together with the
+/// number of positions allocated so far.
+///
+/// The same key always produces the same sequence of positions, across runs,
+/// platforms, and versions of the crate, making it suitable for tests and
+/// reproducible simulations. Like the ones of a `RandomAllocator`, positions
+/// are not checked for occupancy. The derivation is not cryptographically
+/// secure, and positions must not be allocated this way where an adversary may
+/// exploit them being predictable.
+#[derive(Debug, Clone)]
+pub struct DeterministicPositioner {
+    key: u64,
+    hasher: SplitMix64,
+}
+
+impl DeterministicPositioner {
+    /// Create a new positioner deriving positions from the given `key`.
+    #[must_use]
+    pub const fn new(key: u64) -> Self {
+        Self {
+            key,
+            hasher: SplitMix64(key),
+        }
+    }
+
+    /// Returns the key positions are derived from.
+    #[must_use]
+    pub const fn key(&self) -> u64 {
+        self.key
+    }
+}
+
+impl<T> PositionAllocator<T> for DeterministicPositioner {
+    fn allocate(&mut self, _: &T, capacity: u64) -> Option<u64> {
+        if capacity == 0 {
+            return None;
+        }
+        Some(random_below(|| self.hasher.next_u64(), capacity))
+    }
+}
+
+/// Returns a uniformly distributed number in the range `[0, bound[`, drawing
+/// random numbers from the given `next_u64` function.
+pub(crate) fn random_below<F>(mut next_u64: F, bound: u64) -> u64
+where
+    F: FnMut() -> u64,
+{
+    // reject the values that would bias the result towards lower numbers
+    let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+    loop {
+        let n = next_u64();
+        if n <= zone {
+            return n % bound;
+        }
+    }
+}
+
+/// The `SplitMix64` generator, chosen for being small and fully specified.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    const CAPACITY: u64 = 16;
+
+    #[test]
+    fn sequential() {
+        let mut allocator = SequentialAllocator::starting_at(CAPACITY - 2);
+
+        assert_eq!(allocator.allocate(&(), CAPACITY), Some(CAPACITY - 2));
+        assert_eq!(allocator.allocate(&(), CAPACITY), Some(CAPACITY - 1));
+        assert_eq!(allocator.allocate(&(), CAPACITY), None);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn random() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut allocator = RandomAllocator::new(StdRng::seed_from_u64(0xbeef));
+
+        for _ in 0..100 {
+            let position = allocator.allocate(&(), CAPACITY).unwrap();
+            assert!(position < CAPACITY);
+        }
+        assert_eq!(allocator.allocate(&(), 0), None);
+    }
+
+    #[test]
+    fn deterministic() {
+        let mut a = DeterministicPositioner::new(42);
+        let mut b = DeterministicPositioner::new(42);
+        let mut c = DeterministicPositioner::new(43);
+
+        let positions: Vec<u64> =
+            (0..4).map(|_| a.allocate(&(), CAPACITY).unwrap()).collect();
+        assert!(positions.iter().all(|&position| position < CAPACITY));
+        for position in &positions {
+            assert_eq!(b.allocate(&(), CAPACITY), Some(*position));
+        }
+
+        let others: Vec<u64> =
+            (0..4).map(|_| c.allocate(&(), CAPACITY).unwrap()).collect();
+        assert_ne!(positions, others);
+
+        // the sequence is fixed across platforms and versions
+        assert_eq!(positions, [5, 3, 2, 4]);
+        assert_eq!(a.allocate(&(), 0), None);
+    }
+
+    #[test]
+    fn hash_derived() {
+        let mut allocator = HashAllocator::new(|item: &u64| item * 3);
+
+        assert_eq!(allocator.allocate(&5, CAPACITY), Some(15));
+        assert_eq!(allocator.allocate(&6, CAPACITY), Some(2));
+        assert_eq!(allocator.allocate(&6, CAPACITY), Some(2));
+    }
+}
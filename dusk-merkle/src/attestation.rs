@@ -0,0 +1,169 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+use dusk_bytes::Serializable;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+/// The domain separator prepended to the message signed when attesting to a
+/// root.
+pub const ATTESTATION_DOMAIN: &[u8] = b"dusk-merkle:attested-root:v1";
+
+/// Returns the message signed when attesting to the `root` of a tree with
+/// height `H` and arity `A`.
+///
+/// The message is the concatenation of:
+/// - the [`ATTESTATION_DOMAIN`]
+/// - the height `H`, as a little endian `u32`
+/// - the arity `A`, as a little endian `u32`
+/// - the serialized `root`
+///
+/// # Panics
+/// If either `H` or `A` don't fit in a `u32`.
+pub fn attestation_message<
+    T,
+    const T_SIZE: usize,
+    const H: usize,
+    const A: usize,
+>(
+    root: &T,
+) -> Vec<u8>
+where
+    T: Serializable<T_SIZE>,
+{
+    let height = u32::try_from(H).expect("The height should fit in a u32");
+    let arity = u32::try_from(A).expect("The arity should fit in a u32");
+
+    let mut message = Vec::with_capacity(
+        ATTESTATION_DOMAIN.len() + 2 * (u32::BITS as usize / 8) + T_SIZE,
+    );
+
+    message.extend(ATTESTATION_DOMAIN);
+    message.extend(&height.to_bytes());
+    message.extend(&arity.to_bytes());
+    message.extend(&root.to_bytes());
+
+    message
+}
+
+/// The root of a tree, together with an ed25519 signature binding it to the
+/// tree's parameters.
+///
+/// The signed message is produced by [`attestation_message`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttestedRoot<T, const H: usize, const A: usize> {
+    root: T,
+    public_key: VerifyingKey,
+    signature: Signature,
+}
+
+impl<T, const H: usize, const A: usize> AttestedRoot<T, H, A> {
+    /// Attest to the given `root` by signing it with the given `key`.
+    pub fn sign<const T_SIZE: usize>(root: T, key: &SigningKey) -> Self
+    where
+        T: Serializable<T_SIZE>,
+    {
+        let message = attestation_message::<T, T_SIZE, H, A>(&root);
+        let signature = key.sign(&message);
+
+        Self {
+            root,
+            public_key: key.verifying_key(),
+            signature,
+        }
+    }
+
+    /// Create an attested root from its parts, for instance after receiving
+    /// them from the network.
+    ///
+    /// The attestation is not checked, use [`verify`] to do so.
+    ///
+    /// [`verify`]: AttestedRoot::verify
+    pub fn from_parts(
+        root: T,
+        public_key: VerifyingKey,
+        signature: Signature,
+    ) -> Self {
+        Self {
+            root,
+            public_key,
+            signature,
+        }
+    }
+
+    /// Verify the signature over the root and the tree parameters.
+    pub fn verify<const T_SIZE: usize>(&self) -> bool
+    where
+        T: Serializable<T_SIZE>,
+    {
+        let message = attestation_message::<T, T_SIZE, H, A>(&self.root);
+        self.public_key.verify(&message, &self.signature).is_ok()
+    }
+
+    /// Returns the attested root.
+    pub fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the public key of the attester.
+    pub fn public_key(&self) -> &VerifyingKey {
+        &self.public_key
+    }
+
+    /// Returns the signature over the root.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use dusk_bytes::Error as BytesError;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Root([u8; 8]);
+
+    impl Serializable<8> for Root {
+        type Error = BytesError;
+
+        fn from_bytes(buf: &[u8; 8]) -> Result<Self, Self::Error> {
+            Ok(Self(*buf))
+        }
+
+        fn to_bytes(&self) -> [u8; 8] {
+            self.0
+        }
+    }
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    #[test]
+    fn attest_and_verify() {
+        let key = SigningKey::from_bytes(&KEY);
+        let root = Root(*b"the root");
+
+        let attested = AttestedRoot::<_, 17, 4>::sign(root, &key);
+        assert!(attested.verify());
+
+        let tampered = AttestedRoot::<_, 17, 4>::from_parts(
+            Root(*b"another!"),
+            *attested.public_key(),
+            *attested.signature(),
+        );
+        assert!(!tampered.verify());
+
+        // the same signature is not valid for a tree of a different shape
+        let reshaped = AttestedRoot::<_, 16, 4>::from_parts(
+            *attested.root(),
+            *attested.public_key(),
+            *attested.signature(),
+        );
+        assert!(!reshaped.verify());
+    }
+}
@@ -0,0 +1,192 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use crate::{trace, Aggregate, Opening, Tree};
+
+/// A [`Tree`] whose dirty items are recomputed on a background thread after
+/// each mutation.
+///
+/// Once a mutation is performed, a worker thread is notified and computes the
+/// root of the tree, and with it all the items on the mutated paths. This
+/// means the next call to [`root`] or [`opening`] will not need to pay for
+/// the aggregation, provided the worker had time to finish.
+///
+/// When the worker is still busy, calls to the tree will block until it is
+/// done.
+///
+/// [`root`]: BackgroundTree::root
+/// [`opening`]: BackgroundTree::opening
+#[derive(Debug)]
+pub struct BackgroundTree<T, const H: usize, const A: usize> {
+    tree: Arc<Mutex<Tree<T, H, A>>>,
+    sender: Option<Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T, const H: usize, const A: usize> BackgroundTree<T, H, A>
+where
+    T: Aggregate<A> + 'static,
+    Tree<T, H, A>: Send,
+{
+    /// Wraps the given `tree`, spawning the worker thread responsible for
+    /// keeping its items up to date.
+    #[must_use]
+    pub fn new(tree: Tree<T, H, A>) -> Self {
+        let tree = Arc::new(Mutex::new(tree));
+        let (sender, receiver) = mpsc::channel::<()>();
+
+        let worker_tree = Arc::clone(&tree);
+        let worker = thread::spawn(move || {
+            while receiver.recv().is_ok() {
+                // multiple mutations may have happened in the meantime, and
+                // they can all be handled by computing the root once
+                while receiver.try_recv().is_ok() {}
+                trace::span!("background_root");
+                let _ = lock(&worker_tree).root();
+            }
+        });
+
+        // the tree given may have been mutated prior to being wrapped
+        let _ = sender.send(());
+
+        Self {
+            tree,
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// Insert an `item` at the given `position` in the tree, and schedule the
+    /// recomputation of the root.
+    ///
+    /// # Panics
+    /// If `index >= capacity`.
+    pub fn insert(&mut self, index: u64, item: impl Into<T>) {
+        lock(&self.tree).insert(index, item);
+        self.notify();
+    }
+
+    /// Remove and return the item at the given `position` in the tree if it
+    /// exists, and schedule the recomputation of the root.
+    pub fn remove(&mut self, position: u64) -> Option<T> {
+        let item = lock(&self.tree).remove(position);
+        if item.is_some() {
+            self.notify();
+        }
+        item
+    }
+
+    /// Get the root of the merkle tree.
+    #[must_use]
+    pub fn root(&self) -> T
+    where
+        T: Clone,
+    {
+        lock(&self.tree).root().clone()
+    }
+
+    /// Returns the [`Opening`] for the given `position` if it exists.
+    #[must_use]
+    pub fn opening(&self, position: u64) -> Option<Opening<T, H, A>>
+    where
+        T: Clone,
+    {
+        lock(&self.tree).opening(position)
+    }
+
+    /// Calls the given closure with a reference to the underlying tree,
+    /// returning its result.
+    pub fn with_tree<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Tree<T, H, A>) -> R,
+    {
+        f(&lock(&self.tree))
+    }
+
+    /// Stops the worker thread and returns the underlying tree.
+    ///
+    /// # Panics
+    /// If the worker thread could not be stopped.
+    #[must_use]
+    pub fn into_inner(mut self) -> Tree<T, H, A> {
+        self.stop();
+
+        // the worker has been joined, so we hold the only reference
+        let tree = Arc::clone(&self.tree);
+        drop(self);
+
+        Arc::try_unwrap(tree)
+            .ok()
+            .expect("The worker should have been stopped")
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn notify(&self) {
+        if let Some(sender) = &self.sender {
+            // the worker only stops when the sender is dropped, so this can't
+            // fail
+            let _ = sender.send(());
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> BackgroundTree<T, H, A> {
+    fn stop(&mut self) {
+        // dropping the sender makes the worker exit its loop
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            // a panicking worker leaves the tree as it was before computing
+            // the root, so there is nothing else to be done
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> Drop for BackgroundTree<T, H, A> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Sum;
+
+    const H: usize = 8;
+    const A: usize = 2;
+
+    #[test]
+    fn background_root() {
+        let mut tree = Tree::<Sum, H, A>::new();
+        tree.insert(42, 1);
+
+        let mut background = BackgroundTree::new(tree.clone());
+
+        for pos in 0..100 {
+            tree.insert(pos, pos);
+            background.insert(pos, pos);
+        }
+        tree.remove(7);
+        background.remove(7);
+
+        assert_eq!(background.root(), *tree.root());
+        assert_eq!(background.opening(13), tree.opening(13));
+        assert_eq!(background.with_tree(Tree::len), tree.len());
+
+        let inner = background.into_inner();
+        assert_eq!(*inner.root(), *tree.root());
+    }
+}
@@ -0,0 +1,184 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+/// The number of bits in a word of a [`Bitmap`].
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A vector of bits, packed into `u64` words.
+///
+/// The `i`th bit is stored in the word at index `i / 64`, at the bit of
+/// weight `1 << (i % 64)`. The bits of the last word past the length of the
+/// bitmap are always zero.
+///
+/// Created using [`Tree::membership_bitmap`].
+///
+/// [`Tree::membership_bitmap`]: crate::Tree::membership_bitmap
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    /// Create a new bitmap of the given `len`, with all bits unset.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    /// Returns the number of bits in the bitmap.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the bitmap holds no bits.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit at the given `index`, or `None` if it is out of
+    /// bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        let word = self.words[index / WORD_BITS];
+        Some(word & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// Sets the bit at the given `index`.
+    ///
+    /// # Panics
+    /// If the index is out of bounds.
+    pub fn set(&mut self, index: usize) {
+        assert!(
+            index < self.len,
+            "index out of bounds: the len is {} but the index is {index}",
+            self.len
+        );
+        self.words[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    /// Returns the number of set bits.
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the words the bits are packed into.
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns an iterator over the bits of the bitmap, in order.
+    #[must_use]
+    pub fn iter(&self) -> Bits<'_> {
+        Bits {
+            bitmap: self,
+            index: 0,
+        }
+    }
+
+    /// Returns an iterator over the indices of the set bits, in ascending
+    /// order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * WORD_BITS + bit)
+            })
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a Bitmap {
+    type Item = bool;
+    type IntoIter = Bits<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the bits of a [`Bitmap`].
+///
+/// Created using [`Bitmap::iter`].
+#[derive(Debug, Clone)]
+pub struct Bits<'a> {
+    bitmap: &'a Bitmap,
+    index: usize,
+}
+
+impl Iterator for Bits<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bit = self.bitmap.get(self.index)?;
+        self.index += 1;
+        Some(bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bitmap.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Bits<'_> {}
+
+impl FusedIterator for Bits<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitmap() {
+        let mut bitmap = Bitmap::new(130);
+        assert_eq!(bitmap.words().len(), 3);
+        assert_eq!(bitmap.count_ones(), 0);
+
+        for index in [0, 63, 64, 129] {
+            bitmap.set(index);
+        }
+        assert_eq!(bitmap.words(), [1 | 1 << 63, 1, 1 << 1]);
+        assert_eq!(bitmap.get(63), Some(true));
+        assert_eq!(bitmap.get(62), Some(false));
+        assert_eq!(bitmap.get(130), None);
+        assert_eq!(bitmap.count_ones(), 4);
+        assert!(bitmap.ones().eq([0, 63, 64, 129]));
+
+        let bits: Vec<bool> = bitmap.iter().collect();
+        assert_eq!(bits.len(), 130);
+        assert_eq!(bitmap.iter().len(), 130);
+        assert!(bits
+            .iter()
+            .enumerate()
+            .all(|(i, &bit)| bit == [0, 63, 64, 129].contains(&i)));
+
+        assert!(Bitmap::new(0).is_empty());
+        assert_eq!(Bitmap::new(0), Bitmap::default());
+    }
+}
@@ -0,0 +1,539 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! A ready-made item for trees using the blake3 hash function.
+//!
+//! The [`Item`] aggregates any number of children, making the module usable
+//! with trees of any arity, and [`KNOWN_ANSWERS`] holds the roots of several
+//! trees for checking other implementations against.
+
+use ::blake3::{Hash as Blake3Hash, Hasher};
+use dusk_bytes::{Error as BytesError, Serializable};
+
+use crate::{Aggregate, LeafHash};
+
+/// The prefix of the bytes hashed for a leaf.
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of the children hashed for an inner node.
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// An alias for a tree containing blake3 [`Item`]s.
+pub type Tree<const H: usize, const A: usize> = crate::Tree<Item, H, A>;
+
+/// An alias for an opening of a tree containing blake3 [`Item`]s.
+pub type Opening<const H: usize, const A: usize> = crate::Opening<Item, H, A>;
+
+/// The arity of a [`LogTree`].
+pub const LOG_TREE_ARITY: usize = 2;
+
+/// The recommended height of a [`LogTree`], giving a capacity of `2^32`
+/// entries.
+pub const LOG_TREE_HEIGHT: usize = 32;
+
+/// A binary tree of log entries, such as the ones of an append-only
+/// transparency log.
+///
+/// Empty subtrees of every height are represented by the zero hash, meaning
+/// the zero hash table of the tree is [`Item::EMPTY_SUBTREE`] at every level.
+///
+/// [`Item::EMPTY_SUBTREE`]: Aggregate::EMPTY_SUBTREE
+pub type LogTree<const H: usize = LOG_TREE_HEIGHT> = Tree<H, LOG_TREE_ARITY>;
+
+/// An opening of a [`LogTree`].
+pub type LogOpening<const H: usize = LOG_TREE_HEIGHT> =
+    Opening<H, LOG_TREE_ARITY>;
+
+/// The roots of empty subtrees of a [`LogTree`], indexed by the number of
+/// levels below the root of the subtree.
+///
+/// The first entry is [`Item::EMPTY_SUBTREE`], and each following one is the
+/// aggregate of [`LOG_TREE_ARITY`] copies of the previous one - i.e. the root
+/// of a subtree whose leaves are all the zero hash. The table covers
+/// subtrees up to the recommended height of a [`LogTree`].
+///
+/// Only the first entry agrees with [`Item::EMPTY_SUBTREE`]. Trees of blake3
+/// [`Item`]s represent an empty subtree by the zero hash whatever its height,
+/// rather than by the matching entry of this table: an empty subtree is then
+/// never hashed, which is what makes sparse trees of this height practical,
+/// and changing it would change the root of every existing tree. The table is
+/// instead meant for interoperating with implementations that hash zero
+/// leaves all the way up.
+///
+/// [`Item::EMPTY_SUBTREE`]: Aggregate::EMPTY_SUBTREE
+pub const EMPTY_ROOTS: [Item; LOG_TREE_HEIGHT + 1] = [
+    Item::new([
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ]),
+    Item::new([
+        0x48, 0x43, 0x55, 0xd8, 0x06, 0x74, 0xe2, 0x90, 0xec, 0xbc, 0xa3, 0xd2,
+        0xa2, 0x9c, 0x94, 0x89, 0x12, 0x53, 0x7b, 0xbf, 0xcd, 0xd9, 0x30, 0xae,
+        0xaa, 0xf6, 0x03, 0x7d, 0x1a, 0x98, 0xf7, 0xc0,
+    ]),
+    Item::new([
+        0xdf, 0x29, 0x16, 0xb4, 0xd7, 0xfd, 0x80, 0x0b, 0xe8, 0x84, 0xf7, 0x66,
+        0x98, 0x08, 0x6d, 0x2f, 0x65, 0xc0, 0xf4, 0x9b, 0x87, 0xff, 0xd2, 0x38,
+        0x0c, 0x98, 0xf1, 0xb2, 0xb7, 0xa9, 0x81, 0x0d,
+    ]),
+    Item::new([
+        0x1e, 0xef, 0x53, 0x58, 0x46, 0xf6, 0x50, 0xe2, 0xb5, 0xd1, 0x21, 0xff,
+        0x0f, 0x87, 0xca, 0x4f, 0x4f, 0xf9, 0xc9, 0x49, 0x2b, 0xe9, 0x91, 0x69,
+        0xd1, 0xe6, 0xfe, 0x03, 0xa3, 0xae, 0x28, 0x46,
+    ]),
+    Item::new([
+        0x61, 0xee, 0x71, 0x07, 0x64, 0x7a, 0x5b, 0x92, 0x2b, 0x06, 0xa3, 0xdf,
+        0x8a, 0xce, 0x8d, 0x29, 0x6d, 0x4e, 0x0c, 0x17, 0x89, 0xcd, 0x4e, 0x5e,
+        0x52, 0xed, 0x99, 0x5f, 0xe7, 0x36, 0x90, 0x52,
+    ]),
+    Item::new([
+        0x66, 0x67, 0x5d, 0xe6, 0xc6, 0x49, 0x4c, 0x81, 0xce, 0xe0, 0x58, 0xf7,
+        0x60, 0xfc, 0xe2, 0x87, 0x36, 0x58, 0xac, 0x7f, 0x6f, 0x9c, 0x34, 0xb5,
+        0x8a, 0x51, 0x87, 0x59, 0x3a, 0x2e, 0x72, 0xef,
+    ]),
+    Item::new([
+        0x0c, 0xbc, 0x16, 0x13, 0x62, 0xa4, 0x52, 0xdd, 0x89, 0x6b, 0x66, 0x6d,
+        0xd0, 0xa5, 0x55, 0xa1, 0xc3, 0x58, 0x08, 0x12, 0xac, 0x60, 0x17, 0xb2,
+        0x73, 0x52, 0x43, 0x97, 0x5a, 0x22, 0xa6, 0x2e,
+    ]),
+    Item::new([
+        0xfb, 0x10, 0xa4, 0xfc, 0xe5, 0x6d, 0x98, 0xab, 0xf3, 0xb1, 0x7c, 0xb0,
+        0xc9, 0xd3, 0xe7, 0xe1, 0xcd, 0xb7, 0xc8, 0xa2, 0x53, 0x48, 0xd3, 0x63,
+        0x20, 0x9e, 0xd1, 0xe0, 0x8c, 0x45, 0x87, 0xa0,
+    ]),
+    Item::new([
+        0x2b, 0x1f, 0xb5, 0xb3, 0x3f, 0xc5, 0x24, 0x55, 0x75, 0x81, 0x8d, 0xca,
+        0x9c, 0x87, 0x5e, 0xee, 0x98, 0xa4, 0xb4, 0x2a, 0x34, 0xc7, 0x8d, 0xfd,
+        0xec, 0x81, 0xc1, 0xa7, 0xa3, 0xae, 0x38, 0xaf,
+    ]),
+    Item::new([
+        0x9e, 0x07, 0x4c, 0xcf, 0xcc, 0x98, 0x05, 0x65, 0xe1, 0x6d, 0x99, 0x89,
+        0x8f, 0x0b, 0xd3, 0xb3, 0x38, 0xff, 0x03, 0x7c, 0x3e, 0xa0, 0xf0, 0x5b,
+        0x19, 0x40, 0x51, 0xec, 0x25, 0x94, 0x81, 0x88,
+    ]),
+    Item::new([
+        0xb4, 0xc5, 0xfd, 0x83, 0x39, 0xe3, 0x49, 0x02, 0xc7, 0x14, 0xa9, 0x93,
+        0xe1, 0x5c, 0x1a, 0x45, 0x7d, 0x85, 0x7e, 0xc7, 0x57, 0x53, 0xdc, 0x94,
+        0xf4, 0x4c, 0xb3, 0xff, 0x00, 0xb2, 0xe1, 0x0b,
+    ]),
+    Item::new([
+        0x5b, 0x15, 0x3a, 0x9c, 0xc2, 0x6b, 0x98, 0xd5, 0x81, 0x20, 0xfc, 0x57,
+        0x6c, 0x80, 0xc3, 0x8a, 0xd1, 0x62, 0x01, 0x12, 0x3b, 0xae, 0x32, 0x78,
+        0xed, 0xd9, 0x99, 0x8f, 0x70, 0xb8, 0x2b, 0x7c,
+    ]),
+    Item::new([
+        0x91, 0x15, 0xb1, 0x2f, 0x6e, 0x60, 0x78, 0xfc, 0x0f, 0xd2, 0x2f, 0x34,
+        0x07, 0x80, 0x9f, 0xcf, 0x29, 0x36, 0x0d, 0x4e, 0x46, 0x0c, 0x6c, 0x4e,
+        0xa3, 0x5c, 0x05, 0x12, 0x2f, 0x22, 0x1f, 0xbf,
+    ]),
+    Item::new([
+        0xe0, 0x58, 0x94, 0x7b, 0xe4, 0xb4, 0x78, 0x57, 0xa2, 0xbd, 0xb9, 0x9e,
+        0xb4, 0xd7, 0x15, 0x8a, 0x15, 0x09, 0x54, 0x03, 0x07, 0x47, 0x55, 0x91,
+        0xc3, 0xfa, 0xfc, 0xf7, 0xfd, 0xc3, 0xe2, 0x96,
+    ]),
+    Item::new([
+        0x5c, 0x5f, 0x6f, 0x4b, 0x6b, 0x79, 0xe5, 0x8a, 0xdf, 0x5f, 0x56, 0x26,
+        0x39, 0xe2, 0x85, 0xa4, 0x6e, 0xa1, 0xea, 0x62, 0x28, 0xd4, 0x6e, 0xcd,
+        0x1b, 0x4d, 0xac, 0x56, 0xfc, 0x48, 0xe7, 0xfc,
+    ]),
+    Item::new([
+        0x7f, 0x39, 0x55, 0x70, 0x3f, 0x48, 0x93, 0x99, 0xb6, 0xb4, 0xc0, 0x5d,
+        0x94, 0xd9, 0x58, 0xfe, 0x42, 0xc4, 0xcc, 0x14, 0x7d, 0xc0, 0xd8, 0xb3,
+        0x8d, 0x31, 0xb3, 0x54, 0x9b, 0xe4, 0x64, 0x96,
+    ]),
+    Item::new([
+        0x63, 0x21, 0xcd, 0x4d, 0xbe, 0xb1, 0x20, 0x92, 0x5d, 0x54, 0xc2, 0xe3,
+        0x25, 0x2c, 0x92, 0x57, 0xf6, 0x15, 0xbe, 0xca, 0x00, 0x43, 0x2d, 0x9b,
+        0x95, 0xf9, 0x71, 0x0b, 0x77, 0x2d, 0xa0, 0x55,
+    ]),
+    Item::new([
+        0x44, 0xc8, 0xc7, 0x58, 0x1a, 0x9c, 0xe7, 0xb2, 0x4d, 0xee, 0x5e, 0x90,
+        0xda, 0xc0, 0xde, 0x54, 0x37, 0xd3, 0xb4, 0x88, 0xdd, 0x87, 0x7a, 0x29,
+        0x4d, 0x0d, 0x53, 0x1b, 0x90, 0x5c, 0x16, 0x82,
+    ]),
+    Item::new([
+        0xf2, 0xba, 0x02, 0x95, 0x30, 0x19, 0x77, 0xf6, 0xb0, 0x15, 0x1a, 0xde,
+        0xc4, 0xc1, 0x6c, 0x1e, 0x3d, 0x85, 0xc2, 0xfa, 0x7e, 0xbc, 0xdb, 0xfe,
+        0x80, 0x6b, 0x34, 0x33, 0xca, 0x35, 0xe2, 0x96,
+    ]),
+    Item::new([
+        0x4f, 0x3c, 0x3d, 0xb0, 0x5c, 0x3a, 0xe6, 0x40, 0x7a, 0x68, 0x60, 0x5f,
+        0x67, 0x9f, 0x1e, 0xb3, 0x47, 0xe0, 0xf7, 0x00, 0x36, 0xf9, 0x73, 0x84,
+        0xf4, 0x68, 0x59, 0x36, 0xeb, 0x0d, 0x40, 0xcf,
+    ]),
+    Item::new([
+        0xc8, 0x3d, 0xce, 0x58, 0x7f, 0xd8, 0x1a, 0xb5, 0x70, 0x48, 0x3b, 0x49,
+        0xf8, 0x3b, 0x96, 0x1e, 0xa4, 0xf3, 0xdf, 0xd0, 0xc5, 0x5e, 0xe7, 0xfe,
+        0x08, 0xc5, 0xbd, 0x3e, 0x60, 0xe7, 0xd2, 0x43,
+    ]),
+    Item::new([
+        0x00, 0x87, 0xc0, 0xde, 0x93, 0x6a, 0x7c, 0x9f, 0x66, 0xde, 0x13, 0x44,
+        0x0e, 0xc2, 0xf2, 0xe3, 0xa3, 0x41, 0xa9, 0x08, 0xd9, 0xc5, 0xf4, 0xa9,
+        0x97, 0x26, 0x14, 0x36, 0x3d, 0x65, 0xc2, 0x9b,
+    ]),
+    Item::new([
+        0xc0, 0x16, 0x4f, 0xea, 0x60, 0xda, 0xa4, 0x13, 0x47, 0x75, 0x84, 0x68,
+        0x5e, 0x56, 0x5e, 0x46, 0x15, 0xd4, 0x07, 0xe6, 0xf6, 0x6e, 0xd1, 0x2d,
+        0xe9, 0x80, 0x2b, 0x34, 0x1b, 0xde, 0x33, 0x44,
+    ]),
+    Item::new([
+        0x4b, 0x51, 0x6b, 0x2b, 0x38, 0x63, 0x74, 0xa9, 0xf3, 0x8d, 0xe4, 0x75,
+        0xba, 0x01, 0x52, 0xd5, 0xfc, 0xf3, 0xd1, 0x77, 0xc5, 0x09, 0x39, 0x32,
+        0x60, 0x5d, 0x80, 0xa7, 0x6c, 0x75, 0x21, 0xb2,
+    ]),
+    Item::new([
+        0xbc, 0x28, 0x72, 0x17, 0x8e, 0x2a, 0x3e, 0x4e, 0xc2, 0xf6, 0x76, 0xfa,
+        0xed, 0x34, 0x17, 0xb4, 0xc8, 0x19, 0x84, 0x67, 0xa7, 0x63, 0xc5, 0x17,
+        0x4c, 0x82, 0x37, 0x67, 0x4d, 0x9f, 0x9a, 0x55,
+    ]),
+    Item::new([
+        0x40, 0x6c, 0x1a, 0x34, 0xae, 0xf9, 0x95, 0xdb, 0x46, 0x55, 0x91, 0x77,
+        0x35, 0x6e, 0xe3, 0xf1, 0x58, 0xc0, 0xe5, 0x53, 0x03, 0x7b, 0xd2, 0x95,
+        0x9f, 0x1d, 0x78, 0x6d, 0xc9, 0x08, 0x0a, 0xe7,
+    ]),
+    Item::new([
+        0x9d, 0x99, 0x7b, 0x3e, 0x20, 0xf2, 0xad, 0xfa, 0xf9, 0xb1, 0x58, 0xe0,
+        0x0c, 0xab, 0xcc, 0x49, 0xcc, 0x20, 0xa5, 0xcc, 0x10, 0x44, 0x90, 0x96,
+        0x60, 0x8e, 0xf0, 0x26, 0x75, 0x25, 0x65, 0x25,
+    ]),
+    Item::new([
+        0xe9, 0x2d, 0x01, 0x0d, 0x62, 0x2f, 0x96, 0xcf, 0x17, 0xcb, 0x07, 0xba,
+        0x1a, 0x60, 0x34, 0x54, 0x71, 0x90, 0xe7, 0x94, 0xe0, 0xc8, 0x78, 0x83,
+        0xb2, 0xb1, 0x4c, 0xa1, 0xac, 0x3d, 0x0a, 0x68,
+    ]),
+    Item::new([
+        0x95, 0x34, 0xe3, 0xb4, 0x40, 0xbc, 0xa9, 0x04, 0x42, 0x4f, 0xa7, 0xee,
+        0x54, 0xba, 0x48, 0x6e, 0x2e, 0xef, 0x30, 0xca, 0x27, 0xc5, 0x32, 0x84,
+        0xf4, 0xf4, 0x96, 0x5a, 0x26, 0x95, 0x53, 0x9b,
+    ]),
+    Item::new([
+        0xab, 0x14, 0xde, 0xf3, 0x28, 0xfa, 0x34, 0x42, 0xf9, 0x55, 0xf4, 0x2e,
+        0x6d, 0xb8, 0x0f, 0x01, 0x15, 0x73, 0xc9, 0x62, 0x21, 0xfd, 0x83, 0x6c,
+        0xed, 0xe1, 0x43, 0x25, 0x79, 0x4f, 0x08, 0x61,
+    ]),
+    Item::new([
+        0xe8, 0x8a, 0x43, 0x73, 0x50, 0xc9, 0x40, 0x98, 0x77, 0xc8, 0x82, 0x0f,
+        0xc8, 0xb4, 0xee, 0xd4, 0xec, 0xd9, 0x36, 0x4e, 0x1c, 0xaf, 0xec, 0x3a,
+        0x06, 0x35, 0xf3, 0x76, 0x75, 0x38, 0x31, 0x0c,
+    ]),
+    Item::new([
+        0x3d, 0x89, 0x4e, 0x2a, 0xab, 0x2b, 0x53, 0x82, 0x16, 0x21, 0x65, 0xad,
+        0x92, 0x6b, 0x3e, 0xba, 0x2b, 0x4a, 0x2d, 0xa3, 0x7e, 0x26, 0xce, 0xcc,
+        0xcd, 0x16, 0x78, 0xc0, 0x1a, 0x8f, 0x26, 0x29,
+    ]),
+    Item::new([
+        0xae, 0xb8, 0xa8, 0x89, 0x87, 0xd2, 0x6f, 0x4b, 0xe0, 0x93, 0x73, 0x87,
+        0xc2, 0xb9, 0x1b, 0x94, 0xb6, 0x6d, 0xa6, 0x8b, 0x78, 0x12, 0x11, 0x4e,
+        0x5e, 0xaa, 0x62, 0xa6, 0x4b, 0x69, 0xb1, 0xd7,
+    ]),
+];
+
+/// A blake3 hash, aggregated by hashing the concatenation of the children's
+/// hashes, prefixed with [`NODE_PREFIX`].
+///
+/// Empty subtrees are represented by the zero hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive_attr(derive(bytecheck::CheckBytes))
+)]
+pub struct Item([u8; 32]);
+
+impl Item {
+    /// Create a new item from the given hash `bytes`.
+    #[must_use]
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Create the item for a leaf holding the given `bytes`, by hashing them
+    /// prefixed with [`LEAF_PREFIX`].
+    #[must_use]
+    pub fn leaf(bytes: &[u8]) -> Self {
+        let mut hasher = Hasher::new();
+        hasher.update(&[LEAF_PREFIX]);
+        hasher.update(bytes);
+        hasher.finalize().into()
+    }
+
+    /// Returns the bytes of the hash.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<Blake3Hash> for Item {
+    fn from(h: Blake3Hash) -> Self {
+        Self(h.into())
+    }
+}
+
+impl<const A: usize> Aggregate<A> for Item {
+    const EMPTY_SUBTREE: Self = Item([0; 32]);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        let mut hasher = Hasher::new();
+        hasher.update(&[NODE_PREFIX]);
+        for item in items {
+            hasher.update(&item.0);
+        }
+        hasher.finalize().into()
+    }
+}
+
+impl Serializable<32> for Item {
+    type Error = BytesError;
+
+    fn from_bytes(buf: &[u8; 32]) -> Result<Self, Self::Error> {
+        Ok(Self(*buf))
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+/// A known-answer root of a tree of blake3 [`Item`]s, for checking other
+/// implementations against.
+///
+/// The tree has the given `height` and `arity`, and holds the first `leaves`
+/// positions, with the leaf at each position `i` being [`KnownAnswer::leaf`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KnownAnswer {
+    /// The height of the tree.
+    pub height: usize,
+    /// The arity of the tree.
+    pub arity: usize,
+    /// The number of leaves, at the positions starting from zero.
+    pub leaves: u64,
+    /// The bytes of the root of the tree.
+    pub root: [u8; 32],
+}
+
+impl KnownAnswer {
+    /// Returns the leaf at the given `position` of the known-answer trees,
+    /// i.e. the hash of the little-endian bytes of the position.
+    #[must_use]
+    pub fn leaf(position: u64) -> Item {
+        Item::leaf(&position.to_le_bytes())
+    }
+}
+
+/// The known-answer roots of trees of blake3 [`Item`]s, for several heights
+/// and arities.
+///
+/// The empty subtrees are the zero hash at every height, whatever the
+/// arity, so the root of an empty tree is always the zero hash and needs no
+/// vector.
+pub const KNOWN_ANSWERS: &[KnownAnswer] = &[
+    KnownAnswer {
+        height: 4,
+        arity: 2,
+        leaves: 1,
+        root: [
+            0xdb, 0x23, 0x23, 0xaa, 0xde, 0x85, 0x88, 0x8a, 0x22, 0x3b, 0xa7,
+            0x2b, 0xd9, 0x0d, 0x98, 0xfe, 0x98, 0xcf, 0x5d, 0x6a, 0x57, 0x18,
+            0x7e, 0xb2, 0xd9, 0x6b, 0x7e, 0xe3, 0xc6, 0x21, 0x5a, 0x7b,
+        ],
+    },
+    KnownAnswer {
+        height: 4,
+        arity: 2,
+        leaves: 5,
+        root: [
+            0x42, 0xc2, 0xd1, 0xa4, 0x79, 0xf6, 0x3c, 0x38, 0x48, 0xe4, 0xef,
+            0x7b, 0x79, 0xb8, 0x15, 0x7e, 0x0f, 0x69, 0xa7, 0xc1, 0xc6, 0xf7,
+            0x43, 0xe3, 0x53, 0xee, 0xb4, 0xe7, 0xd4, 0x9c, 0x15, 0x31,
+        ],
+    },
+    KnownAnswer {
+        height: 4,
+        arity: 2,
+        leaves: 16,
+        root: [
+            0xec, 0x56, 0xb4, 0x4b, 0xdd, 0x32, 0x6d, 0x55, 0xf8, 0x9a, 0x3d,
+            0xcb, 0x73, 0x6e, 0x2f, 0x19, 0x6f, 0x0b, 0x3a, 0xa0, 0xd0, 0xf9,
+            0xe9, 0x74, 0x51, 0x32, 0x68, 0x8e, 0xc2, 0x78, 0xbd, 0x30,
+        ],
+    },
+    KnownAnswer {
+        height: 3,
+        arity: 4,
+        leaves: 7,
+        root: [
+            0xa4, 0x84, 0xf5, 0xd4, 0x54, 0xb8, 0x5d, 0x8a, 0x5e, 0x38, 0x27,
+            0xd2, 0xe9, 0xfd, 0x65, 0x7d, 0x55, 0x5d, 0x7a, 0xec, 0x5b, 0xfe,
+            0x2c, 0x1c, 0x89, 0xcf, 0x7b, 0xbc, 0x8a, 0x36, 0x2d, 0xfc,
+        ],
+    },
+    KnownAnswer {
+        height: 8,
+        arity: 4,
+        leaves: 100,
+        root: [
+            0x24, 0xba, 0x11, 0xe6, 0xe0, 0xf3, 0xa1, 0x3d, 0x4a, 0x1f, 0xea,
+            0x33, 0xd2, 0x04, 0xf6, 0xb0, 0x1a, 0x00, 0xf4, 0xe4, 0x9e, 0x8d,
+            0xb8, 0xaf, 0x72, 0x14, 0x37, 0x2a, 0x47, 0xf5, 0x68, 0x8e,
+        ],
+    },
+    KnownAnswer {
+        height: 5,
+        arity: 3,
+        leaves: 243,
+        root: [
+            0x93, 0x2b, 0xd0, 0x70, 0x90, 0x31, 0x95, 0x56, 0x0f, 0x46, 0x4f,
+            0xe4, 0x65, 0x89, 0x82, 0x8e, 0x81, 0xc1, 0xcc, 0x48, 0xcb, 0x2d,
+            0xd4, 0xf1, 0xee, 0xe2, 0xb6, 0x6f, 0xe7, 0x8a, 0xdb, 0xa0,
+        ],
+    },
+    KnownAnswer {
+        height: 8,
+        arity: 16,
+        leaves: 17,
+        root: [
+            0x9a, 0xe1, 0x35, 0x03, 0xc9, 0xe9, 0xa3, 0xd2, 0x3b, 0x23, 0x58,
+            0x82, 0xf5, 0xe4, 0x57, 0x49, 0x22, 0x62, 0x23, 0xad, 0x3b, 0xab,
+            0x99, 0xbc, 0xa3, 0xbe, 0x34, 0x93, 0xbe, 0xf4, 0xaa, 0x84,
+        ],
+    },
+    KnownAnswer {
+        height: 32,
+        arity: 2,
+        leaves: 3,
+        root: [
+            0x9f, 0x80, 0x8e, 0x11, 0xa4, 0x1c, 0xe3, 0xbd, 0x49, 0x66, 0xd7,
+            0xfe, 0x6d, 0x8f, 0x71, 0xff, 0x52, 0x83, 0x4d, 0xb5, 0xb9, 0xb0,
+            0x6c, 0xde, 0x32, 0x7e, 0xc4, 0x19, 0x0d, 0x7b, 0x06, 0xb7,
+        ],
+    },
+];
+
+impl LeafHash<32> for Item {
+    fn leaf(bytes: &[u8]) -> Self {
+        Self::leaf(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_bytes() {
+        let mut tree = Tree::<8, 4>::new();
+        tree.insert_bytes(3, b"hello");
+        tree.insert_bytes(42, b"world");
+
+        let opening = tree.opening(42).unwrap();
+        assert!(opening.verify(Item::leaf(b"world")));
+        assert!(!opening.verify(Item::leaf(b"hello")));
+
+        let mut other = Tree::<8, 4>::new();
+        other.insert(3, Item::from(::blake3::hash(b"\x00hello")));
+        other.insert(42, Item::from(::blake3::hash(b"\x00world")));
+        assert_eq!(*tree.root(), *other.root());
+    }
+
+    #[test]
+    fn verify_raw() {
+        let mut tree = Tree::<8, 4>::new();
+        tree.insert_bytes(42, b"world");
+
+        let root = tree.root().to_bytes();
+        let proof = tree.opening(42).unwrap().to_var_bytes();
+        let world = Item::leaf(b"world").to_bytes();
+        let hello = Item::leaf(b"hello").to_bytes();
+
+        assert_eq!(Item::verify_raw::<8, 4>(&root, &proof, &world), Ok(true));
+        assert_eq!(Item::verify_raw::<8, 4>(&root, &proof, &hello), Ok(false));
+    }
+
+    #[test]
+    fn domain_separation() {
+        let leaf = Item::leaf(b"leaf");
+        let node = <Item as Aggregate<2>>::aggregate([&leaf, &leaf]);
+
+        let mut bytes = [NODE_PREFIX; 65];
+        bytes[1..33].copy_from_slice(leaf.as_bytes());
+        bytes[33..].copy_from_slice(leaf.as_bytes());
+        assert_eq!(node, Item::from(::blake3::hash(&bytes)));
+        assert_ne!(node, Item::leaf(&bytes[1..]));
+    }
+
+    fn known_answer_root<const H: usize, const A: usize>(
+        answer: &KnownAnswer,
+    ) -> Item {
+        assert_eq!((answer.height, answer.arity), (H, A));
+
+        let mut tree = Tree::<H, A>::new();
+        assert_eq!(*tree.root(), Item::new([0; 32]));
+
+        for position in 0..answer.leaves {
+            tree.insert(position, KnownAnswer::leaf(position));
+        }
+        let root = *tree.root();
+        root
+    }
+
+    #[test]
+    fn known_answers() {
+        for answer in KNOWN_ANSWERS {
+            let root = match (answer.height, answer.arity) {
+                (4, 2) => known_answer_root::<4, 2>(answer),
+                (3, 4) => known_answer_root::<3, 4>(answer),
+                (8, 4) => known_answer_root::<8, 4>(answer),
+                (5, 3) => known_answer_root::<5, 3>(answer),
+                (8, 16) => known_answer_root::<8, 16>(answer),
+                (32, 2) => known_answer_root::<32, 2>(answer),
+                _ => unreachable!("all parameters should be covered"),
+            };
+            assert_eq!(
+                root.as_bytes(),
+                &answer.root,
+                "root mismatch for height {} and arity {}",
+                answer.height,
+                answer.arity
+            );
+        }
+    }
+
+    #[test]
+    fn empty_roots() {
+        let empty = <Item as Aggregate<LOG_TREE_ARITY>>::EMPTY_SUBTREE;
+        assert_eq!(EMPTY_ROOTS[0], empty);
+
+        // an empty tree is the empty subtree, and not the hash of empty leaves
+        let tree = LogTree::<3>::new();
+        assert_eq!(*tree.root(), empty);
+        assert_ne!(*tree.root(), EMPTY_ROOTS[3]);
+
+        let mut root = empty;
+        for empty_root in EMPTY_ROOTS {
+            assert_eq!(empty_root, root);
+            root = <Item as Aggregate<LOG_TREE_ARITY>>::aggregate([&root; 2]);
+        }
+
+        let mut tree = LogTree::<3>::new();
+        for position in 0..tree.capacity() {
+            tree.insert(position, Item::new([0; 32]));
+        }
+        assert_eq!(*tree.root(), EMPTY_ROOTS[3]);
+    }
+
+    #[test]
+    fn log_tree() {
+        let mut tree = LogTree::<LOG_TREE_HEIGHT>::new();
+        assert_eq!(tree.capacity(), 1 << 32);
+        assert_eq!(*tree.root(), Item::new([0; 32]));
+
+        tree.insert_bytes(0, b"first entry");
+        let opening: LogOpening = tree.opening(0).unwrap();
+        assert!(opening.verify(Item::leaf(b"first entry")));
+    }
+}
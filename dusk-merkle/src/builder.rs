@@ -0,0 +1,231 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#[cfg(feature = "rand")]
+use rand_core::RngCore;
+
+use crate::allocator::{random_below, SplitMix64};
+use crate::{Aggregate, Tree};
+
+/// A builder for trees, meant for setting up fixtures in tests.
+///
+/// Leaves can be given explicitly, or filled at random positions with random
+/// items - either from a given random number generator, or deterministically
+/// from a seed.
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, TreeBuilder};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl From<u64> for Sum {
+///     fn from(n: u64) -> Self {
+///         Sum(n)
+///     }
+/// }
+///
+/// impl Aggregate<2> for Sum {
+///     const EMPTY_SUBTREE: Self = Sum(0);
+///
+///     fn aggregate(items: [&Self; 2]) -> Self {
+///         Sum(items[0].0.wrapping_add(items[1].0))
+///     }
+/// }
+///
+/// let tree = TreeBuilder::<Sum, 8, 2>::new()
+///     .with_leaf(3, Sum(5))
+///     .with_seeded_leaves(10, 0xbeef)
+///     .build();
+/// assert_eq!(tree.len(), 11);
+///
+/// let same = TreeBuilder::<Sum, 8, 2>::new()
+///     .with_leaf(3, Sum(5))
+///     .with_seeded_leaves(10, 0xbeef)
+///     .build();
+/// assert_eq!(*tree.root(), *same.root());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeBuilder<T, const H: usize, const A: usize> {
+    tree: Tree<T, H, A>,
+}
+
+impl<T, const H: usize, const A: usize> Default for TreeBuilder<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const H: usize, const A: usize> TreeBuilder<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Create a new builder for an empty tree.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { tree: Tree::new() }
+    }
+
+    /// Insert an `item` at the given `position`.
+    ///
+    /// # Panics
+    /// If `position >= capacity`.
+    #[must_use]
+    pub fn with_leaf(mut self, position: u64, item: impl Into<T>) -> Self {
+        self.tree.insert(position, item);
+        self
+    }
+
+    /// Insert the given `leaves`, each given as a position and an item.
+    ///
+    /// # Panics
+    /// If any of the positions is larger or equal to the capacity.
+    #[must_use]
+    pub fn with_leaves<I, U>(mut self, leaves: I) -> Self
+    where
+        I: IntoIterator<Item = (u64, U)>,
+        U: Into<T>,
+    {
+        for (position, item) in leaves {
+            self.tree.insert(position, item);
+        }
+        self
+    }
+
+    /// Insert `n` leaves at random unoccupied positions, with items created
+    /// from random numbers, using the given `rng`.
+    ///
+    /// # Panics
+    /// If there are less than `n` unoccupied positions.
+    #[cfg(feature = "rand")]
+    #[must_use]
+    pub fn with_random_leaves<R>(self, n: u64, rng: &mut R) -> Self
+    where
+        T: From<u64>,
+        R: RngCore,
+    {
+        self.with_leaves_from(n, || rng.next_u64())
+    }
+
+    /// Insert `n` leaves at random unoccupied positions, like
+    /// `with_random_leaves`, using a deterministic random number generator
+    /// seeded with the given `seed`.
+    ///
+    /// The same seed always produces the same leaves, across platforms and
+    /// versions of the crate.
+    ///
+    /// # Panics
+    /// If there are less than `n` unoccupied positions.
+    #[must_use]
+    pub fn with_seeded_leaves(self, n: u64, seed: u64) -> Self
+    where
+        T: From<u64>,
+    {
+        let mut rng = SplitMix64(seed);
+        self.with_leaves_from(n, || rng.next_u64())
+    }
+
+    /// Insert `n` leaves at random unoccupied positions, drawing both the
+    /// positions and the items from the given `next_u64` function.
+    fn with_leaves_from<F>(mut self, n: u64, mut next_u64: F) -> Self
+    where
+        T: From<u64>,
+        F: FnMut() -> u64,
+    {
+        let capacity = self.tree.capacity();
+        let free = capacity - self.tree.len();
+        assert!(
+            n <= free,
+            "not enough free positions: {n} requested but {free} are free"
+        );
+
+        for _ in 0..n {
+            let mut position = random_below(&mut next_u64, capacity);
+            while self.tree.contains(position) {
+                position = random_below(&mut next_u64, capacity);
+            }
+            self.tree.insert(position, T::from(next_u64()));
+        }
+        self
+    }
+
+    /// Returns the built tree.
+    #[must_use]
+    pub fn build(self) -> Tree<T, H, A> {
+        self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Count(u64);
+
+    impl From<u64> for Count {
+        fn from(_: u64) -> Self {
+            Self(1)
+        }
+    }
+
+    impl Aggregate<4> for Count {
+        const EMPTY_SUBTREE: Self = Count(0);
+
+        fn aggregate(items: [&Self; 4]) -> Self {
+            Self(items.into_iter().map(|c| c.0).sum())
+        }
+    }
+
+    type Builder = TreeBuilder<Count, 3, 4>;
+
+    #[test]
+    fn tree_builder() {
+        let tree = Builder::new()
+            .with_leaf(0, Count(1))
+            .with_leaves([(1, 1), (2, 1)])
+            .with_seeded_leaves(20, 0xbeef)
+            .build();
+        assert_eq!(tree.len(), 23);
+        assert_eq!(*tree.root(), Count(23));
+
+        // filling the whole tree
+        let tree = Builder::new().with_seeded_leaves(64, 42).build();
+        assert_eq!(*tree.root(), Count(64));
+
+        let a = Builder::new().with_seeded_leaves(10, 42).build();
+        let b = Builder::new().with_seeded_leaves(10, 42).build();
+        let c = Builder::new().with_seeded_leaves(10, 43).build();
+        assert!(a.positions().eq(b.positions()));
+        assert!(!a.positions().eq(c.positions()));
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn tree_builder_random() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let tree = Builder::new()
+            .with_leaf(0, Count(1))
+            .with_random_leaves(20, &mut StdRng::seed_from_u64(0xbeef))
+            .build();
+        assert_eq!(tree.len(), 21);
+        assert_eq!(*tree.root(), Count(21));
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough free positions")]
+    fn tree_builder_too_many_leaves() {
+        let _ = Builder::new()
+            .with_leaf(0, Count(1))
+            .with_seeded_leaves(64, 42);
+    }
+}
@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+/// The policy determining which of the lazily computed items of the inner
+/// nodes of a [`Tree`] are kept once computed.
+///
+/// Heights are counted from the root, which is at height zero. The root is
+/// always kept, and the items of the leaves are never discarded, since they
+/// are the data held by the tree.
+///
+/// Discarding items saves memory at the cost of recomputing them when they
+/// are needed again, which makes sense for verifiers that only ever need the
+/// root.
+///
+/// [`Tree`]: crate::Tree
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachePolicy {
+    /// Keep the items of all nodes.
+    #[default]
+    Always,
+    /// Keep only the root, discarding the items of all other inner nodes.
+    Never,
+    /// Keep the items of the nodes at heights smaller than the given one,
+    /// i.e. in the given number of top levels of the tree.
+    AboveHeight(usize),
+}
+
+impl CachePolicy {
+    /// Returns true if the item of an inner node at the given `height` is
+    /// kept once computed.
+    #[must_use]
+    pub const fn caches(&self, height: usize) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => height == 0,
+            Self::AboveHeight(k) => height == 0 || height < *k,
+        }
+    }
+}
@@ -0,0 +1,238 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! The cells holding the lazily computed state of a tree.
+//!
+//! By default these are [`RefCell`]s, making a tree cheap to query but not
+//! [`Sync`]. With the `sync` feature they are locks instead, allowing for a
+//! tree to be queried concurrently from multiple threads.
+
+#[cfg(not(feature = "sync"))]
+use core::cell::RefCell;
+use core::fmt;
+#[cfg(feature = "sync")]
+use core::ops::Deref;
+#[cfg(feature = "sync")]
+use std::sync::{Mutex, PoisonError, RwLock, RwLockReadGuard, TryLockError};
+
+/// A reference to an item of a tree.
+///
+/// This is a [`core::cell::Ref`], unless the `sync` feature is enabled, in
+/// which case it is a guard holding a read lock on the item.
+#[cfg(not(feature = "sync"))]
+pub use core::cell::Ref;
+
+/// A reference to an item of a tree, holding a read lock on it.
+///
+/// The item can't be recomputed while a reference to it is held, so
+/// references should be dropped as soon as they are no longer needed.
+#[cfg(feature = "sync")]
+pub struct Ref<'a, T>(RwLockReadGuard<'a, Option<T>>);
+
+#[cfg(feature = "sync")]
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self.0.as_ref() {
+            Some(item) => item,
+            None => unreachable!("references are only taken to computed items"),
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T> fmt::Debug for Ref<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A cell holding the item of a node, if it is computed.
+pub(crate) struct ItemCell<T> {
+    #[cfg(not(feature = "sync"))]
+    item: RefCell<Option<T>>,
+    #[cfg(feature = "sync")]
+    item: RwLock<Option<T>>,
+}
+
+impl<T> ItemCell<T> {
+    pub(crate) const fn new(item: Option<T>) -> Self {
+        Self {
+            #[cfg(not(feature = "sync"))]
+            item: RefCell::new(item),
+            #[cfg(feature = "sync")]
+            item: RwLock::new(item),
+        }
+    }
+
+    /// Calls the given closure with the item of the cell, if it is computed.
+    pub(crate) fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Option<T>) -> R,
+    {
+        #[cfg(not(feature = "sync"))]
+        let item = self.item.borrow();
+        #[cfg(feature = "sync")]
+        let item = self.item.read().unwrap_or_else(PoisonError::into_inner);
+
+        f(&item)
+    }
+
+    /// Returns a reference to the item of the cell, if it is computed.
+    pub(crate) fn get(&self) -> Option<Ref<'_, T>> {
+        #[cfg(not(feature = "sync"))]
+        let item = Ref::filter_map(self.item.borrow(), Option::as_ref).ok();
+        #[cfg(feature = "sync")]
+        let item = {
+            let item = self.item.read().unwrap_or_else(PoisonError::into_inner);
+            item.is_some().then(|| Ref(item))
+        };
+
+        item
+    }
+
+    /// Sets the item of the cell, through a shared reference.
+    pub(crate) fn set(&self, item: Option<T>) {
+        #[cfg(not(feature = "sync"))]
+        self.item.replace(item);
+        #[cfg(feature = "sync")]
+        {
+            *self.item.write().unwrap_or_else(PoisonError::into_inner) = item;
+        }
+    }
+
+    /// Discards the item of the cell through a shared reference, unless it
+    /// is currently borrowed - in which case it is kept.
+    pub(crate) fn evict(&self) {
+        #[cfg(not(feature = "sync"))]
+        if let Ok(mut item) = self.item.try_borrow_mut() {
+            *item = None;
+        }
+        #[cfg(feature = "sync")]
+        match self.item.try_write() {
+            Ok(mut item) => *item = None,
+            Err(TryLockError::Poisoned(err)) => *err.into_inner() = None,
+            Err(TryLockError::WouldBlock) => {}
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut Option<T> {
+        #[cfg(not(feature = "sync"))]
+        let item = self.item.get_mut();
+        #[cfg(feature = "sync")]
+        let item = self.item.get_mut().unwrap_or_else(PoisonError::into_inner);
+
+        item
+    }
+
+    pub(crate) fn into_inner(self) -> Option<T> {
+        #[cfg(not(feature = "sync"))]
+        let item = self.item.into_inner();
+        #[cfg(feature = "sync")]
+        let item = self
+            .item
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        item
+    }
+}
+
+impl<T> Clone for ItemCell<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.with(Clone::clone))
+    }
+}
+
+impl<T> PartialEq for ItemCell<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.with(|item| other.with(|other| item == other))
+    }
+}
+
+impl<T> Eq for ItemCell<T> where T: Eq {}
+
+impl<T> fmt::Debug for ItemCell<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with(|item| f.debug_tuple("ItemCell").field(item).finish())
+    }
+}
+
+/// A cell holding a cache of a tree, that may be updated through a shared
+/// reference.
+#[derive(Default)]
+pub(crate) struct CacheCell<T> {
+    #[cfg(not(feature = "sync"))]
+    cache: RefCell<T>,
+    #[cfg(feature = "sync")]
+    cache: Mutex<T>,
+}
+
+impl<T> CacheCell<T> {
+    pub(crate) const fn new(cache: T) -> Self {
+        Self {
+            #[cfg(not(feature = "sync"))]
+            cache: RefCell::new(cache),
+            #[cfg(feature = "sync")]
+            cache: Mutex::new(cache),
+        }
+    }
+
+    /// Calls the given closure with a mutable reference to the cache.
+    pub(crate) fn with_mut<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        #[cfg(not(feature = "sync"))]
+        let mut cache = self.cache.borrow_mut();
+        #[cfg(feature = "sync")]
+        let mut cache =
+            self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+        f(&mut cache)
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        #[cfg(not(feature = "sync"))]
+        let cache = self.cache.get_mut();
+        #[cfg(feature = "sync")]
+        let cache =
+            self.cache.get_mut().unwrap_or_else(PoisonError::into_inner);
+
+        cache
+    }
+}
+
+impl<T> Clone for CacheCell<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.with_mut(|cache| cache.clone()))
+    }
+}
+
+impl<T> fmt::Debug for CacheCell<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_mut(|cache| f.debug_tuple("CacheCell").field(cache).finish())
+    }
+}
@@ -0,0 +1,316 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::{trace, Aggregate, Tree};
+
+/// The identifier of a checkpoint of a [`Tree`].
+///
+/// Created using [`Tree::checkpoint`], and consumed by [`Tree::rollback`] or
+/// [`Tree::commit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CheckpointId(u64);
+
+/// The leaves changed since each checkpoint of a tree, together with the
+/// items they held before.
+#[derive(Debug, Clone)]
+pub(crate) struct Journal<T> {
+    // stored when the first checkpoint is recorded, so that the mutations of
+    // the tree need not require `T: Clone`
+    clone: fn(&T) -> T,
+    next_id: u64,
+    checkpoints: Vec<Checkpoint<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Checkpoint<T> {
+    id: u64,
+    leaves: BTreeMap<u64, Option<T>>,
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Record a checkpoint of the leaves of the tree, to which the tree can
+    /// later be restored with [`rollback`].
+    ///
+    /// While there are checkpoints, the first change to each leaf records the
+    /// item it held before, meaning a checkpoint costs memory proportional to
+    /// the number of leaves changed after it, and never a copy of the tree.
+    /// Checkpoints can be nested, and are kept until they are either rolled
+    /// back or [`commit`]ted.
+    ///
+    /// [`rollback`]: Tree::rollback
+    /// [`commit`]: Tree::commit
+    pub fn checkpoint(&mut self) -> CheckpointId
+    where
+        T: Clone,
+    {
+        let journal = self.journal.get_or_insert_with(|| Journal {
+            clone: T::clone,
+            next_id: 0,
+            checkpoints: Vec::new(),
+        });
+
+        let id = journal.next_id;
+        journal.next_id += 1;
+        journal.checkpoints.push(Checkpoint {
+            id,
+            leaves: BTreeMap::new(),
+        });
+
+        CheckpointId(id)
+    }
+
+    /// Restore the leaves of the tree to the ones it held when the checkpoint
+    /// with the given `id` was recorded, discarding it together with any
+    /// checkpoint recorded after it.
+    ///
+    /// Only the leaves changed since the checkpoint are restored, and only
+    /// the items of their ancestors are recomputed the next time the root is
+    /// computed. The generation of the tree is incremented as with any other
+    /// mutation, so openings produced since the checkpoint are rejected as
+    /// stale.
+    ///
+    /// # Panics
+    /// If the checkpoint was already rolled back or committed.
+    pub fn rollback(&mut self, id: CheckpointId) {
+        let checkpoints = self.split_checkpoints(id);
+
+        trace::span!("rollback", checkpoints = checkpoints.len());
+
+        let mut positions = Vec::new();
+
+        // undo the latest checkpoints first, since the items they recorded
+        // were set after the earlier ones
+        for checkpoint in checkpoints.into_iter().rev() {
+            for (position, leaf) in checkpoint.leaves {
+                match leaf {
+                    Some(item) => {
+                        self.root.insert(0, position, item);
+                        self.positions.insert(position);
+                    }
+                    None => {
+                        if self.positions.remove(&position) {
+                            self.root.remove(0, position);
+                        }
+                    }
+                }
+                positions.push(position);
+            }
+        }
+
+        if positions.is_empty() {
+            return;
+        }
+
+        positions.sort_unstable();
+        positions.dedup();
+
+        self.generation = self.generation.wrapping_add(1);
+        self.invalidate_openings(&positions);
+    }
+
+    /// Commit the changes made since the checkpoint with the given `id`,
+    /// discarding it together with any checkpoint recorded after it.
+    ///
+    /// The changes are kept in the tree, and can then only be undone by
+    /// rolling back an earlier checkpoint, if any. If there is no earlier
+    /// checkpoint, the root of the tree is recorded in its recent roots when
+    /// they are tracked - see [`track_roots`].
+    ///
+    /// [`track_roots`]: Tree::track_roots
+    ///
+    /// # Panics
+    /// If the checkpoint was already rolled back or committed.
+    pub fn commit(&mut self, id: CheckpointId) {
+        let checkpoints = self.split_checkpoints(id);
+
+        let Some(journal) = &mut self.journal else {
+            unreachable!("the checkpoints were split off the journal");
+        };
+
+        // the earliest checkpoints hold the earliest items of the leaves
+        // they share with the later ones
+        if let Some(parent) = journal.checkpoints.last_mut() {
+            for checkpoint in checkpoints {
+                for (position, leaf) in checkpoint.leaves {
+                    parent.leaves.entry(position).or_insert(leaf);
+                }
+            }
+            return;
+        }
+
+        // committing the outermost checkpoint ends a batch of mutations
+        let clone = journal.clone;
+        self.record_root(clone);
+    }
+
+    /// Returns the number of checkpoints recorded, and neither rolled back
+    /// nor committed.
+    #[must_use]
+    pub fn checkpoints(&self) -> usize {
+        self.journal
+            .as_ref()
+            .map_or(0, |journal| journal.checkpoints.len())
+    }
+
+    /// Removes the checkpoint with the given `id`, and the ones recorded after
+    /// it, from the journal, returning them in the order they were recorded.
+    ///
+    /// # Panics
+    /// If there is no checkpoint with the given `id`.
+    fn split_checkpoints(&mut self, id: CheckpointId) -> Vec<Checkpoint<T>> {
+        let index = self.journal.as_ref().and_then(|journal| {
+            journal
+                .checkpoints
+                .binary_search_by_key(&id.0, |checkpoint| checkpoint.id)
+                .ok()
+        });
+
+        match (&mut self.journal, index) {
+            (Some(journal), Some(index)) => {
+                journal.checkpoints.split_off(index)
+            }
+            _ => panic!("checkpoint {} was already discarded", id.0),
+        }
+    }
+
+    /// Records the item of the leaf at the given `position` in the latest
+    /// checkpoint, unless it was already changed since.
+    ///
+    /// Must be called before each mutation of a leaf.
+    pub(crate) fn record_leaf(&mut self, position: u64) {
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        let Some(checkpoint) = journal.checkpoints.last_mut() else {
+            return;
+        };
+
+        if let Entry::Vacant(entry) = checkpoint.leaves.entry(position) {
+            let leaf = self
+                .root
+                .descendant(H, position)
+                .map(|leaf| (journal.clone)(&leaf.item()));
+            entry.insert(leaf);
+        }
+    }
+
+    /// Records the items of the leaves at the given `positions`, like
+    /// [`record_leaf`](Tree::record_leaf).
+    pub(crate) fn record_leaves<'a, I>(&mut self, positions: I)
+    where
+        I: IntoIterator<Item = &'a u64>,
+    {
+        if self.checkpoints() == 0 {
+            return;
+        }
+        for &position in positions {
+            self.record_leaf(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Opening;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Item(u64);
+
+    impl Aggregate<2> for Item {
+        const EMPTY_SUBTREE: Self = Item(0);
+
+        fn aggregate(items: [&Self; 2]) -> Self {
+            Item(items[0].0.wrapping_mul(31).wrapping_add(items[1].0 + 1))
+        }
+    }
+
+    type TestTree = Tree<Item, 4, 2>;
+
+    fn leaves(tree: &TestTree) -> Vec<(u64, Item)> {
+        tree.positions()
+            .map(|position| (position, *tree.leaf(position).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn checkpoint_rollback() {
+        let mut tree = TestTree::new();
+        tree.insert(1, Item(1));
+        tree.insert(7, Item(7));
+
+        let root = *tree.root();
+        let before = leaves(&tree);
+
+        let outer = tree.checkpoint();
+        tree.insert(1, Item(10));
+        tree.insert(3, Item(3));
+
+        let inner = tree.checkpoint();
+        tree.remove(7);
+        tree.insert(3, Item(30));
+        tree.update(1, |item| item.0 += 1);
+        assert_eq!(tree.checkpoints(), 2);
+
+        let opening: Opening<Item, 4, 2> = tree.opening(1).unwrap();
+        let generation = tree.generation();
+
+        tree.rollback(inner);
+        assert_eq!(tree.checkpoints(), 1);
+        assert_eq!(leaves(&tree), [(1, Item(10)), (3, Item(3)), (7, Item(7))]);
+        assert!(tree.generation() > generation);
+        assert!(!opening.verify(Item(10)));
+        assert!(tree.opening(1).unwrap().verify(Item(10)));
+
+        tree.rollback(outer);
+        assert_eq!(tree.checkpoints(), 0);
+        assert_eq!(leaves(&tree), before);
+        assert_eq!(*tree.root(), root);
+    }
+
+    #[test]
+    fn checkpoint_commit() {
+        let mut tree = TestTree::new();
+        tree.insert(2, Item(2));
+
+        let outer = tree.checkpoint();
+        tree.insert(5, Item(5));
+
+        let inner = tree.checkpoint();
+        tree.insert(2, Item(20));
+        tree.insert(5, Item(50));
+        tree.commit(inner);
+        assert_eq!(leaves(&tree), [(2, Item(20)), (5, Item(50))]);
+
+        // the committed changes are undone by rolling back the outer one
+        tree.rollback(outer);
+        assert_eq!(leaves(&tree), [(2, Item(2))]);
+
+        let checkpoint = tree.checkpoint();
+        tree.insert(9, Item(9));
+        tree.commit(checkpoint);
+        assert_eq!(tree.checkpoints(), 0);
+        assert_eq!(leaves(&tree), [(2, Item(2)), (9, Item(9))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "checkpoint 0 was already discarded")]
+    fn checkpoint_discarded() {
+        let mut tree = TestTree::new();
+
+        let checkpoint = tree.checkpoint();
+        tree.rollback(checkpoint);
+        tree.rollback(checkpoint);
+    }
+}
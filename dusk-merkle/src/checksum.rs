@@ -0,0 +1,176 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+use crate::Aggregate;
+
+/// A 64-bit checksum computed using the [`Hasher`] `S`, aggregated by
+/// hashing the checksums of the children.
+///
+/// This allows for building lightweight integrity trees - e.g. for tests or
+/// caches - without a cryptographic hash function. The hashers in `core` and
+/// `std` are not collision resistant, and such trees must not be used where
+/// an adversary may choose the leaves.
+///
+/// Empty subtrees are represented by a zero checksum.
+///
+/// # Example
+/// ```rust
+/// use std::collections::hash_map::DefaultHasher;
+///
+/// use dusk_merkle::{Checksum, Tree};
+///
+/// type Item = Checksum<DefaultHasher>;
+///
+/// let mut tree = Tree::<Item, 16, 2>::new();
+/// tree.insert(42, Item::of(&"hello"));
+///
+/// let opening = tree.opening(42).unwrap();
+/// assert!(opening.verify(Item::of(&"hello")));
+/// assert!(!opening.verify(Item::of(&"world")));
+/// ```
+pub struct Checksum<S> {
+    value: u64,
+    _hasher: PhantomData<fn() -> S>,
+}
+
+impl<S> Checksum<S> {
+    /// Create a new checksum with the given `value`.
+    #[must_use]
+    pub const fn new(value: u64) -> Self {
+        Self {
+            value,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Returns the value of the checksum.
+    #[must_use]
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+impl<S> Checksum<S>
+where
+    S: Hasher + Default,
+{
+    /// Create the checksum of the given `value`, by hashing it with a new
+    /// hasher.
+    pub fn of<V>(value: &V) -> Self
+    where
+        V: Hash + ?Sized,
+    {
+        let mut hasher = S::default();
+        value.hash(&mut hasher);
+        Self::new(hasher.finish())
+    }
+}
+
+impl<S, const A: usize> Aggregate<A> for Checksum<S>
+where
+    S: Hasher + Default,
+{
+    const EMPTY_SUBTREE: Self = Self::new(0);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        let mut hasher = S::default();
+        for item in items {
+            hasher.write_u64(item.value);
+        }
+        Self::new(hasher.finish())
+    }
+}
+
+// The traits are implemented manually, since deriving them would require the
+// hasher to implement them as well.
+
+impl<S> fmt::Debug for Checksum<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Checksum").field(&self.value).finish()
+    }
+}
+
+impl<S> Clone for Checksum<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Checksum<S> {}
+
+impl<S> PartialEq for Checksum<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<S> Eq for Checksum<S> {}
+
+impl<S> Hash for Checksum<S> {
+    fn hash<HS: Hasher>(&self, state: &mut HS) {
+        self.value.hash(state);
+    }
+}
+
+impl<S> From<u64> for Checksum<S> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Tree;
+
+    /// The 64-bit FNV-1a hash function.
+    struct Fnv(u64);
+
+    impl Default for Fnv {
+        fn default() -> Self {
+            Self(0xcbf2_9ce4_8422_2325)
+        }
+    }
+
+    impl Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 ^= u64::from(*byte);
+                self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    type Item = Checksum<Fnv>;
+
+    #[test]
+    fn checksum_tree() {
+        let mut tree = Tree::<Item, 4, 4>::new();
+        assert_eq!(*tree.root(), Item::new(0));
+
+        tree.insert(3, Item::of(b"hello"));
+        tree.insert(42, Item::of(b"world"));
+
+        let opening = tree.opening(42).unwrap();
+        assert!(opening.verify(Item::of(b"world")));
+        assert!(!opening.verify(Item::of(b"hello")));
+
+        // the aggregation is sensitive to the order of the children
+        let mut swapped = Tree::<Item, 4, 4>::new();
+        swapped.insert(3, Item::of(b"world"));
+        swapped.insert(42, Item::of(b"hello"));
+        assert_ne!(*tree.root(), *swapped.root());
+    }
+}
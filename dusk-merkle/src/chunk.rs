@@ -0,0 +1,411 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "rkyv-impl")]
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{capacity, trace, Aggregate, RangeOpening, Tree};
+
+/// The bound on the size of the chunks a tree is split into by
+/// [`Tree::chunk_ranges`] and [`Tree::chunks`].
+///
+/// Every chunk holds at least one leaf, even if the bound is smaller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkBound {
+    /// The maximum number of leaves in a chunk.
+    Leaves(u64),
+    /// The maximum serialized size of the leaves in a chunk, given the
+    /// serialized size of each leaf.
+    Bytes {
+        /// The maximum size of a chunk, in bytes.
+        max: u64,
+        /// The size of a serialized leaf, in bytes.
+        leaf_size: u64,
+    },
+}
+
+impl ChunkBound {
+    /// Returns the maximum number of leaves in a chunk.
+    #[must_use]
+    pub const fn max_leaves(&self) -> u64 {
+        let max = match *self {
+            Self::Leaves(max) => max,
+            Self::Bytes { max, leaf_size } => match leaf_size {
+                0 => u64::MAX,
+                leaf_size => max / leaf_size,
+            },
+        };
+        if max == 0 {
+            1
+        } else {
+            max
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Split the positions of the tree into contiguous ranges, each holding
+    /// as many leaves as the given `bound` allows.
+    ///
+    /// The ranges cover all the positions of the tree, from zero up to its
+    /// capacity, meaning a peer receiving all of them knows it holds the
+    /// whole tree. Each range ends where the first leaf of the next one is,
+    /// and an empty tree is covered by a single range.
+    #[must_use]
+    pub fn chunk_ranges(&self, bound: ChunkBound) -> Vec<Range<u64>> {
+        let max_leaves =
+            usize::try_from(bound.max_leaves()).unwrap_or(usize::MAX);
+
+        let mut ranges = Vec::new();
+        let mut start = 0;
+
+        // every chunk but the first starts at the leaf following the last
+        // leaf of the previous one
+        for &next in self.positions.iter().skip(max_leaves).step_by(max_leaves)
+        {
+            ranges.push(start..next);
+            start = next;
+        }
+        ranges.push(start..self.capacity());
+
+        ranges
+    }
+
+    /// Returns an iterator over the chunks of the tree bounded by the given
+    /// `bound`, each as a [`RangeOpening`] proving its leaves against the
+    /// root.
+    ///
+    /// The chunks are independent of each other, allowing for them to be
+    /// distributed to - and verified by - several peers in parallel. The
+    /// chunks cover the positions given by [`chunk_ranges`].
+    ///
+    /// [`chunk_ranges`]: Tree::chunk_ranges
+    pub fn chunks(
+        &self,
+        bound: ChunkBound,
+    ) -> impl Iterator<Item = RangeOpening<T, H, A>> + '_
+    where
+        T: Clone,
+    {
+        self.chunk_ranges(bound).into_iter().map(|range| {
+            let Some(opening) = self.opening_range(range.start, range.end)
+            else {
+                unreachable!("chunk ranges are non-empty and within capacity");
+            };
+            opening
+        })
+    }
+}
+
+/// The leaves of a subtree of a merkle tree, together with a proof linking
+/// them to the root of the tree.
+///
+/// Chunks allow for a tree to be synchronized subtree by subtree, e.g. by a
+/// node catching up to the state of its peers. Each chunk can be verified on
+/// its own against the root, and proves it holds all the leaves of its
+/// subtree, meaning importing it with [`Tree::import_chunk`] replaces the
+/// subtree as a whole.
+///
+/// Created using [`Tree::export_chunk`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(Archive, Serialize, Deserialize),
+    archive_attr(derive(CheckBytes))
+)]
+pub struct Chunk<T, const H: usize, const A: usize> {
+    height: usize,
+    index: u64,
+    opening: RangeOpening<T, H, A>,
+}
+
+impl<T, const H: usize, const A: usize> Chunk<T, H, A> {
+    /// Returns the height of the subtree of the chunk, with the subtree at
+    /// height zero being the whole tree.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the index of the subtree of the chunk within its height.
+    #[must_use]
+    pub const fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the root of the tree the chunk was exported from.
+    #[must_use]
+    pub const fn root(&self) -> &T {
+        self.opening.root()
+    }
+
+    /// Returns the leaves of the subtree, together with their positions, in
+    /// ascending order.
+    #[must_use]
+    pub fn leaves(&self) -> &[(u64, T)] {
+        self.opening.leaves()
+    }
+
+    /// Returns the [`RangeOpening`] proving the leaves of the subtree.
+    #[must_use]
+    pub const fn opening(&self) -> &RangeOpening<T, H, A> {
+        &self.opening
+    }
+
+    /// Returns the positions covered by the subtree of the chunk, or `None` if
+    /// its height or index are out of bounds for the tree.
+    #[must_use]
+    pub const fn range(&self) -> Option<Range<u64>> {
+        let Some(depth) = H.checked_sub(self.height) else {
+            return None;
+        };
+        if self.index >= capacity(A as u64, self.height) {
+            return None;
+        }
+
+        let len = capacity(A as u64, depth);
+        let Some(start) = self.index.checked_mul(len) else {
+            return None;
+        };
+        let Some(end) = start.checked_add(len) else {
+            return None;
+        };
+
+        Some(start..end)
+    }
+
+    /// Verify the leaves of the chunk are all the leaves of its subtree, and
+    /// that the chunk is cryptographically correct.
+    pub fn verify(&self) -> bool
+    where
+        T: Aggregate<A> + Clone + PartialEq,
+    {
+        let Some(range) = self.range() else {
+            return false;
+        };
+        self.opening.start() == range.start
+            && self.opening.end() == range.end
+            && self.opening.verify()
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// Export the subtree at the given `height` and `index` within that
+    /// height as a [`Chunk`], holding its leaves and a proof linking them to
+    /// the root of the tree.
+    ///
+    /// # Panics
+    /// If `height > H`, or the index is out of bounds for the height.
+    pub fn export_chunk(&self, height: usize, index: u64) -> Chunk<T, H, A> {
+        assert!(height <= H, "height out of bounds: the height is {H}");
+        let level_cap = capacity(A as u64, height);
+        assert!(
+            index < level_cap,
+            "index out of bounds: \
+             the level capacity is {level_cap} but the index is {index}"
+        );
+
+        trace::span!("export_chunk", height, index);
+
+        let len = capacity(A as u64, H - height);
+        let opening = RangeOpening::new(self, index * len, (index + 1) * len);
+
+        Chunk {
+            height,
+            index,
+            opening,
+        }
+    }
+
+    /// Import the given `chunk`, replacing the leaves of its subtree with the
+    /// ones of the chunk, provided it verifies against the given `root`.
+    ///
+    /// Once every chunk covering the tree is imported, the tree has the
+    /// given root. Chunks can be imported in any order.
+    ///
+    /// # Errors
+    /// If the chunk is for another root, or doesn't verify, a [`ChunkError`]
+    /// is returned and the tree is left untouched.
+    pub fn import_chunk(
+        &mut self,
+        chunk: &Chunk<T, H, A>,
+        root: &T,
+    ) -> Result<(), ChunkError>
+    where
+        T: PartialEq,
+    {
+        if chunk.root() != root {
+            return Err(ChunkError::RootMismatch);
+        }
+        if !chunk.verify() {
+            return Err(ChunkError::Unverified);
+        }
+
+        trace::span!(
+            "import_chunk",
+            height = chunk.height,
+            index = chunk.index,
+            leaves = chunk.leaves().len()
+        );
+
+        let Some(range) = chunk.range() else {
+            unreachable!("verified chunks are within the bounds of the tree");
+        };
+        let stale: Vec<u64> = self.positions.range(range).copied().collect();
+        self.remove_batch(stale);
+        for (position, leaf) in chunk.leaves() {
+            self.insert(*position, leaf.clone());
+        }
+
+        Ok(())
+    }
+}
+
+/// The error returned when importing a [`Chunk`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkError {
+    /// The chunk was exported from a tree with another root.
+    RootMismatch,
+    /// The chunk doesn't prove the leaves of its subtree.
+    Unverified,
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RootMismatch => write!(f, "root mismatch"),
+            Self::Unverified => write!(f, "verification failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::Sum;
+    use crate::TreeBuilder;
+
+    const H: usize = 3;
+    const A: usize = 2;
+
+    #[test]
+    fn chunks() {
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves([1, 2, 3, 5, 6].map(|position| (position, Sum(1))))
+            .build();
+        let whole = 0..tree.capacity();
+
+        assert_eq!(
+            tree.chunk_ranges(ChunkBound::Leaves(2)),
+            [0..3, 3..6, 6..8]
+        );
+        assert_eq!(
+            tree.chunk_ranges(ChunkBound::Leaves(5)),
+            core::slice::from_ref(&whole)
+        );
+        assert_eq!(
+            tree.chunk_ranges(ChunkBound::Bytes {
+                max: 40,
+                leaf_size: 16
+            }),
+            tree.chunk_ranges(ChunkBound::Leaves(2))
+        );
+        assert_eq!(
+            tree.chunk_ranges(ChunkBound::Leaves(0)),
+            [0..2, 2..3, 3..5, 5..6, 6..8]
+        );
+
+        let chunks: Vec<_> = tree.chunks(ChunkBound::Leaves(2)).collect();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(RangeOpening::verify));
+        assert_eq!(chunks[1].leaves(), [(3, Sum(1)), (5, Sum(1))]);
+
+        let empty = Tree::<Sum, H, A>::new();
+        assert_eq!(empty.chunk_ranges(ChunkBound::Leaves(2)), [whole]);
+        assert!(empty.chunks(ChunkBound::Leaves(2)).all(|c| c.verify()));
+    }
+
+    #[test]
+    fn export_import_chunk() {
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves(
+                [1, 2, 3, 5, 6].map(|position| (position, Sum(position))),
+            )
+            .build();
+        let root = *tree.root();
+
+        let chunk = tree.export_chunk(1, 1);
+        assert_eq!(chunk.range(), Some(4..8));
+        assert_eq!(chunk.root(), &root);
+        assert_eq!(chunk.leaves(), [(5, Sum(5)), (6, Sum(6))]);
+        assert!(chunk.verify());
+
+        // stale leaves in the subtree are replaced, others are kept
+        let mut synced = Tree::<Sum, H, A>::new();
+        synced.insert(4, Sum(4));
+        synced.insert(5, Sum(50));
+        synced.insert(0, Sum(0));
+        assert_eq!(synced.import_chunk(&chunk, &root), Ok(()));
+        assert!(!synced.contains(4));
+        assert_eq!(*synced.leaf(5).unwrap(), Sum(5));
+        assert_eq!(*synced.leaf(0).unwrap(), Sum(0));
+
+        let mut synced = Tree::<Sum, H, A>::new();
+        for index in 0..4 {
+            let chunk = tree.export_chunk(2, index);
+            assert_eq!(synced.import_chunk(&chunk, &root), Ok(()));
+        }
+        assert_eq!(*synced.root(), root);
+        assert!(synced.diff(&tree).next().is_none());
+
+        let mut empty = Tree::<Sum, H, A>::new();
+        assert_eq!(
+            empty.import_chunk(&chunk, &Sum(0)),
+            Err(ChunkError::RootMismatch)
+        );
+        let mut forged = chunk.clone();
+        forged.index = 0;
+        assert_eq!(
+            empty.import_chunk(&forged, &root),
+            Err(ChunkError::Unverified)
+        );
+        assert!(empty.is_empty());
+
+        // chunks out of the bounds of the tree are rejected
+        let mut forged = chunk.clone();
+        forged.height = H + 1;
+        assert_eq!(forged.range(), None);
+        assert!(!forged.verify());
+        assert_eq!(
+            empty.import_chunk(&forged, &root),
+            Err(ChunkError::Unverified)
+        );
+        let mut forged = chunk;
+        forged.index = u64::MAX;
+        assert_eq!(forged.range(), None);
+        assert_eq!(
+            empty.import_chunk(&forged, &root),
+            Err(ChunkError::Unverified)
+        );
+        assert!(empty.is_empty());
+    }
+}
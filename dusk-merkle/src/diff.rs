@@ -0,0 +1,205 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::{Aggregate, Node, Tree};
+
+/// A position where two trees differ, as yielded by [`Tree::diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiffEntry<T> {
+    /// A leaf present only in this tree.
+    Ours {
+        /// The position of the leaf.
+        position: u64,
+        /// The leaf in this tree.
+        item: T,
+    },
+    /// A leaf present only in the other tree.
+    Theirs {
+        /// The position of the leaf.
+        position: u64,
+        /// The leaf in the other tree.
+        item: T,
+    },
+    /// A leaf present in both trees, with different items.
+    Changed {
+        /// The position of the leaf.
+        position: u64,
+        /// The leaf in this tree.
+        ours: T,
+        /// The leaf in the other tree.
+        theirs: T,
+    },
+}
+
+impl<T> DiffEntry<T> {
+    /// Returns the position of the entry.
+    pub const fn position(&self) -> u64 {
+        match self {
+            Self::Ours { position, .. }
+            | Self::Theirs { position, .. }
+            | Self::Changed { position, .. } => *position,
+        }
+    }
+}
+
+/// A pair of nodes at the same height and index in two trees, at least one
+/// of which exists.
+type NodePair<'a, T, const H: usize, const A: usize> = (
+    usize,
+    u64,
+    Option<&'a Node<T, H, A>>,
+    Option<&'a Node<T, H, A>>,
+);
+
+/// Iterator over the positions where two trees differ, in ascending order.
+///
+/// Created using [`Tree::diff`].
+#[derive(Debug)]
+pub struct Diff<'a, T, const H: usize, const A: usize> {
+    // The pairs of nodes left to compare, with the next one on top.
+    stack: Vec<NodePair<'a, T, H, A>>,
+}
+
+impl<'a, T, const H: usize, const A: usize> Iterator for Diff<'a, T, H, A>
+where
+    T: Aggregate<A> + Clone + PartialEq,
+{
+    type Item = DiffEntry<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((height, index, ours, theirs)) = self.stack.pop() {
+            // subtrees with equal items are assumed to be identical
+            if let (Some(ours), Some(theirs)) = (ours, theirs) {
+                if *ours.item() == *theirs.item() {
+                    continue;
+                }
+            }
+
+            if height == H {
+                let entry = match (ours, theirs) {
+                    (Some(ours), Some(theirs)) => DiffEntry::Changed {
+                        position: index,
+                        ours: ours.item().clone(),
+                        theirs: theirs.item().clone(),
+                    },
+                    (Some(ours), None) => DiffEntry::Ours {
+                        position: index,
+                        item: ours.item().clone(),
+                    },
+                    (None, Some(theirs)) => DiffEntry::Theirs {
+                        position: index,
+                        item: theirs.item().clone(),
+                    },
+                    (None, None) => unreachable!("one of the nodes exists"),
+                };
+                return Some(entry);
+            }
+
+            // pushed in reverse, so the children are popped in order
+            for i in (0..A).rev() {
+                let child = |node: Option<&'a Node<T, H, A>>| {
+                    node.and_then(|node| node.children[i].as_deref())
+                };
+                let (ours, theirs) = (child(ours), child(theirs));
+                if ours.is_some() || theirs.is_some() {
+                    let index = index * A as u64 + i as u64;
+                    self.stack.push((height + 1, index, ours, theirs));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<T, const H: usize, const A: usize> FusedIterator for Diff<'_, T, H, A> where
+    T: Aggregate<A> + Clone + PartialEq
+{
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A> + Clone + PartialEq,
+{
+    /// Returns an iterator over the positions where this tree and the
+    /// `other` differ, in ascending order.
+    ///
+    /// Both trees are descended simultaneously, and subtrees with equal
+    /// items are assumed to be identical and skipped, meaning only the paths
+    /// to the differing leaves are visited. This computes the items of the
+    /// nodes visited in both trees, if they are not already.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<'a, T, H, A> {
+        Diff {
+            stack: alloc::vec![(0, 0, Some(&self.root), Some(&other.root))],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Item(u64);
+
+    impl Aggregate<2> for Item {
+        const EMPTY_SUBTREE: Self = Item(0);
+
+        fn aggregate(items: [&Self; 2]) -> Self {
+            Item(items[0].0.wrapping_mul(31).wrapping_add(items[1].0 + 1))
+        }
+    }
+
+    type TestTree = Tree<Item, 4, 2>;
+
+    #[test]
+    fn diff() {
+        let mut ours = TestTree::new();
+        let mut theirs = TestTree::new();
+        for position in [0, 3, 5, 9, 14] {
+            ours.insert(position, Item(position));
+            theirs.insert(position, Item(position));
+        }
+        assert_eq!(ours.diff(&theirs).next(), None);
+
+        ours.insert(1, Item(1));
+        ours.insert(9, Item(90));
+        theirs.remove(14);
+        theirs.insert(15, Item(15));
+
+        let diff: Vec<_> = ours.diff(&theirs).collect();
+        assert_eq!(
+            diff,
+            [
+                DiffEntry::Ours {
+                    position: 1,
+                    item: Item(1)
+                },
+                DiffEntry::Changed {
+                    position: 9,
+                    ours: Item(90),
+                    theirs: Item(9)
+                },
+                DiffEntry::Ours {
+                    position: 14,
+                    item: Item(14)
+                },
+                DiffEntry::Theirs {
+                    position: 15,
+                    item: Item(15)
+                },
+            ]
+        );
+
+        // the diff is symmetric
+        let positions: Vec<_> =
+            theirs.diff(&ours).map(|entry| entry.position()).collect();
+        assert_eq!(positions, [1, 9, 14, 15]);
+    }
+}
@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! An item for trees using any hash function implementing the `RustCrypto`
+//! [`Digest`] trait, such as blake2 or sha3.
+//!
+//! Leaves and inner nodes are domain separated as specified by [RFC 6962],
+//! as in the `sha2` module: leaves are hashed prefixed with a `0x00` byte,
+//! and the children of inner nodes are hashed prefixed with a `0x01` byte.
+//! Empty subtrees are represented by the zero hash.
+//!
+//! [RFC 6962]: https://www.rfc-editor.org/rfc/rfc6962#section-2.1
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+use ::digest::typenum::Unsigned;
+use ::digest::Digest;
+use dusk_bytes::{Error as BytesError, Serializable};
+
+use crate::{Aggregate, LeafHash};
+
+/// The prefix of the bytes hashed for a leaf.
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of the children hashed for an inner node.
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// An alias for a tree containing [`HashItem`]s of the hash function `D`,
+/// with outputs of `N` bytes.
+pub type Tree<D, const N: usize, const H: usize, const A: usize> =
+    crate::Tree<HashItem<D, N>, H, A>;
+
+/// An alias for an opening of a tree containing [`HashItem`]s of the hash
+/// function `D`, with outputs of `N` bytes.
+pub type Opening<D, const N: usize, const H: usize, const A: usize> =
+    crate::Opening<HashItem<D, N>, H, A>;
+
+/// A hash computed with the hash function `D`, whose output is `N` bytes
+/// long, aggregated by hashing the concatenation of the children's hashes,
+/// prefixed with [`NODE_PREFIX`].
+///
+/// Empty subtrees are represented by the zero hash. Using a hash function
+/// whose output is not `N` bytes long fails to compile.
+pub struct HashItem<D, const N: usize> {
+    bytes: [u8; N],
+    hash: PhantomData<fn() -> D>,
+}
+
+impl<D, const N: usize> HashItem<D, N>
+where
+    D: Digest,
+{
+    /// Evaluating this constant fails compilation when the output of the
+    /// hash function is not `N` bytes long.
+    const VALID_SIZE: () = assert!(
+        <D::OutputSize as Unsigned>::USIZE == N,
+        "The output size of the hash function must be N"
+    );
+
+    /// Create a new item from the given hash `bytes`.
+    #[must_use]
+    pub const fn new(bytes: [u8; N]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SIZE;
+
+        Self {
+            bytes,
+            hash: PhantomData,
+        }
+    }
+
+    /// Create the item for a leaf holding the given `bytes`, by hashing them
+    /// prefixed with [`LEAF_PREFIX`].
+    #[must_use]
+    pub fn leaf(bytes: &[u8]) -> Self {
+        let mut hasher = D::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(bytes);
+        Self::from_digest(&hasher.finalize())
+    }
+
+    /// Returns the bytes of the hash.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; N] {
+        &self.bytes
+    }
+
+    fn from_digest(output: &[u8]) -> Self {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(output);
+        Self::new(bytes)
+    }
+}
+
+impl<D, const N: usize, const A: usize> Aggregate<A> for HashItem<D, N>
+where
+    D: Digest,
+{
+    const EMPTY_SUBTREE: Self = Self::new([0; N]);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        let mut hasher = D::new();
+        hasher.update([NODE_PREFIX]);
+        for item in items {
+            hasher.update(item.bytes);
+        }
+        Self::from_digest(&hasher.finalize())
+    }
+}
+
+impl<D, const N: usize> Serializable<N> for HashItem<D, N>
+where
+    D: Digest,
+{
+    type Error = BytesError;
+
+    fn from_bytes(buf: &[u8; N]) -> Result<Self, Self::Error> {
+        Ok(Self::new(*buf))
+    }
+
+    fn to_bytes(&self) -> [u8; N] {
+        self.bytes
+    }
+}
+
+impl<D, const N: usize> fmt::Debug for HashItem<D, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HashItem").field(&self.bytes).finish()
+    }
+}
+
+impl<D, const N: usize> Clone for HashItem<D, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D, const N: usize> Copy for HashItem<D, N> {}
+
+impl<D, const N: usize> PartialEq for HashItem<D, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<D, const N: usize> Eq for HashItem<D, N> {}
+
+impl<D, const N: usize> Hash for HashItem<D, N> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.bytes.hash(state);
+    }
+}
+
+impl<D, const N: usize> LeafHash<N> for HashItem<D, N>
+where
+    D: Digest,
+{
+    fn leaf(bytes: &[u8]) -> Self {
+        Self::leaf(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sha2::{Sha256, Sha512};
+
+    type Item = HashItem<Sha256, 32>;
+
+    #[test]
+    fn domain_separation() {
+        let leaf = Item::leaf(b"hello");
+        assert_eq!(leaf.as_bytes()[..], Sha256::digest(b"\x00hello")[..]);
+
+        let node = <Item as Aggregate<2>>::aggregate([&leaf, &leaf]);
+
+        let mut bytes = [NODE_PREFIX; 65];
+        bytes[1..33].copy_from_slice(leaf.as_bytes());
+        bytes[33..].copy_from_slice(leaf.as_bytes());
+        assert_eq!(node.as_bytes()[..], Sha256::digest(bytes)[..]);
+    }
+
+    #[test]
+    fn insert_bytes() {
+        let mut tree = Tree::<Sha512, 64, 8, 4>::new();
+        tree.insert_bytes(3, b"hello");
+        tree.insert_bytes(42, b"world");
+
+        let opening = tree.opening(42).unwrap();
+        assert!(opening.verify(HashItem::leaf(b"world")));
+        assert!(!opening.verify(HashItem::leaf(b"hello")));
+    }
+}
@@ -0,0 +1,45 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+
+/// The error returned by the fallible mutations of a [`Tree`].
+///
+/// [`Tree`]: crate::Tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The position is outside the capacity of the tree.
+    OutOfBounds {
+        /// The position given.
+        position: u64,
+        /// The capacity of the tree.
+        capacity: u64,
+    },
+    /// The position is recorded as occupied, but the tree holds no leaf
+    /// there. This never happens unless the tree was corrupted, e.g. by
+    /// restoring it from a tampered archive without validation.
+    Inconsistent {
+        /// The position given.
+        position: u64,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { position, capacity } => write!(
+                f,
+                "position {position} out of bounds: the capacity is {capacity}"
+            ),
+            Self::Inconsistent { position } => {
+                write!(f, "inconsistent tree: no leaf at position {position}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
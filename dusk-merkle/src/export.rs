@@ -0,0 +1,268 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use dusk_bytes::{DeserializableSlice, Serializable};
+
+use crate::{Aggregate, Opening, OpeningError, Tree};
+
+/// A self-contained record of a leaf, holding its position, the serialized
+/// leaf, and its serialized [`Opening`].
+///
+/// Records allow for handing authenticated extracts of a tree to third
+/// parties that don't hold it, which only need to know the root to check
+/// them with [`verify_records`].
+///
+/// Created using [`Tree::export_records`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeafRecord {
+    position: u64,
+    leaf: Vec<u8>,
+    opening: Vec<u8>,
+}
+
+impl LeafRecord {
+    /// Returns the position of the leaf.
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the serialized leaf.
+    #[must_use]
+    pub fn leaf(&self) -> &[u8] {
+        &self.leaf
+    }
+
+    /// Returns the opening of the leaf, as serialized by
+    /// [`Opening::to_var_bytes`].
+    #[must_use]
+    pub fn opening(&self) -> &[u8] {
+        &self.opening
+    }
+
+    /// Serialize the record to a vector of bytes.
+    ///
+    /// The serialized record is composed of:
+    /// - the position, as a `u64`
+    /// - the length of the leaf, as a `u32`
+    /// - the leaf
+    /// - the opening
+    #[must_use]
+    pub fn to_var_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(12 + self.leaf.len() + self.opening.len());
+
+        bytes.extend(&self.position.to_bytes());
+        // leaves are serialized to arrays, which are never larger than a u32
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.extend(&(self.leaf.len() as u32).to_bytes());
+        bytes.extend(&self.leaf);
+        bytes.extend(&self.opening);
+
+        bytes
+    }
+
+    /// Deserialize a record from a slice of bytes, returning `None` if it is
+    /// too short to hold the position and the leaf.
+    ///
+    /// The leaf and the opening are not decoded until they're verified.
+    #[must_use]
+    pub fn from_slice(buf: &[u8]) -> Option<Self> {
+        let mut buf = buf;
+
+        let position = u64::from_reader(&mut buf).ok()?;
+        let leaf_len = u32::from_reader(&mut buf).ok()? as usize;
+        if buf.len() < leaf_len {
+            return None;
+        }
+        let (leaf, opening) = buf.split_at(leaf_len);
+
+        Some(Self {
+            position,
+            leaf: leaf.to_vec(),
+            opening: opening.to_vec(),
+        })
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A> + Clone + PartialEq,
+{
+    /// Returns an iterator over the [`LeafRecord`]s of all the leaves in the
+    /// tree, in ascending order of their positions.
+    pub fn export_records<const T_SIZE: usize>(
+        &self,
+    ) -> impl Iterator<Item = LeafRecord> + '_
+    where
+        T: Serializable<T_SIZE>,
+    {
+        self.positions().map(move |position| {
+            let Some(opening) = self.opening(position) else {
+                unreachable!("the position is in the tree");
+            };
+            let Some(leaf) = self.leaf(position) else {
+                unreachable!("the position is in the tree");
+            };
+
+            LeafRecord {
+                position,
+                leaf: leaf.to_bytes().to_vec(),
+                opening: opening.to_var_bytes(),
+            }
+        })
+    }
+}
+
+/// Verify the given stream of `records` against the given `root`, returning
+/// the number of records verified.
+///
+/// Each record must hold a well-formed leaf and opening, the opening must be
+/// for the given root and for the position of the record, and must verify
+/// the leaf.
+///
+/// # Errors
+/// Returns a [`RecordError`] for the first record that fails verification.
+pub fn verify_records<
+    'r,
+    T,
+    const H: usize,
+    const A: usize,
+    const T_SIZE: usize,
+    I,
+>(
+    root: &T,
+    records: I,
+) -> Result<usize, RecordError>
+where
+    T: Aggregate<A> + Serializable<T_SIZE> + Clone + PartialEq,
+    <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    I: IntoIterator<Item = &'r LeafRecord>,
+{
+    let mut count = 0;
+
+    for (index, record) in records.into_iter().enumerate() {
+        let leaf = T::from_slice(&record.leaf)
+            .map_err(|_| RecordError::InvalidLeaf { record: index })?;
+        let opening = Opening::<T, H, A>::from_slice(&record.opening).map_err(
+            |error| RecordError::InvalidOpening {
+                record: index,
+                error,
+            },
+        )?;
+
+        if opening.root() != root
+            || opening.position() != record.position
+            || !opening.verify(leaf)
+        {
+            return Err(RecordError::Unverified { record: index });
+        }
+
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// The error returned when a [`LeafRecord`] fails verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordError {
+    /// The leaf of the record could not be decoded.
+    InvalidLeaf {
+        /// The index of the record in the stream.
+        record: usize,
+    },
+    /// The opening of the record could not be decoded.
+    InvalidOpening {
+        /// The index of the record in the stream.
+        record: usize,
+        /// The error decoding the opening.
+        error: OpeningError,
+    },
+    /// The opening is not for the root or the position of the record, or
+    /// doesn't prove the leaf.
+    Unverified {
+        /// The index of the record in the stream.
+        record: usize,
+    },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLeaf { record } => {
+                write!(f, "record {record}: invalid leaf")
+            }
+            Self::InvalidOpening { record, error } => {
+                write!(f, "record {record}: invalid opening: {error}")
+            }
+            Self::Unverified { record } => {
+                write!(f, "record {record}: verification failed")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecordError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::Sum;
+    use crate::TreeBuilder;
+
+    const H: usize = 3;
+    const A: usize = 2;
+
+    fn verify(root: Sum, records: &[LeafRecord]) -> Result<usize, RecordError> {
+        verify_records::<Sum, H, A, 8, _>(&root, records)
+    }
+
+    #[test]
+    fn export_records() {
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves([(1, Sum(3)), (4, Sum(5)), (6, Sum(7))])
+            .build();
+        let root = *tree.root();
+
+        let records: Vec<LeafRecord> = tree.export_records().collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].position(), 4);
+        assert_eq!(records[1].leaf(), Sum(5).to_bytes());
+
+        let records: Vec<LeafRecord> = records
+            .iter()
+            .map(|record| LeafRecord::from_slice(&record.to_var_bytes()))
+            .collect::<Option<_>>()
+            .unwrap();
+        assert_eq!(verify(root, &records), Ok(3));
+        assert_eq!(
+            verify(Sum(14), &records),
+            Err(RecordError::Unverified { record: 0 })
+        );
+
+        // a record claiming another position is rejected
+        let mut records = records;
+        records[2].position = 7;
+        assert_eq!(
+            verify(root, &records),
+            Err(RecordError::Unverified { record: 2 })
+        );
+
+        records[1].leaf.pop();
+        assert_eq!(
+            verify(root, &records),
+            Err(RecordError::InvalidLeaf { record: 1 })
+        );
+
+        assert_eq!(LeafRecord::from_slice(&[0; 11]), None);
+    }
+}
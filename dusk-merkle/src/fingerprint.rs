@@ -0,0 +1,246 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+use dusk_bytes::{Error as BytesError, Serializable};
+#[cfg(feature = "rkyv-impl")]
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// The version of the format of serialized trees and openings.
+///
+/// This is incremented whenever a change to the format makes it
+/// incompatible with previously serialized data.
+pub const FORMAT_VERSION: u32 = 3;
+
+/// The parameters a tree was built with, allowing for openings and snapshots
+/// to be checked for compatibility with the tree they are used against.
+///
+/// An opening produced before a tree was snapshotted and restored can be
+/// safely validated against the restored tree if, and only if, their
+/// fingerprints match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(Archive, Serialize, Deserialize),
+    archive_attr(derive(CheckBytes))
+)]
+pub struct Fingerprint {
+    height: u32,
+    arity: u32,
+    backend: u32,
+    version: u32,
+}
+
+impl Fingerprint {
+    /// Create the fingerprint of a tree with height `H` and arity `A`, built
+    /// using the given `backend`, in the current [`FORMAT_VERSION`].
+    ///
+    /// The backend is an identifier chosen by the user of the tree,
+    /// distinguishing e.g. the hash function used for its items.
+    #[must_use]
+    pub const fn new<const H: usize, const A: usize>(backend: u32) -> Self {
+        // The height of a tree is checked to fit in a `u32` when it is
+        // created, and an arity that doesn't would make for unusably large
+        // nodes.
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            height: H as u32,
+            arity: A as u32,
+            backend,
+            version: FORMAT_VERSION,
+        }
+    }
+
+    /// Returns the height of the tree.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the arity of the tree.
+    #[must_use]
+    pub const fn arity(&self) -> u32 {
+        self.arity
+    }
+
+    /// Returns the identifier of the backend of the tree.
+    #[must_use]
+    pub const fn backend(&self) -> u32 {
+        self.backend
+    }
+
+    /// Returns the format version of the tree.
+    #[must_use]
+    pub const fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Check this fingerprint is the `expected` one.
+    ///
+    /// # Errors
+    /// Returns a [`FingerprintError`] describing the first parameter that
+    /// doesn't match.
+    pub fn check(&self, expected: &Self) -> Result<(), FingerprintError> {
+        let mismatch = |expected: u32, found: u32| {
+            (expected != found).then_some((expected, found))
+        };
+
+        if let Some((expected, found)) =
+            mismatch(expected.version, self.version)
+        {
+            return Err(FingerprintError::Version { expected, found });
+        }
+        if let Some((expected, found)) = mismatch(expected.height, self.height)
+        {
+            return Err(FingerprintError::Height { expected, found });
+        }
+        if let Some((expected, found)) = mismatch(expected.arity, self.arity) {
+            return Err(FingerprintError::Arity { expected, found });
+        }
+        if let Some((expected, found)) =
+            mismatch(expected.backend, self.backend)
+        {
+            return Err(FingerprintError::Backend { expected, found });
+        }
+
+        Ok(())
+    }
+}
+
+impl Serializable<16> for Fingerprint {
+    type Error = BytesError;
+
+    fn from_bytes(buf: &[u8; 16]) -> Result<Self, Self::Error> {
+        let mut fields = [0u32; 4];
+        for (field, bytes) in fields.iter_mut().zip(buf.chunks_exact(4)) {
+            *field = u32::from_le_bytes(match bytes.try_into() {
+                Ok(bytes) => bytes,
+                Err(_) => unreachable!("The chunks should be 4 bytes long"),
+            });
+        }
+
+        Ok(Self {
+            height: fields[0],
+            arity: fields[1],
+            backend: fields[2],
+            version: fields[3],
+        })
+    }
+
+    fn to_bytes(&self) -> [u8; 16] {
+        let mut buf = [0u8; 16];
+        let fields = [self.height, self.arity, self.backend, self.version];
+        for (bytes, field) in buf.chunks_exact_mut(4).zip(fields) {
+            bytes.copy_from_slice(&field.to_le_bytes());
+        }
+        buf
+    }
+}
+
+/// The error returned when a [`Fingerprint`] doesn't match the expected one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FingerprintError {
+    /// The format versions differ.
+    Version {
+        /// The expected format version.
+        expected: u32,
+        /// The format version found.
+        found: u32,
+    },
+    /// The heights differ.
+    Height {
+        /// The expected height.
+        expected: u32,
+        /// The height found.
+        found: u32,
+    },
+    /// The arities differ.
+    Arity {
+        /// The expected arity.
+        expected: u32,
+        /// The arity found.
+        found: u32,
+    },
+    /// The backends differ.
+    Backend {
+        /// The expected backend.
+        expected: u32,
+        /// The backend found.
+        found: u32,
+    },
+}
+
+impl fmt::Display for FingerprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (parameter, expected, found) = match self {
+            Self::Version { expected, found } => ("version", expected, found),
+            Self::Height { expected, found } => ("height", expected, found),
+            Self::Arity { expected, found } => ("arity", expected, found),
+            Self::Backend { expected, found } => ("backend", expected, found),
+        };
+        write!(
+            f,
+            "fingerprint mismatch: expected {parameter} {expected}, \
+             found {found}"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FingerprintError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint() {
+        let fingerprint = Fingerprint::new::<17, 4>(2);
+        assert_eq!(fingerprint.height(), 17);
+        assert_eq!(fingerprint.arity(), 4);
+        assert_eq!(fingerprint.backend(), 2);
+        assert_eq!(fingerprint.version(), FORMAT_VERSION);
+
+        let bytes = fingerprint.to_bytes();
+        assert_eq!(Fingerprint::from_bytes(&bytes), Ok(fingerprint));
+        assert_eq!(fingerprint.check(&fingerprint), Ok(()));
+
+        assert_eq!(
+            fingerprint.check(&Fingerprint::new::<16, 4>(2)),
+            Err(FingerprintError::Height {
+                expected: 16,
+                found: 17
+            })
+        );
+        assert_eq!(
+            fingerprint.check(&Fingerprint::new::<17, 2>(2)),
+            Err(FingerprintError::Arity {
+                expected: 2,
+                found: 4
+            })
+        );
+        assert_eq!(
+            fingerprint.check(&Fingerprint::new::<17, 4>(1)),
+            Err(FingerprintError::Backend {
+                expected: 1,
+                found: 2
+            })
+        );
+
+        let mut bytes = fingerprint.to_bytes();
+        bytes[12] = 0;
+        assert_eq!(
+            Fingerprint::from_bytes(&bytes).unwrap().check(&fingerprint),
+            Err(FingerprintError::Version {
+                expected: FORMAT_VERSION,
+                found: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,348 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+use crate::{list_root, Aggregate, CheckpointId, MultiRoot, Padding, Tree};
+
+/// A tree that can be part of a [`Forest`].
+///
+/// It exposes the root the forest commits to, and the hooks the forest uses
+/// to either keep or undo the mutations of a commit. This is implemented for
+/// every [`Tree`], whatever its height, and can be implemented for other
+/// types - e.g. one wrapping a tree of another item, and committing to a
+/// root derived from it.
+pub trait ForestTree {
+    /// The type of the root the forest commits to.
+    type Root;
+
+    /// Returns the root the forest commits to.
+    fn root(&self) -> Self::Root;
+
+    /// Record a checkpoint before the mutations of a commit are applied.
+    fn checkpoint(&mut self) -> CheckpointId;
+
+    /// Undo the mutations applied since the checkpoint with the given `id`.
+    fn rollback(&mut self, id: CheckpointId);
+
+    /// Keep the mutations applied since the checkpoint with the given `id`.
+    fn commit(&mut self, id: CheckpointId);
+}
+
+impl<T, const H: usize, const A: usize> ForestTree for Tree<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    type Root = T;
+
+    fn root(&self) -> T {
+        Tree::root(self).clone()
+    }
+
+    fn checkpoint(&mut self) -> CheckpointId {
+        Tree::checkpoint(self)
+    }
+
+    fn rollback(&mut self, id: CheckpointId) {
+        Tree::rollback(self, id);
+    }
+
+    fn commit(&mut self, id: CheckpointId) {
+        Tree::commit(self, id);
+    }
+}
+
+/// The trees of a [`Forest`], all committing to the same type of root.
+///
+/// This is implemented for arrays of [`ForestTree`]s, and for tuples of up to
+/// four of them - allowing for the trees to differ in their items and
+/// heights.
+pub trait Trees {
+    /// The type of the roots of the trees.
+    type Root;
+
+    /// Returns the roots of the trees, in order.
+    fn roots(&self) -> Vec<Self::Root>;
+
+    /// Record a checkpoint of each of the trees, in order.
+    fn checkpoint(&mut self) -> Vec<CheckpointId>;
+
+    /// Undo the mutations applied to each tree since its checkpoint.
+    fn rollback(&mut self, ids: Vec<CheckpointId>);
+
+    /// Keep the mutations applied to each tree since its checkpoint.
+    fn commit(&mut self, ids: Vec<CheckpointId>);
+}
+
+impl<X, const N: usize> Trees for [X; N]
+where
+    X: ForestTree,
+{
+    type Root = X::Root;
+
+    fn roots(&self) -> Vec<Self::Root> {
+        self.iter().map(X::root).collect()
+    }
+
+    fn checkpoint(&mut self) -> Vec<CheckpointId> {
+        self.iter_mut().map(X::checkpoint).collect()
+    }
+
+    fn rollback(&mut self, ids: Vec<CheckpointId>) {
+        for (tree, id) in self.iter_mut().zip(ids) {
+            tree.rollback(id);
+        }
+    }
+
+    fn commit(&mut self, ids: Vec<CheckpointId>) {
+        for (tree, id) in self.iter_mut().zip(ids) {
+            tree.commit(id);
+        }
+    }
+}
+
+macro_rules! impl_trees {
+    ($first:ident $(, $rest:ident)*) => {
+        impl<$first, $($rest),*> Trees for ($first, $($rest,)*)
+        where
+            $first: ForestTree,
+            $($rest: ForestTree<Root = $first::Root>,)*
+        {
+            type Root = $first::Root;
+
+            #[allow(non_snake_case)]
+            fn roots(&self) -> Vec<Self::Root> {
+                let ($first, $($rest,)*) = self;
+                alloc::vec![$first.root(), $($rest.root()),*]
+            }
+
+            #[allow(non_snake_case)]
+            fn checkpoint(&mut self) -> Vec<CheckpointId> {
+                let ($first, $($rest,)*) = self;
+                alloc::vec![$first.checkpoint(), $($rest.checkpoint()),*]
+            }
+
+            #[allow(non_snake_case)]
+            fn rollback(&mut self, ids: Vec<CheckpointId>) {
+                let ($first, $($rest,)*) = self;
+                let mut ids = ids.into_iter();
+                $first.rollback(next_id(&mut ids));
+                $($rest.rollback(next_id(&mut ids));)*
+            }
+
+            #[allow(non_snake_case)]
+            fn commit(&mut self, ids: Vec<CheckpointId>) {
+                let ($first, $($rest,)*) = self;
+                let mut ids = ids.into_iter();
+                $first.commit(next_id(&mut ids));
+                $($rest.commit(next_id(&mut ids));)*
+            }
+        }
+    };
+}
+
+impl_trees!(X0);
+impl_trees!(X0, X1);
+impl_trees!(X0, X1, X2);
+impl_trees!(X0, X1, X2, X3);
+
+/// Returns the next of the checkpoints recorded for the trees of a tuple.
+fn next_id<I>(ids: &mut I) -> CheckpointId
+where
+    I: Iterator<Item = CheckpointId>,
+{
+    match ids.next() {
+        Some(id) => id,
+        None => unreachable!("a checkpoint is recorded for each tree"),
+    }
+}
+
+/// A set of trees that are mutated together, with a combined commitment to
+/// all of their roots.
+///
+/// Mutations are applied through [`commit`], which either applies all of
+/// them, or none at all. This ensures that the trees never diverge - e.g. a
+/// notes tree and a nullifier tree being updated by the same block. The trees
+/// are given as an array, or as a tuple when they differ in their items or
+/// heights - see [`Trees`].
+///
+/// The commitment to the roots aggregates them with an arity of `A`.
+///
+/// [`commit`]: Forest::commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest<F, const A: usize> {
+    trees: F,
+}
+
+impl<F, const A: usize> Default for Forest<F, A>
+where
+    F: Default,
+{
+    fn default() -> Self {
+        Self::new(F::default())
+    }
+}
+
+impl<F, const A: usize> Forest<F, A> {
+    /// Create a new forest of the given `trees`.
+    #[must_use]
+    pub const fn new(trees: F) -> Self {
+        Self { trees }
+    }
+
+    /// Returns the trees of the forest.
+    #[must_use]
+    pub const fn trees(&self) -> &F {
+        &self.trees
+    }
+
+    /// Returns the trees of the forest, consuming it.
+    #[must_use]
+    pub fn into_trees(self) -> F {
+        self.trees
+    }
+}
+
+impl<F, const A: usize> Forest<F, A>
+where
+    F: Trees,
+    F::Root: Aggregate<A> + Clone,
+{
+    /// Apply the mutations performed by the given closure to the trees of
+    /// the forest atomically.
+    ///
+    /// A checkpoint of each tree is recorded before calling the closure with
+    /// the trees, and is then kept if the closure succeeds.
+    ///
+    /// # Errors
+    /// If the closure returns an error, all the mutations it applied are
+    /// rolled back and the error is returned, leaving the forest as it was
+    /// before the call.
+    pub fn commit<M, O, E>(&mut self, mutations: M) -> Result<O, E>
+    where
+        M: FnOnce(&mut F) -> Result<O, E>,
+    {
+        let ids = self.trees.checkpoint();
+
+        match mutations(&mut self.trees) {
+            Ok(output) => {
+                self.trees.commit(ids);
+                Ok(output)
+            }
+            Err(err) => {
+                self.trees.rollback(ids);
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns the commitment to the roots of all the trees.
+    ///
+    /// This is the root of the list of roots, as computed by [`list_root`]
+    /// with [`Padding::Empty`].
+    #[must_use]
+    pub fn commitment(&self) -> F::Root {
+        list_root(&self.trees.roots(), Padding::Empty)
+    }
+
+    /// Returns the [`MultiRoot`] of the roots of all the trees, allowing for
+    /// openings proving which tree a root belongs to.
+    #[must_use]
+    pub fn multi_root(&self) -> MultiRoot<F::Root, A> {
+        MultiRoot::new(self.trees.roots())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Sum;
+
+    const H: usize = 3;
+    const A: usize = 2;
+
+    /// A tree counting its leaves, committed to as a [`Sum`].
+    #[derive(Debug, Default, Clone)]
+    struct CountTree(Tree<(), 5, A>);
+
+    impl ForestTree for CountTree {
+        type Root = Sum;
+
+        fn root(&self) -> Sum {
+            Sum(self.0.len())
+        }
+
+        fn checkpoint(&mut self) -> CheckpointId {
+            self.0.checkpoint()
+        }
+
+        fn rollback(&mut self, id: CheckpointId) {
+            self.0.rollback(id);
+        }
+
+        fn commit(&mut self, id: CheckpointId) {
+            self.0.commit(id);
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Duplicate(u64);
+
+    type TestForest = Forest<(Tree<Sum, H, A>, Tree<Sum, 5, A>), A>;
+
+    #[test]
+    fn commit() {
+        let mut forest = TestForest::default();
+
+        forest
+            .commit(|(first, second)| {
+                first.insert(1, Sum(3));
+                second.insert(20, Sum(4));
+                first.insert(2, Sum(1));
+                first.remove(2);
+                Ok::<_, ()>(())
+            })
+            .expect("The mutations should be valid");
+
+        assert_eq!(*forest.trees().0.root(), Sum(3));
+        assert_eq!(*forest.trees().1.root(), Sum(4));
+        assert_eq!(forest.commitment(), Sum(7));
+        assert_eq!(forest.trees().0.checkpoints(), 0);
+
+        let forest = Forest::<[Tree<Sum, H, A>; 3], A>::default();
+        assert_eq!(forest.commitment(), Sum(0));
+    }
+
+    #[test]
+    fn rollback() {
+        let mut forest = Forest::<_, A>::new((
+            Tree::<Sum, H, A>::new(),
+            CountTree::default(),
+        ));
+
+        // each insertion is paired with a nullifier, which must be new
+        let mut insert = |position: u64, nullifier: u64| {
+            forest.commit(|(notes, nullifiers)| {
+                notes.insert(position, Sum(position));
+                if nullifiers.0.contains(nullifier) {
+                    return Err(Duplicate(nullifier));
+                }
+                nullifiers.0.insert(nullifier, ());
+                Ok(())
+            })
+        };
+
+        assert_eq!(insert(1, 7), Ok(()));
+        assert_eq!(insert(2, 9), Ok(()));
+        assert_eq!(insert(3, 7), Err(Duplicate(7)));
+
+        let (notes, nullifiers) = forest.trees();
+        assert!(notes.positions().eq([1, 2]));
+        assert!(nullifiers.0.positions().eq([7, 9]));
+        assert_eq!(notes.checkpoints(), 0);
+        assert_eq!(forest.commitment(), Sum(3 + 2));
+    }
+}
@@ -0,0 +1,432 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::{capacity, init_array, Aggregate, Node};
+
+/// A builder computing the root of a tree whose leaves are appended strictly
+/// left to right, starting from position zero.
+///
+/// Only the right edge of the tree is kept - the items of the complete
+/// subtrees not yet aggregated into a parent - with each level holding less
+/// than `A` items. This means at most `H * (A - 1)` items are kept however
+/// many leaves are appended, as opposed to a node per leaf in a [`Tree`],
+/// making it suited for computing the root of millions of sequential leaves
+/// in one go. The root is the same as the one of a [`Tree`] with the leaves
+/// inserted at the same positions.
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, Frontier, Tree};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl Aggregate<4> for Sum {
+///     const EMPTY_SUBTREE: Self = Sum(0);
+///
+///     fn aggregate(items: [&Self; 4]) -> Self {
+///         Sum(items.iter().map(|item| item.0).sum())
+///     }
+/// }
+///
+/// let mut frontier = Frontier::<Sum, 16, 4>::new();
+/// for n in 0..10_000 {
+///     frontier.push(Sum(n));
+/// }
+///
+/// assert_eq!(frontier.len(), 10_000);
+/// assert_eq!(frontier.root(), Sum(49_995_000));
+/// ```
+///
+/// [`Tree`]: crate::Tree
+#[derive(Debug, Clone)]
+pub struct Frontier<T, const H: usize, const A: usize> {
+    // The items of the complete subtrees at each level, with the leaves at
+    // level zero. Levels are only allocated once they are reached.
+    levels: Vec<Vec<T>>,
+    // The root of the tree, once all its leaves are appended.
+    full: Option<T>,
+    len: u64,
+}
+
+impl<T, const H: usize, const A: usize> Default for Frontier<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const H: usize, const A: usize> Frontier<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Create a new builder, with no leaves.
+    ///
+    /// Fails to compile under the same conditions as [`Tree::new`].
+    ///
+    /// [`Tree::new`]: crate::Tree::new
+    #[must_use]
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Node::<T, H, A>::VALID_PARAMS;
+
+        Self {
+            levels: Vec::new(),
+            full: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of leaves appended, which is also the position the
+    /// next leaf will be appended at.
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if no leaves were appended.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if as many leaves as the capacity of the tree were
+    /// appended.
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.full.is_some()
+    }
+
+    /// Append an `item` as the next leaf.
+    ///
+    /// # Panics
+    /// If the tree is already full.
+    pub fn push(&mut self, item: impl Into<T>) {
+        let capacity = capacity(A as u64, H);
+        assert!(
+            self.len < capacity,
+            "tree is full: the capacity is {capacity}"
+        );
+        self.len += 1;
+
+        let mut item = item.into();
+        for level in 0..H {
+            if level == self.levels.len() {
+                self.levels.push(Vec::with_capacity(A));
+            }
+
+            let items = &mut self.levels[level];
+            items.push(item);
+            if items.len() < A {
+                return;
+            }
+
+            // the subtree is complete, so it is aggregated into its parent
+            item = T::aggregate(init_array(|i| &items[i]));
+            items.clear();
+        }
+
+        self.full = Some(item);
+    }
+
+    /// Returns the root of the tree holding the leaves appended so far, as
+    /// computed by a [`Tree`] with the leaves inserted at the same positions.
+    ///
+    /// The incomplete subtrees on the right edge are aggregated on each call,
+    /// meaning this computes at most `H` items.
+    ///
+    /// [`Tree`]: crate::Tree
+    #[must_use]
+    pub fn root(&self) -> T
+    where
+        T: Clone,
+    {
+        if let Some(root) = &self.full {
+            return root.clone();
+        }
+
+        let empty_subtree = &T::EMPTY_SUBTREE;
+
+        // the incomplete subtree at each level is made of the complete ones
+        // at that level, followed by the incomplete one from the level below
+        let mut partial: Option<T> = None;
+        for level in 0..H {
+            let items = self.levels.get(level).map_or(&[][..], Vec::as_slice);
+            if items.is_empty() && partial.is_none() {
+                continue;
+            }
+
+            let item =
+                T::aggregate(init_array(|i| match i.cmp(&items.len()) {
+                    Ordering::Less => &items[i],
+                    Ordering::Equal => {
+                        partial.as_ref().unwrap_or(empty_subtree)
+                    }
+                    Ordering::Greater => empty_subtree,
+                }));
+            partial = Some(item);
+        }
+
+        partial.unwrap_or(T::EMPTY_SUBTREE)
+    }
+}
+
+impl<T, U, const H: usize, const A: usize> Extend<U> for Frontier<T, H, A>
+where
+    T: Aggregate<A>,
+    U: Into<T>,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, leaves: I) {
+        for leaf in leaves {
+            self.push(leaf);
+        }
+    }
+}
+
+impl<T, U, const H: usize, const A: usize> FromIterator<U> for Frontier<T, H, A>
+where
+    T: Aggregate<A>,
+    U: Into<T>,
+{
+    fn from_iter<I: IntoIterator<Item = U>>(leaves: I) -> Self {
+        let mut frontier = Self::new();
+        frontier.extend(leaves);
+        frontier
+    }
+}
+
+/// Computes the root of a tree from its leaves, fed one at a time in the
+/// order of their positions, starting from position zero.
+///
+/// This exposes the incremental `update`/`finalize` interface of hash
+/// functions, allowing code written against it to produce the same root as a
+/// [`Tree`] with the same leaves, without building the tree. It is backed
+/// by a [`Frontier`], meaning the memory used doesn't grow with the number of
+/// leaves.
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, RootHasher, Tree};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl Aggregate<2> for Sum {
+///     const EMPTY_SUBTREE: Self = Sum(0);
+///
+///     fn aggregate(items: [&Self; 2]) -> Self {
+///         Sum(items[0].0 * 3 + items[1].0)
+///     }
+/// }
+///
+/// let mut hasher = RootHasher::<Sum, 4, 2>::new();
+/// let mut tree = Tree::<Sum, 4, 2>::new();
+///
+/// for (position, n) in [5, 8, 13].into_iter().enumerate() {
+///     hasher.update(Sum(n));
+///     tree.insert(position as u64, Sum(n));
+/// }
+///
+/// assert_eq!(hasher.finalize(), *tree.root());
+/// ```
+///
+/// [`Tree`]: crate::Tree
+#[derive(Debug, Clone)]
+pub struct RootHasher<T, const H: usize, const A: usize> {
+    frontier: Frontier<T, H, A>,
+}
+
+impl<T, const H: usize, const A: usize> Default for RootHasher<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const H: usize, const A: usize> RootHasher<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Create a new hasher, with no leaves.
+    ///
+    /// Fails to compile under the same conditions as [`Tree::new`].
+    ///
+    /// [`Tree::new`]: crate::Tree::new
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            frontier: Frontier::new(),
+        }
+    }
+
+    /// Feed the next `leaf` to the hasher.
+    ///
+    /// # Panics
+    /// If the hasher was already fed as many leaves as the capacity of the
+    /// tree.
+    pub fn update(&mut self, leaf: impl Into<T>) {
+        self.frontier.push(leaf);
+    }
+
+    /// Feed the next `leaf` to the hasher, in a chained manner.
+    ///
+    /// # Panics
+    /// Under the same conditions as [`update`].
+    ///
+    /// [`update`]: RootHasher::update
+    #[must_use]
+    pub fn chain(mut self, leaf: impl Into<T>) -> Self {
+        self.update(leaf);
+        self
+    }
+
+    /// Returns the number of leaves fed to the hasher.
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.frontier.len()
+    }
+
+    /// Returns true if the hasher wasn't fed any leaves.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.frontier.len() == 0
+    }
+
+    /// Returns the root of the tree holding the leaves fed to the hasher.
+    pub fn finalize(self) -> T
+    where
+        T: Clone,
+    {
+        self.frontier.root()
+    }
+
+    /// Returns the root of the tree holding the leaves fed to the hasher,
+    /// and resets it to have no leaves.
+    pub fn finalize_reset(&mut self) -> T
+    where
+        T: Clone,
+    {
+        let root = self.frontier.root();
+        self.reset();
+        root
+    }
+
+    /// Resets the hasher to have no leaves.
+    pub fn reset(&mut self) {
+        self.frontier = Frontier::new();
+    }
+}
+
+impl<T, U, const H: usize, const A: usize> Extend<U> for RootHasher<T, H, A>
+where
+    T: Aggregate<A>,
+    U: Into<T>,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, leaves: I) {
+        for leaf in leaves {
+            self.update(leaf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Tree;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Item(u64);
+
+    impl Aggregate<3> for Item {
+        const EMPTY_SUBTREE: Self = Item(0);
+
+        fn aggregate(items: [&Self; 3]) -> Self {
+            Item(
+                items[0]
+                    .0
+                    .wrapping_mul(31)
+                    .wrapping_add(items[1].0.wrapping_mul(7))
+                    .wrapping_add(items[2].0 + 1),
+            )
+        }
+    }
+
+    const H: usize = 3;
+    const A: usize = 3;
+
+    #[test]
+    fn same_root_as_tree() {
+        let mut hasher = RootHasher::<Item, H, A>::new();
+        let mut tree = Tree::<Item, H, A>::new();
+        assert_eq!(hasher.clone().finalize(), *tree.root());
+
+        for position in 0..tree.capacity() {
+            hasher.update(Item(position * 11 + 1));
+            tree.insert(position, Item(position * 11 + 1));
+            assert_eq!(hasher.clone().finalize(), *tree.root());
+        }
+        assert_eq!(hasher.len(), tree.capacity());
+    }
+
+    #[test]
+    fn frontier_large_tree() {
+        let mut tree = Tree::<Item, 17, A>::new();
+        for position in 0..2000 {
+            tree.insert(position, Item(position));
+        }
+
+        let frontier: Frontier<Item, 17, A> = (0..2000).map(Item).collect();
+        assert_eq!(frontier.len(), 2000);
+        assert!(!frontier.is_full());
+        assert_eq!(frontier.root(), *tree.root());
+    }
+
+    #[test]
+    fn frontier_full() {
+        let mut frontier = Frontier::<Item, 2, A>::new();
+        assert!(frontier.is_empty());
+
+        frontier.extend((0..9).map(Item));
+        assert!(frontier.is_full());
+
+        let mut tree = Tree::<Item, 2, A>::new();
+        for position in 0..9 {
+            tree.insert(position, Item(position));
+        }
+        assert_eq!(frontier.root(), *tree.root());
+    }
+
+    #[test]
+    fn finalize_reset() {
+        let mut hasher = RootHasher::<Item, H, A>::new();
+        hasher.extend([Item(1), Item(2)]);
+
+        let root = hasher.finalize_reset();
+        assert!(hasher.is_empty());
+        assert_eq!(
+            root,
+            RootHasher::<Item, H, A>::new()
+                .chain(Item(1))
+                .chain(Item(2))
+                .finalize()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tree is full")]
+    fn overflow() {
+        let mut hasher = RootHasher::<Item, 1, A>::new();
+        hasher.extend([Item(1), Item(2), Item(3), Item(4)]);
+    }
+}
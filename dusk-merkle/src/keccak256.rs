@@ -0,0 +1,180 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! A ready-made item for trees using the Keccak-256 hash function, as used by
+//! Ethereum.
+//!
+//! Leaves and inner nodes are domain separated as specified by [RFC 6962]:
+//! leaves are hashed prefixed with a `0x00` byte, and the children of inner
+//! nodes are hashed prefixed with a `0x01` byte. Empty subtrees are
+//! represented by the zero hash.
+//!
+//! [RFC 6962]: https://www.rfc-editor.org/rfc/rfc6962#section-2.1
+
+use dusk_bytes::{Error as BytesError, Serializable};
+use sha3::{Digest, Keccak256};
+
+use crate::{Aggregate, LeafHash};
+
+/// The prefix of the bytes hashed for a leaf.
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of the children hashed for an inner node.
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// An alias for a tree containing Keccak-256 [`Item`]s.
+pub type Tree<const H: usize, const A: usize> = crate::Tree<Item, H, A>;
+
+/// An alias for an opening of a tree containing Keccak-256 [`Item`]s.
+pub type Opening<const H: usize, const A: usize> = crate::Opening<Item, H, A>;
+
+/// A Keccak-256 hash, aggregated by hashing the concatenation of the
+/// children's hashes, prefixed with [`NODE_PREFIX`].
+///
+/// Empty subtrees are represented by the zero hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive_attr(derive(bytecheck::CheckBytes))
+)]
+pub struct Item([u8; 32]);
+
+impl Item {
+    /// Create a new item from the given hash `bytes`.
+    #[must_use]
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Create the item for a leaf holding the given `bytes`, by hashing them
+    /// prefixed with [`LEAF_PREFIX`].
+    #[must_use]
+    pub fn leaf(bytes: &[u8]) -> Self {
+        let mut hasher = Keccak256::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(bytes);
+        Self(hasher.finalize().into())
+    }
+
+    /// Returns the bytes of the hash.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl<const A: usize> Aggregate<A> for Item {
+    const EMPTY_SUBTREE: Self = Item([0; 32]);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        let mut hasher = Keccak256::new();
+        hasher.update([NODE_PREFIX]);
+        for item in items {
+            hasher.update(item.0);
+        }
+        Self(hasher.finalize().into())
+    }
+}
+
+impl Serializable<32> for Item {
+    type Error = BytesError;
+
+    fn from_bytes(buf: &[u8; 32]) -> Result<Self, Self::Error> {
+        Ok(Self(*buf))
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl LeafHash<32> for Item {
+    fn leaf(bytes: &[u8]) -> Self {
+        Self::leaf(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    fn keccak256(bytes: &[u8]) -> [u8; 32] {
+        Keccak256::digest(bytes).into()
+    }
+
+    #[test]
+    fn keccak256_vectors() {
+        assert_eq!(
+            keccak256(b""),
+            [
+                0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e,
+                0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53,
+                0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85,
+                0xa4, 0x70,
+            ]
+        );
+
+        // a full block, followed by a block holding only the padding
+        let block: Vec<u8> = (0..=135).collect();
+        assert_eq!(
+            keccak256(&block),
+            [
+                0x7c, 0xe7, 0x59, 0xf1, 0xab, 0x7f, 0x9c, 0xe4, 0x37, 0x71,
+                0x99, 0x70, 0xc2, 0x6b, 0x0a, 0x66, 0xff, 0x11, 0xfe, 0x3e,
+                0x38, 0xe1, 0x7d, 0xf8, 0x9c, 0xf5, 0xd2, 0x9c, 0x7d, 0x7f,
+                0x80, 0x7e,
+            ]
+        );
+
+        let bytes: Vec<u8> = (0..200).collect();
+        assert_eq!(
+            keccak256(&bytes),
+            [
+                0xbf, 0xb0, 0xaa, 0x97, 0x86, 0x3e, 0x79, 0x79, 0x43, 0xcf,
+                0x7c, 0x33, 0xbb, 0x7e, 0x88, 0x0b, 0xb4, 0x54, 0x3f, 0x3d,
+                0x27, 0x03, 0xc0, 0x92, 0x3c, 0x69, 0x01, 0xc2, 0xaf, 0x57,
+                0xb8, 0x90,
+            ]
+        );
+    }
+
+    #[test]
+    fn domain_separation() {
+        // Keccak-256 of the single byte `0x00`, i.e. the leaf of no bytes
+        assert_eq!(
+            Item::leaf(b"").as_bytes(),
+            &[
+                0xbc, 0x36, 0x78, 0x9e, 0x7a, 0x1e, 0x28, 0x14, 0x36, 0x46,
+                0x42, 0x29, 0x82, 0x8f, 0x81, 0x7d, 0x66, 0x12, 0xf7, 0xb4,
+                0x77, 0xd6, 0x65, 0x91, 0xff, 0x96, 0xa9, 0xe0, 0x64, 0xbc,
+                0xc9, 0x8a,
+            ]
+        );
+
+        let leaf = Item::leaf(b"hello");
+        let node = <Item as Aggregate<2>>::aggregate([&leaf, &leaf]);
+
+        let mut bytes = [NODE_PREFIX; 65];
+        bytes[1..33].copy_from_slice(leaf.as_bytes());
+        bytes[33..].copy_from_slice(leaf.as_bytes());
+        assert_eq!(node.as_bytes(), &keccak256(&bytes));
+        assert_ne!(node, Item::leaf(&bytes[1..]));
+    }
+
+    #[test]
+    fn insert_bytes() {
+        let mut tree = Tree::<8, 4>::new();
+        tree.insert_bytes(3, b"hello");
+        tree.insert_bytes(42, b"world");
+
+        let opening = tree.opening(42).unwrap();
+        assert!(opening.verify(Item::leaf(b"world")));
+        assert!(!opening.verify(Item::leaf(b"hello")));
+    }
+}
@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use dusk_bytes::{BadLength, Serializable};
+
+use crate::{Aggregate, Opening, OpeningError, Tree};
+
+/// An item of `N` bytes created by hashing the bytes of a leaf, such as the
+/// items of the hash backends of this crate.
+pub trait LeafHash<const N: usize>:
+    Serializable<N> + PartialEq + Clone
+{
+    /// Create the item for a leaf holding the given `bytes`.
+    fn leaf(bytes: &[u8]) -> Self;
+
+    /// Verify the serialized opening `proof` is for the given `leaf` and
+    /// leads to the given `root`, in a single pass over the bytes and without
+    /// allocating.
+    ///
+    /// See [`Opening::verify_raw`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed.
+    fn verify_raw<const H: usize, const A: usize>(
+        root: &[u8; N],
+        proof: &[u8],
+        leaf: &[u8; N],
+    ) -> Result<bool, OpeningError>
+    where
+        Self: Aggregate<A>,
+        Self::Error: BadLength,
+    {
+        Opening::<Self, H, A>::verify_raw(root, proof, leaf)
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Insert a leaf holding the given `bytes` at the given `position`,
+    /// hashing them to produce the item.
+    ///
+    /// # Panics
+    /// If `position >= capacity`.
+    pub fn insert_bytes<const N: usize>(&mut self, position: u64, bytes: &[u8])
+    where
+        T: LeafHash<N>,
+    {
+        self.insert(position, T::leaf(bytes));
+    }
+}
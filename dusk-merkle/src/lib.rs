@@ -9,19 +9,94 @@
 #![deny(clippy::pedantic)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use core::mem::MaybeUninit;
 use core::ptr;
 
+mod allocator;
+#[cfg(feature = "ed25519")]
+mod attestation;
+#[cfg(feature = "std")]
+mod background;
+mod bitmap;
+#[cfg(feature = "blake3")]
+pub mod blake3;
+mod builder;
+mod cache;
+mod cell;
+mod checkpoint;
+mod checksum;
+mod chunk;
+mod diff;
+#[cfg(feature = "digest")]
+pub mod digest;
+mod error;
+mod export;
+mod fingerprint;
+mod forest;
+mod frontier;
+#[cfg(feature = "keccak256")]
+pub mod keccak256;
+mod leaf;
+mod list;
+mod map;
+mod multi_opening;
+mod multi_root;
 mod node;
+mod non_membership;
 mod opening;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod range_opening;
+mod recent;
+#[cfg(feature = "sha2")]
+pub mod sha2;
+mod shuffle;
+mod stream;
+mod subtree;
+#[cfg(test)]
+pub(crate) mod test_util;
+mod trace;
 mod tree;
 mod walk;
+mod weight;
 
+pub use allocator::*;
+#[cfg(feature = "ed25519")]
+pub use attestation::*;
+#[cfg(feature = "std")]
+pub use background::*;
+pub use bitmap::*;
+pub use builder::*;
+pub use cache::*;
+pub use cell::*;
+pub use checkpoint::*;
+pub use checksum::*;
+pub use chunk::*;
+pub use diff::*;
+pub use error::*;
+pub use export::*;
+pub use fingerprint::*;
+pub use forest::*;
+pub use frontier::*;
+pub use leaf::*;
+pub use list::*;
+pub use map::*;
+pub use multi_opening::*;
+pub use multi_root::*;
 pub use node::*;
+pub use non_membership::*;
 pub use opening::*;
+pub use range_opening::*;
+pub use recent::*;
+pub use shuffle::*;
+pub use stream::*;
+pub use subtree::*;
 pub use tree::*;
 pub use walk::*;
+pub use weight::*;
 
 /// A type that can be produced by aggregating `A` instances of itself.
 pub trait Aggregate<const A: usize> {
@@ -32,6 +107,17 @@ pub trait Aggregate<const A: usize> {
     fn aggregate(items: [&Self; A]) -> Self;
 }
 
+/// An item carrying a numeric weight, such as stake.
+///
+/// For weighted queries over a tree to be meaningful, the weight of an
+/// aggregated item must be the sum of the weights of the items it was
+/// aggregated from, and the weight of [`Aggregate::EMPTY_SUBTREE`] must be
+/// zero.
+pub trait Weighted {
+    /// Returns the weight of the item.
+    fn weight(&self) -> u64;
+}
+
 // Implement aggregate for an item with empty data
 impl<const A: usize> Aggregate<A> for () {
     const EMPTY_SUBTREE: Self = ();
@@ -64,3 +150,14 @@ const fn capacity(arity: u64, depth: usize) -> u64 {
     #[allow(clippy::cast_possible_truncation)]
     u64::pow(arity, depth as u32)
 }
+
+/// Returns the capacity of a node at a given depth in the tree, or `None` if
+/// it doesn't fit in a `u64`.
+const fn checked_capacity(arity: u64, depth: usize) -> Option<u64> {
+    if depth > u32::MAX as usize {
+        return None;
+    }
+    // The depth was just checked to fit in a `u32`.
+    #[allow(clippy::cast_possible_truncation)]
+    u64::checked_pow(arity, depth as u32)
+}
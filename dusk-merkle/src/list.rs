@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+use crate::{init_array, Aggregate};
+
+/// The rule used to fill the missing children of a node when computing the
+/// root of a list of leaves with [`list_root`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Padding {
+    /// Missing children are filled with [`Aggregate::EMPTY_SUBTREE`].
+    ///
+    /// For two or more leaves, this produces the same root as the smallest
+    /// subtree of a [`Tree`] where the leaves were inserted sequentially
+    /// starting from position `0`.
+    ///
+    /// [`Tree`]: crate::Tree
+    Empty,
+    /// Missing children are filled with the last present child of the node,
+    /// as done by Bitcoin style merkle trees.
+    DuplicateLast,
+    /// The leaves are split left-balanced, as specified by [RFC 6962]: the
+    /// first children hold the largest power of `A` number of leaves strictly
+    /// smaller than the total, and the last child holds the remainder.
+    ///
+    /// For `A = 2` this produces the tree shape of RFC 6962. For larger
+    /// arities, nodes with less than `A` children are filled with
+    /// [`Aggregate::EMPTY_SUBTREE`].
+    ///
+    /// [RFC 6962]: https://www.rfc-editor.org/rfc/rfc6962#section-2.1
+    Rfc6962,
+}
+
+/// Compute the root of the smallest tree holding the given list of `leaves`,
+/// filling incomplete nodes according to the given `padding`.
+///
+/// An empty list has [`Aggregate::EMPTY_SUBTREE`] as its root, and a list
+/// with a single leaf has that leaf as its root.
+pub fn list_root<T, const A: usize>(leaves: &[T], padding: Padding) -> T
+where
+    T: Aggregate<A> + Clone,
+{
+    match leaves.len() {
+        0 => T::EMPTY_SUBTREE,
+        1 => leaves[0].clone(),
+        _ => match padding {
+            Padding::Empty | Padding::DuplicateLast => {
+                levelled_root(leaves, padding)
+            }
+            Padding::Rfc6962 => balanced_root(leaves),
+        },
+    }
+}
+
+/// Aggregates the leaves level by level, padding the last node of each level
+/// when there are not enough items to fill it.
+fn levelled_root<T, const A: usize>(leaves: &[T], padding: Padding) -> T
+where
+    T: Aggregate<A> + Clone,
+{
+    let empty_subtree = &T::EMPTY_SUBTREE;
+
+    let mut level: Vec<T> = aggregate_level(leaves, padding, empty_subtree);
+    while level.len() > 1 {
+        level = aggregate_level(&level, padding, empty_subtree);
+    }
+
+    // unwrapping is ok since aggregating a non-empty level always produces at
+    // least one item
+    level.pop().unwrap()
+}
+
+pub(crate) fn aggregate_level<T, const A: usize>(
+    level: &[T],
+    padding: Padding,
+    empty_subtree: &T,
+) -> Vec<T>
+where
+    T: Aggregate<A>,
+{
+    level
+        .chunks(A)
+        .map(|chunk| {
+            let pad = match padding {
+                // unwrapping is ok since chunks are never empty
+                Padding::DuplicateLast => chunk.last().unwrap(),
+                _ => empty_subtree,
+            };
+            T::aggregate(init_array(|i| chunk.get(i).unwrap_or(pad)))
+        })
+        .collect()
+}
+
+/// Recursively splits the leaves into subtrees of the largest power of `A`
+/// smaller than their number.
+fn balanced_root<T, const A: usize>(leaves: &[T]) -> T
+where
+    T: Aggregate<A> + Clone,
+{
+    if leaves.len() == 1 {
+        return leaves[0].clone();
+    }
+
+    let mut split = 1;
+    while split * A < leaves.len() {
+        split *= A;
+    }
+
+    let children: Vec<T> = leaves.chunks(split).map(balanced_root).collect();
+
+    let empty_subtree = &T::EMPTY_SUBTREE;
+    T::aggregate(init_array(|i| children.get(i).unwrap_or(empty_subtree)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::format;
+    use alloc::string::String;
+
+    use crate::Tree;
+
+    /// An item that records the shape of the tree it was aggregated from.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Shape(String);
+
+    impl From<&str> for Shape {
+        fn from(s: &str) -> Self {
+            Self(s.into())
+        }
+    }
+
+    impl<const A: usize> Aggregate<A> for Shape {
+        const EMPTY_SUBTREE: Self = Shape(String::new());
+
+        fn aggregate(items: [&Self; A]) -> Self {
+            let mut shape = String::from("(");
+            for (i, item) in items.into_iter().enumerate() {
+                if i != 0 {
+                    shape.push(',');
+                }
+                shape.push_str(if item.0.is_empty() { "_" } else { &item.0 });
+            }
+            shape.push(')');
+            Shape(shape)
+        }
+    }
+
+    fn leaves(n: usize) -> Vec<Shape> {
+        (0..n).map(|i| Shape(format!("{i}"))).collect()
+    }
+
+    #[test]
+    fn padding_shapes() {
+        let leaves = leaves(3);
+
+        let root = list_root::<_, 2>(&leaves, Padding::Empty);
+        assert_eq!(root, Shape::from("((0,1),(2,_))"));
+
+        let root = list_root::<_, 2>(&leaves, Padding::DuplicateLast);
+        assert_eq!(root, Shape::from("((0,1),(2,2))"));
+
+        let root = list_root::<_, 2>(&leaves, Padding::Rfc6962);
+        assert_eq!(root, Shape::from("((0,1),2)"));
+    }
+
+    #[test]
+    fn rfc6962_higher_arity() {
+        let leaves = leaves(6);
+
+        let root = list_root::<_, 4>(&leaves, Padding::Rfc6962);
+        assert_eq!(root, Shape::from("((0,1,2,3),(4,5,_,_),_,_)"));
+
+        let root = list_root::<_, 3>(&leaves, Padding::Rfc6962);
+        assert_eq!(root, Shape::from("((0,1,2),(3,4,5),_)"));
+    }
+
+    #[test]
+    fn trivial_lists() {
+        let root = list_root::<Shape, 2>(&[], Padding::DuplicateLast);
+        assert_eq!(root, <Shape as Aggregate<2>>::EMPTY_SUBTREE);
+
+        let root = list_root::<_, 2>(&leaves(1), Padding::Rfc6962);
+        assert_eq!(root, Shape::from("0"));
+    }
+
+    #[test]
+    fn empty_padding_matches_tree() {
+        for n in 2..=9 {
+            let leaves = leaves(n);
+
+            let mut tree = Tree::<Shape, 4, 2>::new();
+            for (pos, leaf) in leaves.iter().enumerate() {
+                tree.insert(pos as u64, leaf.clone());
+            }
+
+            let (subtree_root, _) = tree.smallest_subtree();
+            let root = list_root::<_, 2>(&leaves, Padding::Empty);
+            assert_eq!(*subtree_root, root);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use dusk_bytes::Serializable;
+
+use crate::{capacity, Aggregate, Opening, OpeningError, Ref, Tree};
+
+/// The layout of a [`MerkleMap`], defining where each key is placed in the
+/// tree, and which leaf a key and its value are committed to.
+///
+/// Keys that derive the same position replace each other in the map, so the
+/// position should be derived from a collision resistant hash of the key,
+/// over a tree large enough to make collisions unlikely. The leaf should
+/// commit to the key as well as the value, so that a value can't be proven
+/// under another key deriving the same position.
+pub trait MapLayout<T> {
+    /// The type of the keys of the map.
+    type Key: ?Sized;
+    /// The type of the values of the map.
+    type Value: ?Sized;
+
+    /// Returns the position derived from the given `key`, before being
+    /// reduced to the capacity of the tree.
+    fn position(key: &Self::Key) -> u64;
+
+    /// Returns the leaf committing to the given `key` and `value`.
+    fn leaf(key: &Self::Key, value: &Self::Value) -> T;
+}
+
+/// Returns the position of the given `key` in a tree of height `H` and arity
+/// `A` using the layout `M`.
+fn key_position<M, T, const H: usize, const A: usize>(key: &M::Key) -> u64
+where
+    M: MapLayout<T>,
+{
+    M::position(key) % capacity(A as u64, H)
+}
+
+/// A map from keys to values, committed to by a merkle tree according to the
+/// [`MapLayout`] `M`.
+///
+/// Each key is placed at the position derived from it by the layout, with a
+/// leaf committing to both the key and its value. Membership is proven with
+/// a [`MerkleMapProof`], which checks the derivation of the position together
+/// with the opening.
+pub struct MerkleMap<M, T, const H: usize, const A: usize> {
+    tree: Tree<T, H, A>,
+    layout: PhantomData<fn() -> M>,
+}
+
+impl<M, T, const H: usize, const A: usize> fmt::Debug for MerkleMap<M, T, H, A>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleMap")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> Clone for MerkleMap<M, T, H, A>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            layout: PhantomData,
+        }
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> Default for MerkleMap<M, T, H, A>
+where
+    M: MapLayout<T>,
+    T: Aggregate<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> MerkleMap<M, T, H, A>
+where
+    M: MapLayout<T>,
+    T: Aggregate<A>,
+{
+    /// Create a new, empty, map.
+    ///
+    /// Fails to compile under the same conditions as [`Tree::new`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            tree: Tree::new(),
+            layout: PhantomData,
+        }
+    }
+
+    /// Insert the given `value` under the given `key`, returning the
+    /// position it was placed at.
+    ///
+    /// Any value previously inserted under a key deriving the same position
+    /// is replaced.
+    pub fn insert(&mut self, key: &M::Key, value: &M::Value) -> u64 {
+        let position = key_position::<M, T, H, A>(key);
+        self.tree.insert(position, M::leaf(key, value));
+        position
+    }
+
+    /// Remove the leaf at the position of the given `key`, returning it if it
+    /// exists.
+    pub fn remove(&mut self, key: &M::Key) -> Option<T> {
+        self.tree.remove(key_position::<M, T, H, A>(key))
+    }
+
+    /// Returns true if there is a leaf at the position of the given `key`.
+    pub fn contains_key(&self, key: &M::Key) -> bool {
+        self.tree.contains(key_position::<M, T, H, A>(key))
+    }
+
+    /// Returns the [`MerkleMapProof`] for the given `key`, if there is a leaf
+    /// at its position.
+    pub fn proof(&self, key: &M::Key) -> Option<MerkleMapProof<M, T, H, A>>
+    where
+        T: Clone,
+    {
+        let opening = self.tree.opening(key_position::<M, T, H, A>(key))?;
+        Some(MerkleMapProof::new(opening))
+    }
+
+    /// Returns the root of the map.
+    pub fn root(&self) -> Ref<'_, T> {
+        self.tree.root()
+    }
+
+    /// Returns the tree committing to the map.
+    #[must_use]
+    pub const fn tree(&self) -> &Tree<T, H, A> {
+        &self.tree
+    }
+
+    /// Returns the number of entries in the map.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.tree.len()
+    }
+
+    /// Returns true if the map has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}
+
+/// A proof that a value is in a [`MerkleMap`] under a given key.
+///
+/// The proof binds the key, the position derived from it, the leaf
+/// committing to the key and value, and the branch leading to the root, all
+/// checked by a single call to [`verify`].
+///
+/// Created using [`MerkleMap::proof`].
+///
+/// [`verify`]: MerkleMapProof::verify
+pub struct MerkleMapProof<M, T, const H: usize, const A: usize> {
+    opening: Opening<T, H, A>,
+    layout: PhantomData<fn() -> M>,
+}
+
+impl<M, T, const H: usize, const A: usize> fmt::Debug
+    for MerkleMapProof<M, T, H, A>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleMapProof")
+            .field("opening", &self.opening)
+            .finish()
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> Clone for MerkleMapProof<M, T, H, A>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.opening.clone())
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> PartialEq
+    for MerkleMapProof<M, T, H, A>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.opening == other.opening
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> Eq for MerkleMapProof<M, T, H, A> where
+    T: Eq
+{
+}
+
+impl<M, T, const H: usize, const A: usize> MerkleMapProof<M, T, H, A> {
+    const fn new(opening: Opening<T, H, A>) -> Self {
+        Self {
+            opening,
+            layout: PhantomData,
+        }
+    }
+
+    /// Returns the opening of the leaf committing to the key and value.
+    #[must_use]
+    pub const fn opening(&self) -> &Opening<T, H, A> {
+        &self.opening
+    }
+}
+
+impl<M, T, const H: usize, const A: usize> MerkleMapProof<M, T, H, A>
+where
+    M: MapLayout<T>,
+    T: Aggregate<A> + Clone + PartialEq,
+{
+    /// Verify the given `value` is in the map with the given `root`, under
+    /// the given `key`.
+    ///
+    /// This checks the position of the opening is the one derived from the
+    /// key, that the opening is for the leaf committing to the key and the
+    /// value, and that it leads to the given root.
+    pub fn verify(&self, root: &T, key: &M::Key, value: &M::Value) -> bool {
+        self.opening.position() == key_position::<M, T, H, A>(key)
+            && self.opening.root() == root
+            && self.opening.verify(M::leaf(key, value))
+    }
+
+    /// Serialize the proof to a vector of bytes, as the opening it holds.
+    ///
+    /// See [`Opening::to_var_bytes`] for the format.
+    pub fn to_var_bytes<const T_SIZE: usize>(&self) -> Vec<u8>
+    where
+        T: Serializable<T_SIZE>,
+    {
+        self.opening.to_var_bytes()
+    }
+
+    /// Deserialize a proof from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed.
+    pub fn from_slice<const T_SIZE: usize>(
+        buf: &[u8],
+    ) -> Result<Self, OpeningError>
+    where
+        T: Serializable<T_SIZE>,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        Opening::from_slice(buf).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use dusk_bytes::Error as BytesError;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Item(u64);
+
+    impl Aggregate<2> for Item {
+        const EMPTY_SUBTREE: Self = Item(0);
+
+        fn aggregate(items: [&Self; 2]) -> Self {
+            Item(items[0].0.wrapping_mul(31).wrapping_add(items[1].0 + 1))
+        }
+    }
+
+    impl Serializable<8> for Item {
+        type Error = BytesError;
+
+        fn from_bytes(buf: &[u8; 8]) -> Result<Self, Self::Error> {
+            Ok(Self(u64::from_bytes(buf)?))
+        }
+
+        fn to_bytes(&self) -> [u8; 8] {
+            self.0.to_bytes()
+        }
+    }
+
+    /// Places keys at their own value, with leaves mixing the key and value.
+    struct Layout;
+
+    impl MapLayout<Item> for Layout {
+        type Key = u64;
+        type Value = u64;
+
+        fn position(key: &u64) -> u64 {
+            *key
+        }
+
+        fn leaf(key: &u64, value: &u64) -> Item {
+            Item(key.wrapping_mul(0x9e37_79b9).wrapping_add(*value))
+        }
+    }
+
+    type Map = MerkleMap<Layout, Item, 4, 2>;
+
+    #[test]
+    fn map_proof() {
+        let mut map = Map::new();
+        assert_eq!(map.insert(&3, &30), 3);
+        assert_eq!(map.insert(&21, &210), 5);
+        assert_eq!(map.len(), 2);
+        assert!(map.contains_key(&5));
+
+        let root = *map.root();
+        let proof = map.proof(&21).unwrap();
+
+        assert!(proof.verify(&root, &21, &210));
+        assert!(!proof.verify(&root, &21, &211));
+        assert!(!proof.verify(&Item(0), &21, &210));
+
+        // a key deriving the same position is a different leaf
+        assert!(!proof.verify(&root, &5, &210));
+        // a key deriving another position is rejected
+        assert!(!proof.verify(&root, &3, &30));
+
+        let bytes = proof.to_var_bytes();
+        assert_eq!(
+            MerkleMapProof::<Layout, Item, 4, 2>::from_slice(&bytes),
+            Ok(proof)
+        );
+
+        assert!(map.proof(&4).is_none());
+        assert!(map.remove(&3).is_some());
+        assert!(map.proof(&3).is_none());
+    }
+}
@@ -0,0 +1,236 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "rkyv-impl")]
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{init_array, trace, Aggregate, Tree};
+
+/// An opening for several positions in a merkle tree, sharing the items
+/// common to their paths.
+///
+/// Only the items that can't be computed from the opened leaves are stored -
+/// i.e. the siblings of the paths that aren't themselves on a path. Opening
+/// many leaves this way is considerably smaller and faster to verify than
+/// opening each of them individually, since the upper levels of their paths
+/// are mostly shared.
+///
+/// Created using [`Opening::batch`].
+///
+/// [`Opening::batch`]: crate::Opening::batch
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(Archive, Serialize, Deserialize),
+    archive_attr(derive(CheckBytes))
+)]
+pub struct MultiOpening<T, const H: usize, const A: usize> {
+    root: T,
+    positions: Vec<u64>,
+    siblings: Vec<T>,
+}
+
+impl<T, const H: usize, const A: usize> MultiOpening<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// # Panics
+    /// If any of the given sorted and deduplicated `positions` is not in the
+    /// `tree`.
+    pub(crate) fn new(tree: &Tree<T, H, A>, positions: Vec<u64>) -> Self {
+        trace::span!("multi_opening", positions = positions.len());
+
+        let mut siblings = Vec::new();
+
+        let mut level = positions.clone();
+        for height in (1..=H).rev() {
+            let mut parents = Vec::with_capacity(level.len());
+
+            let mut rest = level.as_slice();
+            while let Some(&first) = rest.first() {
+                let parent = first / A as u64;
+                let len = rest.partition_point(|i| i / A as u64 == parent);
+                let (children, tail) = rest.split_at(len);
+                rest = tail;
+
+                for index in parent * A as u64..(parent + 1) * A as u64 {
+                    if children.binary_search(&index).is_err() {
+                        let sibling = match tree.root.descendant(height, index)
+                        {
+                            Some(node) => node.item().clone(),
+                            None => T::EMPTY_SUBTREE,
+                        };
+                        siblings.push(sibling);
+                    }
+                }
+
+                parents.push(parent);
+            }
+
+            level = parents;
+        }
+
+        Self {
+            root: tree.root.item().clone(),
+            positions,
+            siblings,
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> MultiOpening<T, H, A> {
+    /// Returns the root of the opening.
+    #[must_use]
+    pub const fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the opened positions, in ascending order.
+    #[must_use]
+    pub fn positions(&self) -> &[u64] {
+        &self.positions
+    }
+
+    /// Returns the items needed to compute the root from the opened leaves,
+    /// level by level from the leaves up, and from left to right within each
+    /// level.
+    #[must_use]
+    pub fn siblings(&self) -> &[T] {
+        &self.siblings
+    }
+
+    /// Verify the given `leaves` are the ones at the opened positions, and
+    /// that the opening is cryptographically correct.
+    ///
+    /// The leaves may be given in any order, but each of the opened positions
+    /// must be given exactly once.
+    pub fn verify(&self, leaves: &[(u64, T)]) -> bool
+    where
+        T: Aggregate<A> + Clone + PartialEq,
+    {
+        // the opened positions must be strictly increasing, otherwise a
+        // duplicated position would let a leaf go unchecked
+        if !self.positions.windows(2).all(|pair| pair[0] < pair[1]) {
+            return false;
+        }
+
+        let mut level: Vec<(u64, T)> = leaves.to_vec();
+        level.sort_by_key(|(position, _)| *position);
+
+        if !level
+            .iter()
+            .map(|(position, _)| position)
+            .eq(&self.positions)
+        {
+            return false;
+        }
+
+        let mut siblings = self.siblings.iter();
+
+        for _ in 0..H {
+            let mut parents = Vec::with_capacity(level.len());
+
+            let mut rest = level.as_slice();
+            while let Some((first, _)) = rest.first() {
+                let parent = first / A as u64;
+                let len = rest.partition_point(|(i, _)| i / A as u64 == parent);
+                let (children, tail) = rest.split_at(len);
+                rest = tail;
+
+                let mut children = children.iter().peekable();
+                let mut items = Vec::with_capacity(A);
+                for index in parent * A as u64..(parent + 1) * A as u64 {
+                    let item = match children.next_if(|(i, _)| *i == index) {
+                        Some((_, item)) => item,
+                        None => match siblings.next() {
+                            Some(sibling) => sibling,
+                            None => return false,
+                        },
+                    };
+                    items.push(item);
+                }
+                // every child must be aggregated into the parent
+                if children.next().is_some() {
+                    return false;
+                }
+
+                let item = T::aggregate(init_array(|i| items[i]));
+                parents.push((parent, item));
+            }
+
+            level = parents;
+        }
+
+        match (level.as_slice(), siblings.next()) {
+            ([(0, root)], None) => *root == self.root,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::Sum;
+    use crate::{Opening, Tree, TreeBuilder};
+
+    const H: usize = 4;
+    const A: usize = 3;
+
+    #[test]
+    fn multi_opening() {
+        let positions = [0, 1, 5, 26, 40, 80];
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves(
+                positions.map(|position| (position, Sum(position + 1))),
+            )
+            .build();
+
+        let opening = Opening::batch(&tree, &[40, 0, 5, 1]).unwrap();
+        assert_eq!(opening.root(), &*tree.root());
+        assert_eq!(opening.positions(), [0, 1, 5, 40]);
+
+        // the shared upper levels make for fewer items than separate openings
+        assert!(opening.siblings().len() < 4 * (A - 1) * H);
+
+        let leaves = [(5, Sum(6)), (0, Sum(1)), (40, Sum(41)), (1, Sum(2))];
+        assert!(opening.verify(&leaves));
+
+        let wrong_leaf = [(5, Sum(6)), (0, Sum(1)), (40, Sum(40)), (1, Sum(2))];
+        assert!(!opening.verify(&wrong_leaf));
+        assert!(!opening.verify(&leaves[..3]));
+        let duplicated = [(5, Sum(6)), (0, Sum(1)), (40, Sum(41)), (5, Sum(6))];
+        assert!(!opening.verify(&duplicated));
+
+        assert!(Opening::batch(&tree, &[0, 2]).is_none());
+        assert!(Opening::batch(&tree, &[]).is_none());
+
+        let single = Opening::batch(&tree, &[80]).unwrap();
+        assert_eq!(single.siblings().len(), (A - 1) * H);
+        assert!(single.verify(&[(80, Sum(81))]));
+    }
+
+    #[test]
+    fn forged_positions() {
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves([0, 5, 40].map(|position| (position, Sum(1))))
+            .build();
+        let opening = Opening::batch(&tree, &[0, 5]).unwrap();
+
+        // a duplicated position would leave one of its leaves unchecked
+        let mut duplicated = opening.clone();
+        duplicated.positions = alloc::vec![0, 0, 5];
+        assert!(!duplicated.verify(&[(0, Sum(1)), (0, Sum(7)), (5, Sum(1))]));
+
+        // positions out of order are rejected
+        let mut unordered = opening;
+        unordered.positions = alloc::vec![5, 0];
+        assert!(!unordered.verify(&[(5, Sum(1)), (0, Sum(1))]));
+    }
+}
@@ -0,0 +1,228 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+use crate::list::aggregate_level;
+use crate::{init_array, list_root, Aggregate, Opening, Padding};
+
+/// A single commitment to the roots of several trees - e.g. the state,
+/// nullifier, and message trees of a chain - allowing block headers to commit
+/// to one value.
+///
+/// The commitment is the root of the list of roots, as computed by
+/// [`list_root`] with [`Padding::Empty`], and is the same as the one computed
+/// by [`Forest::commitment`].
+///
+/// [`Forest::commitment`]: crate::Forest::commitment
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, MultiRoot, Tree};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl Aggregate<2> for Sum {
+///     const EMPTY_SUBTREE: Self = Sum(0);
+///
+///     fn aggregate(items: [&Self; 2]) -> Self {
+///         Sum(items[0].0 + items[1].0)
+///     }
+/// }
+///
+/// let mut state = Tree::<Sum, 8, 2>::new();
+/// let mut nullifiers = Tree::<Sum, 8, 2>::new();
+/// state.insert(3, Sum(5));
+/// nullifiers.insert(7, Sum(2));
+///
+/// let roots = [*state.root(), *nullifiers.root()];
+/// let multi_root = MultiRoot::<Sum, 2>::new(roots);
+/// assert_eq!(multi_root.commitment(), Sum(7));
+///
+/// // prove the opening of the nullifier belongs to the second tree
+/// let roots_opening = multi_root.opening(1).unwrap();
+/// let opening = nullifiers.opening(7).unwrap();
+/// assert!(roots_opening.verify_opening(&opening, Sum(2)));
+///
+/// let roots_opening = multi_root.opening(0).unwrap();
+/// assert!(!roots_opening.verify_opening(&opening, Sum(2)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiRoot<T, const A: usize> {
+    roots: Vec<T>,
+}
+
+impl<T, const A: usize> MultiRoot<T, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// Create a new commitment to the given `roots`, in order.
+    pub fn new<I>(roots: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self {
+            roots: roots.into_iter().collect(),
+        }
+    }
+
+    /// Returns the committed roots.
+    #[must_use]
+    pub fn roots(&self) -> &[T] {
+        &self.roots
+    }
+
+    /// Returns the commitment to all the roots.
+    #[must_use]
+    pub fn commitment(&self) -> T {
+        list_root(&self.roots, Padding::Empty)
+    }
+
+    /// Returns the opening of the root at the given `index`, or `None` if it
+    /// is out of bounds.
+    #[must_use]
+    pub fn opening(&self, index: usize) -> Option<MultiRootOpening<T, A>> {
+        if index >= self.roots.len() {
+            return None;
+        }
+
+        let empty_subtree = &T::EMPTY_SUBTREE;
+
+        let mut branch = Vec::new();
+        let mut level = self.roots.clone();
+        let mut level_index = index;
+
+        while level.len() > 1 {
+            let start = level_index - level_index % A;
+            branch.push(init_array(|i| {
+                level.get(start + i).unwrap_or(empty_subtree).clone()
+            }));
+
+            level = aggregate_level(&level, Padding::Empty, empty_subtree);
+            level_index /= A;
+        }
+
+        Some(MultiRootOpening {
+            commitment: self.commitment(),
+            index,
+            branch,
+        })
+    }
+}
+
+/// An opening of a root committed to by a [`MultiRoot`], proving which of the
+/// trees it is the root of.
+///
+/// Only the indices of the committed roots should be trusted: openings of
+/// larger indices prove the membership of an empty subtree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiRootOpening<T, const A: usize> {
+    commitment: T,
+    index: usize,
+    branch: Vec<[T; A]>,
+}
+
+impl<T, const A: usize> MultiRootOpening<T, A>
+where
+    T: Aggregate<A> + PartialEq,
+{
+    /// Returns the commitment the opening is for.
+    #[must_use]
+    pub const fn commitment(&self) -> &T {
+        &self.commitment
+    }
+
+    /// Returns the index of the opened root.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the branch of the opening, from the level of the roots up to
+    /// the level just below the commitment.
+    #[must_use]
+    pub fn branch(&self) -> &[[T; A]] {
+        &self.branch
+    }
+
+    /// Verify the given `root` is the opened root, and that the opening is
+    /// cryptographically correct.
+    pub fn verify(&self, root: impl Into<T>) -> bool {
+        let mut item = root.into();
+        let mut index = self.index;
+
+        for level in &self.branch {
+            if item != level[index % A] {
+                return false;
+            }
+            item = T::aggregate(init_array(|i| &level[i]));
+            index /= A;
+        }
+
+        index == 0 && item == self.commitment
+    }
+
+    /// Verify the given `opening` of a tree proves the given `item`, and
+    /// that the root of the tree is the opened root.
+    pub fn verify_opening<const H: usize, const TA: usize>(
+        &self,
+        opening: &Opening<T, H, TA>,
+        item: impl Into<T>,
+    ) -> bool
+    where
+        T: Aggregate<TA> + Clone,
+    {
+        opening.verify(item) && self.verify(opening.root().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::Sum;
+    use crate::{Forest, Tree};
+
+    const H: usize = 3;
+    const A: usize = 3;
+
+    #[test]
+    fn multi_root() {
+        let mut forest = Forest::<[Tree<Sum, H, A>; 5], A>::default();
+        for index in 0..5 {
+            forest
+                .commit(|trees| {
+                    trees[index].insert(index as u64, Sum(1 << index));
+                    Ok::<_, ()>(())
+                })
+                .unwrap();
+        }
+
+        let multi_root = forest.multi_root();
+        assert_eq!(multi_root.commitment(), forest.commitment());
+        assert_eq!(multi_root.commitment(), Sum(31));
+
+        for index in 0..5 {
+            let opening = multi_root.opening(index).unwrap();
+            assert_eq!(opening.index(), index);
+            assert_eq!(opening.branch().len(), 2);
+            assert!(opening.verify(Sum(1 << index)));
+            assert!(!opening.verify(Sum(0)));
+
+            let tree: &Tree<Sum, H, A> = &forest.trees()[index];
+            let tree_opening = tree.opening(index as u64).unwrap();
+            assert!(opening.verify_opening(&tree_opening, Sum(1 << index)));
+        }
+        assert!(multi_root.opening(5).is_none());
+
+        let single = MultiRoot::<Sum, A>::new([Sum(4)]);
+        let opening = single.opening(0).unwrap();
+        assert!(opening.branch().is_empty());
+        assert!(opening.verify(Sum(4)));
+        assert!(!opening.verify(Sum(3)));
+    }
+}
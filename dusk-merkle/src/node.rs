@@ -4,43 +4,89 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+#[cfg(not(feature = "shared"))]
 use alloc::boxed::Box;
-use core::cell::{Ref, RefCell};
+#[cfg(feature = "shared")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
 
-use crate::{capacity, init_array, Aggregate};
+#[cfg(feature = "shared")]
+use std::sync::OnceLock;
+
+use crate::cell::ItemCell;
+use crate::{
+    capacity, checked_capacity, init_array, Aggregate, CachePolicy, Ref,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[doc(hidden)]
 pub struct Node<T, const H: usize, const A: usize> {
-    item: RefCell<Option<T>>,
-    pub(crate) children: [Option<Box<Node<T, H, A>>>; A],
+    item: ItemCell<T>,
+    pub(crate) children: [Option<Link<T, H, A>>; A],
 }
 
 impl<T, const H: usize, const A: usize> Node<T, H, A>
 where
     T: Aggregate<A>,
 {
-    const INIT_NODE: Option<Box<Node<T, H, A>>> = None;
+    const INIT_NODE: Option<Link<T, H, A>> = None;
+
+    /// Evaluating this constant fails compilation when the parameters of the
+    /// tree are not supported.
+    ///
+    /// It must be evaluated by every way of creating a tree, an opening, or
+    /// any other structure relying on the positions of a tree fitting in a
+    /// `u64`.
+    pub(crate) const VALID_PARAMS: () = {
+        assert!(H > 0, "Height must be larger than zero");
+        assert!(A >= 2, "Arity must be at least two");
+        assert!(H <= u32::MAX as usize, "Height must fit in a u32");
+        // the capacity of the tree must be addressable with a `u64` position,
+        // i.e. `H * log2(A) < 64`
+        assert!(
+            checked_capacity(A as u64, H).is_some(),
+            "Capacity of the tree must fit in a u64"
+        );
+    };
 
     pub(crate) const fn new() -> Self {
-        debug_assert!(H > 0, "Height must be larger than zero");
-        debug_assert!(A > 0, "Arity must be larger than zero");
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_PARAMS;
 
         Self {
-            item: RefCell::new(None),
+            item: ItemCell::new(None),
             children: [Self::INIT_NODE; A],
         }
     }
 
-    pub(crate) fn item(&self) -> Ref<T> {
-        // a leaf will always have a computed item, so we never go into it
-        if self.item.borrow().is_none() {
+    pub(crate) fn item(&self) -> Ref<'_, T> {
+        self.item_with(0, CachePolicy::Always)
+    }
+
+    /// Returns the item of this node at the given `height`, computing it if
+    /// necessary, and discarding the computed items of the children that the
+    /// `policy` doesn't keep.
+    pub(crate) fn item_with(
+        &self,
+        height: usize,
+        policy: CachePolicy,
+    ) -> Ref<'_, T> {
+        loop {
+            // a leaf will always have a computed item, so we always return it
+            if let Some(item) = self.item.get() {
+                return item;
+            }
+
             // compute our item, recursing into the children.
             let empty_subtree = &T::EMPTY_SUBTREE;
             let mut item_refs = [empty_subtree; A];
 
             let child_items: [Option<Ref<T>>; A] = init_array(|i| {
-                self.children[i].as_ref().map(|item| item.item())
+                self.children[i]
+                    .as_ref()
+                    .map(|child| child.item_with(height + 1, policy))
             });
 
             let mut has_children = false;
@@ -51,15 +97,92 @@ where
                 }
             });
 
-            if has_children {
-                self.item.replace(Some(T::aggregate(item_refs)));
+            let item = if has_children {
+                T::aggregate(item_refs)
             } else {
-                self.item.replace(Some(T::EMPTY_SUBTREE));
+                T::EMPTY_SUBTREE
+            };
+            drop(child_items);
+
+            // items still borrowed elsewhere are kept, since they can't be
+            // discarded without invalidating the references to them
+            if height + 1 < H && !policy.caches(height + 1) {
+                for child in self.children.iter().flatten() {
+                    child.item.evict();
+                }
             }
+
+            // with the `sync` feature another thread may evict the item
+            // before it is returned, in which case it is computed again
+            self.item.set(Some(item));
+        }
+    }
+
+    /// Returns a node with the same structure as this one, at the given
+    /// `height`, with the leaves converted using `f`.
+    ///
+    /// The items of the inner nodes are left to be computed.
+    pub(crate) fn map<U, F>(&self, height: usize, f: &F) -> Node<U, H, A>
+    where
+        U: Aggregate<A>,
+        F: Fn(&T) -> U,
+    {
+        let mut node = Node::new();
+        if height == H {
+            *node.item.get_mut() = self.item.with(|item| item.as_ref().map(f));
+            return node;
+        }
+
+        for (mapped, child) in node.children.iter_mut().zip(&self.children) {
+            *mapped = child
+                .as_ref()
+                .map(|child| Link::new(child.map(height + 1, f)));
+        }
+        node
+    }
+
+    /// Discards the items of the inner nodes in this subtree, at the given
+    /// `height`, that the `policy` doesn't keep and that are not currently
+    /// borrowed.
+    pub(crate) fn evict(&self, height: usize, policy: CachePolicy) {
+        if height == H {
+            return;
+        }
+        if !policy.caches(height) {
+            self.item.evict();
+        }
+        for child in self.children.iter().flatten() {
+            child.evict(height + 1, policy);
+        }
+    }
+
+    /// Returns the number of nodes in this subtree with no computed item.
+    #[cfg(test)]
+    pub(crate) fn uncached_nodes(&self) -> usize {
+        let uncached_children: usize = self
+            .children
+            .iter()
+            .flatten()
+            .map(|child| child.uncached_nodes())
+            .sum();
+        usize::from(self.item.with(Option::is_none)) + uncached_children
+    }
+
+    /// Returns the number of nodes in this subtree whose item needs to be
+    /// computed, including this one.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn stale_nodes(&self) -> usize {
+        if self.item.with(Option::is_some) {
+            return 0;
         }
 
-        // unwrapping is ok since we ensure it exists
-        Ref::map(self.item.borrow(), |item| item.as_ref().unwrap())
+        let stale_children: usize = self
+            .children
+            .iter()
+            .flatten()
+            .map(|child| child.stale_nodes())
+            .sum();
+        1 + stale_children
     }
 
     pub(crate) fn child_location(height: usize, position: u64) -> (usize, u64) {
@@ -74,6 +197,109 @@ where
         (child_index, child_pos)
     }
 
+    /// Returns the descendant of this node at the given `height` - relative
+    /// to this node - and at the given `index` within that height, if it
+    /// exists.
+    pub(crate) fn descendant(
+        &self,
+        height: usize,
+        index: u64,
+    ) -> Option<&Self> {
+        let mut node = self;
+
+        for h in 0..height {
+            let child_cap = capacity(A as u64, height - h - 1);
+
+            // Casting to a `usize` should be fine, since the index should be
+            // within the `[0, A[` bound anyway.
+            #[allow(clippy::cast_possible_truncation)]
+            let child_index = ((index / child_cap) % A as u64) as usize;
+
+            node = node.children[child_index].as_ref()?;
+        }
+
+        Some(node)
+    }
+
+    /// Returns a mutable reference to the descendant of this node at the
+    /// given `height` - relative to this node - and at the given `index`
+    /// within that height, if it exists.
+    pub(crate) fn descendant_mut(
+        &mut self,
+        height: usize,
+        index: u64,
+    ) -> Option<&mut Self> {
+        let mut node = self;
+
+        for h in 0..height {
+            let child_cap = capacity(A as u64, height - h - 1);
+
+            // Casting to a `usize` should be fine, since the index should be
+            // within the `[0, A[` bound anyway.
+            #[allow(clippy::cast_possible_truncation)]
+            let child_index = ((index / child_cap) % A as u64) as usize;
+
+            node = node.children[child_index].as_mut()?.make_mut();
+        }
+
+        Some(node)
+    }
+
+    /// Like [`Node::descendant_mut`], but creating the descendant and any of
+    /// its missing ancestors.
+    pub(crate) fn descendant_or_insert(
+        &mut self,
+        height: usize,
+        index: u64,
+    ) -> &mut Self {
+        let mut node = self;
+
+        for h in 0..height {
+            let child_cap = capacity(A as u64, height - h - 1);
+
+            // Casting to a `usize` should be fine, since the index should be
+            // within the `[0, A[` bound anyway.
+            #[allow(clippy::cast_possible_truncation)]
+            let child_index = ((index / child_cap) % A as u64) as usize;
+
+            node = node.children[child_index]
+                .get_or_insert_with(|| Link::new(Node::new()))
+                .make_mut();
+        }
+
+        node
+    }
+
+    /// Marks the items of the nodes on the path to the descendant at the given
+    /// `height` - relative to this node - and `index` as stale, removing the
+    /// descendant if it has no children. Returns if there are any children
+    /// left in this node.
+    pub(crate) fn invalidate_path(
+        &mut self,
+        height: usize,
+        index: u64,
+    ) -> bool {
+        if height == 0 {
+            return self.children.iter().any(Option::is_some);
+        }
+        self.item.set(None);
+
+        let child_cap = capacity(A as u64, height - 1);
+
+        // Casting to a `usize` should be fine, since the index should be
+        // within the `[0, A[` bound anyway.
+        #[allow(clippy::cast_possible_truncation)]
+        let child_index = ((index / child_cap) % A as u64) as usize;
+
+        if let Some(child) = &mut self.children[child_index] {
+            if !child.make_mut().invalidate_path(height - 1, index) {
+                self.children[child_index] = None;
+            }
+        }
+
+        self.children.iter().any(Option::is_some)
+    }
+
     pub(crate) fn insert(
         &mut self,
         height: usize,
@@ -81,21 +307,36 @@ where
         item: impl Into<T>,
     ) {
         if height == H {
-            self.item.replace(Some(item.into()));
+            *self.item.get_mut() = Some(item.into());
             return;
         }
-        self.item.replace(None);
+        self.item.set(None);
 
         let (child_index, child_pos) = Self::child_location(height, position);
 
-        let child = &mut self.children[child_index];
-        if child.is_none() {
-            *child = Some(Box::new(Node::new()));
+        let child = self.children[child_index]
+            .get_or_insert_with(|| Link::new(Node::new()));
+        Self::insert(child.make_mut(), height + 1, child_pos, item);
+    }
+
+    /// Returns a mutable reference to the element at the given position, if
+    /// it exists, marking the items of all nodes along the path as stale.
+    pub(crate) fn leaf_mut(
+        &mut self,
+        height: usize,
+        position: u64,
+    ) -> Option<&mut T> {
+        if height == H {
+            return self.item.get_mut().as_mut();
         }
 
-        // We just inserted a child at the given index.
-        let child = self.children[child_index].as_mut().unwrap();
-        Self::insert(child, height + 1, child_pos, item);
+        let (child_index, child_pos) = Self::child_location(height, position);
+
+        let child = self.children[child_index].as_mut()?;
+        let leaf = child.make_mut().leaf_mut(height + 1, child_pos)?;
+        *self.item.get_mut() = None;
+
+        Some(leaf)
     }
 
     /// Returns the removed element, together with if there are any siblings
@@ -104,45 +345,353 @@ where
     /// # Panics
     /// If an element does not exist at the given position.
     pub(crate) fn remove(&mut self, height: usize, position: u64) -> (T, bool) {
+        self.try_remove(height, position)
+            .expect("There should be an element at this position")
+    }
+
+    /// Returns the removed element, together with if there are any siblings
+    /// left in the branch, or `None` if an element does not exist at the
+    /// given position.
+    ///
+    /// When no element exists the structure of the node is left untouched,
+    /// although the items along the path may be marked as stale.
+    pub(crate) fn try_remove(
+        &mut self,
+        height: usize,
+        position: u64,
+    ) -> Option<(T, bool)> {
         if height == H {
-            // unwrapping is ok since leaves are always filled
-            let item = self.item.take().unwrap();
-            return (item, false);
+            let item = self.item.get_mut().take()?;
+            return Some((item, false));
         }
-        self.item.replace(None);
+        self.item.set(None);
 
         let (child_index, child_pos) = Self::child_location(height, position);
 
-        let child = self.children[child_index]
-            .as_mut()
-            .expect("There should be a child at this position");
+        let child = self.children[child_index].as_mut()?;
         let (removed_item, child_has_children) =
-            Self::remove(child, height + 1, child_pos);
+            Self::try_remove(child.make_mut(), height + 1, child_pos)?;
 
         if !child_has_children {
             self.children[child_index] = None;
         }
 
-        let mut has_children = false;
-        for child in &self.children {
-            if child.is_some() {
-                has_children = true;
-                break;
+        let has_children = self.children.iter().any(Option::is_some);
+
+        Some((removed_item, has_children))
+    }
+
+    /// Removes the elements at the given sorted `positions`, pushing them to
+    /// `removed` in order. Returns if there are any children left in the
+    /// node.
+    ///
+    /// Contrary to the other methods, `positions` are absolute positions in
+    /// the tree, allowing them to be passed down without being rewritten.
+    ///
+    /// # Panics
+    /// If an element does not exist at any of the given positions.
+    pub(crate) fn remove_batch(
+        &mut self,
+        height: usize,
+        positions: &[u64],
+        removed: &mut Vec<(u64, T)>,
+    ) -> bool {
+        if height == H {
+            // unwrapping is ok since leaves are always filled
+            let item = self.item.get_mut().take().unwrap();
+            removed.push((positions[0], item));
+            return false;
+        }
+        self.item.set(None);
+
+        let child_cap = capacity(A as u64, H - height - 1);
+
+        let mut rest = positions;
+        while let Some(&first) = rest.first() {
+            // Casting to a `usize` should be fine, since the index should be
+            // within the `[0, A[` bound anyway.
+            #[allow(clippy::cast_possible_truncation)]
+            let child_index = ((first / child_cap) % A as u64) as usize;
+
+            let len =
+                rest.partition_point(|p| p / child_cap == first / child_cap);
+            let (child_positions, tail) = rest.split_at(len);
+            rest = tail;
+
+            let child = self.children[child_index]
+                .as_mut()
+                .expect("There should be a child at this position");
+            if !child.make_mut().remove_batch(
+                height + 1,
+                child_positions,
+                removed,
+            ) {
+                self.children[child_index] = None;
+            }
+        }
+
+        self.children.iter().any(Option::is_some)
+    }
+
+    /// Grafts the children of the `other` node into this one, assuming the
+    /// two nodes hold no leaves at the same positions.
+    ///
+    /// Children present in only one of the nodes are moved without being
+    /// traversed, and only the items of the nodes present in both are marked
+    /// as stale.
+    pub(crate) fn graft(&mut self, other: Self) {
+        let mut changed = false;
+        for (child, other_child) in self.children.iter_mut().zip(other.children)
+        {
+            match (child, other_child) {
+                (_, None) => {}
+                (child @ None, other_child) => {
+                    *child = other_child;
+                    changed = true;
+                }
+                (Some(child), Some(other_child)) => {
+                    child.make_mut().graft(other_child.into_node());
+                    changed = true;
+                }
             }
         }
 
-        (removed_item, has_children)
+        if changed {
+            self.item.set(None);
+        }
+    }
+
+    /// Merges the `other` node into this one, calling `resolve` on the leaves
+    /// present in both nodes whose items differ.
+    ///
+    /// Children present in both nodes are always descended into, since equal
+    /// aggregates don't imply equal subtrees, unless they link to the very
+    /// same node.
+    pub(crate) fn merge_with<F>(
+        &mut self,
+        other: Self,
+        height: usize,
+        resolve: &F,
+    ) where
+        T: PartialEq,
+        F: Fn(T, T) -> T,
+    {
+        if height == H {
+            // unwrapping is ok since leaves are always filled
+            let ours = self.item.get_mut().take().unwrap();
+            let theirs = other.item.into_inner().unwrap();
+
+            let item = if ours == theirs {
+                ours
+            } else {
+                resolve(ours, theirs)
+            };
+            *self.item.get_mut() = Some(item);
+
+            return;
+        }
+
+        let mut changed = false;
+        for (child, other_child) in self.children.iter_mut().zip(other.children)
+        {
+            match (child, other_child) {
+                (_, None) => {}
+                (child @ None, other_child) => {
+                    *child = other_child;
+                    changed = true;
+                }
+                (Some(child), Some(other_child)) => {
+                    if !child.ptr_eq(&other_child) {
+                        child.make_mut().merge_with(
+                            other_child.into_node(),
+                            height + 1,
+                            resolve,
+                        );
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        if changed {
+            self.item.set(None);
+        }
+    }
+}
+
+/// A link from a node to one of its children.
+///
+/// Without the `shared` feature each node owns its children, meaning cloning
+/// a tree copies all of its nodes. With it, the children are reference
+/// counted and shared between the clones of a tree, and a node is only
+/// copied when it is mutated while shared - i.e. copy-on-write.
+#[cfg(not(feature = "shared"))]
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Link<T, const H: usize, const A: usize>(Box<Node<T, H, A>>);
+
+#[cfg(not(feature = "shared"))]
+impl<T, const H: usize, const A: usize> Link<T, H, A> {
+    pub(crate) fn new(node: Node<T, H, A>) -> Self {
+        Self(Box::new(node))
+    }
+
+    /// Returns a mutable reference to the linked node.
+    pub(crate) fn make_mut(&mut self) -> &mut Node<T, H, A> {
+        &mut self.0
+    }
+
+    /// Returns the linked node.
+    pub(crate) fn into_node(self) -> Node<T, H, A> {
+        *self.0
+    }
+
+    /// Returns `true` if both links are to the same node, which is never the
+    /// case for distinct owned nodes.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        core::ptr::eq(&raw const *self.0, &raw const *other.0)
+    }
+}
+
+#[cfg(not(feature = "shared"))]
+impl<T, const H: usize, const A: usize> Deref for Link<T, H, A> {
+    type Target = Node<T, H, A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "shared")]
+type CloneFn<T> = fn(&T) -> T;
+
+/// A link from a node to one of its children, shared between the clones of
+/// a tree and copied on write.
+#[cfg(feature = "shared")]
+pub(crate) struct Link<T, const H: usize, const A: usize> {
+    node: Arc<Node<T, H, A>>,
+    // Set on both links whenever the node is shared, which only happens when
+    // a tree is cloned and therefore `T: Clone`. This allows for the node to
+    // be copied on write without requiring `T: Clone` for every mutation.
+    clone: OnceLock<CloneFn<T>>,
+}
+
+#[cfg(feature = "shared")]
+impl<T, const H: usize, const A: usize> Link<T, H, A> {
+    pub(crate) fn new(node: Node<T, H, A>) -> Self {
+        Self {
+            node: Arc::new(node),
+            clone: OnceLock::new(),
+        }
+    }
+
+    /// Returns another link to the same node, copying it with the given
+    /// `clone` function once it is mutated.
+    fn share(&self, clone: CloneFn<T>) -> Self {
+        // the function is the same whenever it is set, so it may only be
+        // set once
+        let _ = self.clone.set(clone);
+        Self {
+            node: Arc::clone(&self.node),
+            clone: OnceLock::from(clone),
+        }
+    }
+
+    /// Returns a mutable reference to the linked node, copying it first if
+    /// it is shared with another link.
+    ///
+    /// The copy keeps the item of the node, and shares its children.
+    pub(crate) fn make_mut(&mut self) -> &mut Node<T, H, A> {
+        if Arc::get_mut(&mut self.node).is_none() {
+            let Some(&clone) = self.clone.get() else {
+                unreachable!("shared links always have a clone function");
+            };
+
+            let node = Node {
+                item: ItemCell::new(
+                    self.node.item.with(|item| item.as_ref().map(clone)),
+                ),
+                children: init_array(|i| {
+                    self.node.children[i]
+                        .as_ref()
+                        .map(|child| child.share(clone))
+                }),
+            };
+            *self = Self::new(node);
+        }
+
+        match Arc::get_mut(&mut self.node) {
+            Some(node) => node,
+            None => unreachable!("the node was just copied"),
+        }
+    }
+
+    /// Returns the linked node, copying it if it is shared with another
+    /// link.
+    pub(crate) fn into_node(mut self) -> Node<T, H, A> {
+        self.make_mut();
+        match Arc::try_unwrap(self.node) {
+            Ok(node) => node,
+            Err(_) => unreachable!("the node was just made unique"),
+        }
+    }
+
+    /// Returns `true` if both links are to the same node - e.g. a subtree
+    /// shared between the clones of a tree.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.node, &other.node)
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<T, const H: usize, const A: usize> Deref for Link<T, H, A> {
+    type Target = Node<T, H, A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<T, const H: usize, const A: usize> Clone for Link<T, H, A>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        self.share(T::clone)
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<T, const H: usize, const A: usize> PartialEq for Link<T, H, A>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.node, &other.node) || self.node == other.node
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<T, const H: usize, const A: usize> Eq for Link<T, H, A> where T: Eq {}
+
+impl<T, const H: usize, const A: usize> fmt::Debug for Link<T, H, A>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
     }
 }
 
 #[cfg(feature = "rkyv-impl")]
 mod rkyv_impl {
-    use super::Node;
+    use super::{ItemCell, Link, Node};
+    use crate::{init_array, Aggregate};
 
     use alloc::boxed::Box;
-    use core::cell::RefCell;
 
     use bytecheck::CheckBytes;
+    use rkyv::boxed::{ArchivedBox, BoxResolver};
     use rkyv::{
         out_field, ser::Serializer, Archive, Archived, Deserialize, Fallible,
         Resolver, Serialize,
@@ -158,9 +707,57 @@ mod rkyv_impl {
         children: Archived<[Option<Box<Node<T, H, A>>>; A]>,
     }
 
+    impl<T, const H: usize, const A: usize> ArchivedNode<T, H, A>
+    where
+        T: Archive,
+    {
+        /// Returns the archived item of the node, if it was computed at the
+        /// time the node was serialized.
+        pub(crate) fn item(&self) -> Option<&T::Archived> {
+            self.item.as_ref()
+        }
+
+        /// Returns a pointer to the archived item of the node pointed to by
+        /// `this`, without creating a reference to the node.
+        ///
+        /// # Safety
+        /// `this` must be valid for computing the address of its fields.
+        pub(crate) unsafe fn item_ptr(
+            this: *const Self,
+        ) -> *const Archived<Option<T>> {
+            core::ptr::addr_of!((*this).item)
+        }
+
+        /// Returns the archived child at the given `index`, if it exists.
+        pub(crate) fn child(&self, index: usize) -> Option<&Self> {
+            self.children[index].as_deref()
+        }
+
+        /// Returns the item of the node, deserializing it if it was archived,
+        /// and computing it from the children otherwise.
+        pub(crate) fn to_item(&self) -> T
+        where
+            T: Aggregate<A>,
+            T::Archived: Deserialize<T, rkyv::Infallible>,
+        {
+            if let Some(item) = self.item() {
+                return match item.deserialize(&mut rkyv::Infallible) {
+                    Ok(item) => item,
+                    Err(infallible) => match infallible {},
+                };
+            }
+
+            // only inner nodes may have no archived item
+            let items: [T; A] = init_array(|i| {
+                self.child(i).map_or(T::EMPTY_SUBTREE, Self::to_item)
+            });
+            T::aggregate(init_array(|i| &items[i]))
+        }
+    }
+
     pub struct NodeResolver<T: Archive, const H: usize, const A: usize> {
         item: Resolver<Option<T>>,
-        children: Resolver<[Option<Box<Node<T, H, A>>>; A]>,
+        children: Resolver<[Option<Link<T, H, A>>; A]>,
     }
 
     impl<T, const H: usize, const A: usize> Archive for Node<T, H, A>
@@ -179,9 +776,9 @@ mod rkyv_impl {
             let (item_pos, item) = out_field!(out.item);
             let (children_pos, children) = out_field!(out.children);
 
-            self.item
-                .borrow()
-                .resolve(pos + item_pos, resolver.item, item);
+            self.item.with(|item_ref| {
+                item_ref.resolve(pos + item_pos, resolver.item, item);
+            });
             self.children.resolve(
                 pos + children_pos,
                 resolver.children,
@@ -199,9 +796,7 @@ mod rkyv_impl {
             &self,
             serializer: &mut S,
         ) -> Result<Self::Resolver, S::Error> {
-            let item = self.item.borrow();
-
-            let item = item.serialize(serializer)?;
+            let item = self.item.with(|item| item.serialize(serializer))?;
             let children = self.children.serialize(serializer)?;
 
             Ok(Self::Resolver { item, children })
@@ -222,9 +817,57 @@ mod rkyv_impl {
             let item = self.item.deserialize(deserializer)?;
             let children = self.children.deserialize(deserializer)?;
             Ok(Node {
-                item: RefCell::new(item),
+                item: ItemCell::new(item),
                 children,
             })
         }
     }
+
+    /// Links are archived as boxes, meaning the archived tree is the same
+    /// whether its nodes are shared or not.
+    impl<T, const H: usize, const A: usize> Archive for Link<T, H, A>
+    where
+        T: Archive,
+    {
+        type Archived = ArchivedBox<ArchivedNode<T, H, A>>;
+        type Resolver = BoxResolver<()>;
+
+        unsafe fn resolve(
+            &self,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedBox::resolve_from_ref(&**self, pos, resolver, out);
+        }
+    }
+
+    impl<S, T, const H: usize, const A: usize> Serialize<S> for Link<T, H, A>
+    where
+        S: Serializer + ?Sized,
+        T: Archive + Serialize<S>,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedBox::serialize_from_ref(&**self, serializer)
+        }
+    }
+
+    impl<D, T, const H: usize, const A: usize> Deserialize<Link<T, H, A>, D>
+        for ArchivedBox<ArchivedNode<T, H, A>>
+    where
+        D: Fallible + ?Sized,
+        T: Archive,
+        Archived<T>: Deserialize<T, D>,
+    {
+        fn deserialize(
+            &self,
+            deserializer: &mut D,
+        ) -> Result<Link<T, H, A>, D::Error> {
+            let node = self.get().deserialize(deserializer)?;
+            Ok(Link::new(node))
+        }
+    }
 }
@@ -0,0 +1,158 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "rkyv-impl")]
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{capacity, init_array, trace, Aggregate, Node, Tree};
+
+/// A proof that no leaf exists at a position of a merkle tree.
+///
+/// The proof holds the path from the root down to the highest empty subtree
+/// containing the position, showing its item is the empty subtree. In a
+/// sparse tree this path is usually much shorter than a full opening.
+///
+/// Created using [`Tree::non_membership`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(Archive, Serialize, Deserialize),
+    archive_attr(derive(CheckBytes))
+)]
+pub struct NonMembershipProof<T, const H: usize, const A: usize> {
+    root: T,
+    position: u64,
+    branch: Vec<[T; A]>,
+}
+
+impl<T, const H: usize, const A: usize> NonMembershipProof<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// # Panics
+    /// If the given `position` is in the `tree`, or is larger or equal to its
+    /// capacity.
+    pub(crate) fn new(tree: &Tree<T, H, A>, position: u64) -> Self {
+        let mut branch = Vec::new();
+
+        let mut node = &tree.root;
+        let mut relative = position;
+        for height in 0..H {
+            branch.push(init_array(|i| match &node.children[i] {
+                Some(child) => child.item().clone(),
+                None => T::EMPTY_SUBTREE,
+            }));
+
+            let (child_index, child_pos) =
+                Node::<T, H, A>::child_location(height, relative);
+            match &node.children[child_index] {
+                Some(child) => node = child,
+                None => break,
+            }
+            relative = child_pos;
+        }
+
+        trace::span!("non_membership", position, nodes = branch.len() + 1);
+
+        Self {
+            root: tree.root.item().clone(),
+            position,
+            branch,
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> NonMembershipProof<T, H, A> {
+    /// Returns the root of the proof.
+    #[must_use]
+    pub const fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the position proven to hold no leaf.
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the levels of the path, from the root down to the level
+    /// holding the empty subtree.
+    #[must_use]
+    pub fn branch(&self) -> &[[T; A]] {
+        &self.branch
+    }
+
+    /// Verify the proof is cryptographically correct, i.e. that the path
+    /// leads from an empty subtree containing the position up to the root.
+    pub fn verify(&self) -> bool
+    where
+        T: Aggregate<A> + PartialEq,
+    {
+        if self.branch.len() > H || self.position >= capacity(A as u64, H) {
+            return false;
+        }
+
+        let mut item = T::EMPTY_SUBTREE;
+        for (height, level) in self.branch.iter().enumerate().rev() {
+            let index = self.position / capacity(A as u64, H - height - 1);
+            // the remainder is always smaller than the arity
+            #[allow(clippy::cast_possible_truncation)]
+            let index = (index % A as u64) as usize;
+
+            if item != level[index] {
+                return false;
+            }
+            item = T::aggregate(init_array(|i| &level[i]));
+        }
+
+        item == self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::Sum;
+    use crate::{Tree, TreeBuilder};
+
+    const H: usize = 3;
+    const A: usize = 2;
+
+    #[test]
+    fn non_membership() {
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves([(0, Sum(1)), (1, Sum(2)), (2, Sum(4))])
+            .build();
+
+        // the sibling of a leaf needs the whole path
+        let proof = tree.non_membership(3).unwrap();
+        assert_eq!(proof.root(), &Sum(7));
+        assert_eq!(proof.position(), 3);
+        assert_eq!(proof.branch().len(), H);
+        assert!(proof.verify());
+
+        // the right half of the tree is empty
+        let proof = tree.non_membership(6).unwrap();
+        assert_eq!(proof.branch(), [[Sum(7), Sum(0)]]);
+        assert!(proof.verify());
+
+        // the proof is bound to its position
+        let mut moved = proof.clone();
+        moved.position = 2;
+        assert!(!moved.verify());
+
+        assert!(tree.non_membership(1).is_none());
+        assert!(tree.non_membership(8).is_none());
+
+        let empty = Tree::<Sum, H, A>::new();
+        let proof = empty.non_membership(5).unwrap();
+        assert_eq!(proof.branch().len(), 1);
+        assert!(proof.verify());
+    }
+}
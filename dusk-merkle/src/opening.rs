@@ -4,18 +4,216 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use crate::{init_array, Aggregate, Node, Tree};
+#[cfg(feature = "rkyv-impl")]
+use crate::ArchivedTree;
+use crate::{
+    init_array, trace, Aggregate, Fingerprint, FingerprintError, MultiOpening,
+    Node, Tree, FORMAT_VERSION,
+};
 
+use alloc::boxed::Box;
 use alloc::vec::Vec;
+use core::fmt;
 
 #[cfg(feature = "rkyv-impl")]
 use bytecheck::CheckBytes;
 use dusk_bytes::{DeserializableSlice, Error as BytesError, Serializable};
 #[cfg(feature = "rkyv-impl")]
 use rkyv::{Archive, Deserialize, Serialize};
+#[cfg(feature = "subtle")]
+use subtle::{Choice, ConstantTimeEq};
+
+/// The error returned when deserializing a malformed [`Opening`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpeningError {
+    /// The buffer doesn't have the expected length.
+    BadLength {
+        /// The length of the buffer.
+        found: usize,
+        /// The length the buffer was expected to have.
+        expected: usize,
+    },
+    /// The number of trimmed levels is not smaller than the height.
+    InvalidTrimmedLevels(usize),
+    /// The root could not be decoded.
+    InvalidRoot,
+    /// An item of the branch could not be decoded.
+    InvalidItem {
+        /// The level of the branch holding the item.
+        level: usize,
+        /// The index of the item within its level.
+        index: usize,
+    },
+    /// A position of the path is not smaller than the arity.
+    InvalidPosition {
+        /// The level of the branch the position is for.
+        level: usize,
+        /// The decoded position.
+        position: usize,
+    },
+    /// The buffer is longer than allowed by the [`DecodeLimits`].
+    TooLong {
+        /// The length of the buffer.
+        found: usize,
+        /// The maximum length allowed.
+        limit: usize,
+    },
+    /// The opening holds more items than allowed by the [`DecodeLimits`].
+    TooManyItems {
+        /// The number of items in the opening.
+        found: usize,
+        /// The maximum number of items allowed.
+        limit: usize,
+    },
+    /// The opening was produced by an incompatible tree.
+    Fingerprint(FingerprintError),
+    /// The header of the opening doesn't match the shape of the tree it is
+    /// deserialized for.
+    Header(HeaderError),
+}
+
+impl fmt::Display for OpeningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadLength { found, expected } => {
+                write!(f, "bad length: expected {expected}, found {found}")
+            }
+            Self::InvalidTrimmedLevels(trimmed) => {
+                write!(f, "invalid number of trimmed levels: {trimmed}")
+            }
+            Self::InvalidRoot => write!(f, "invalid root"),
+            Self::InvalidItem { level, index } => {
+                write!(f, "invalid item {index} at level {level}")
+            }
+            Self::InvalidPosition { level, position } => {
+                write!(f, "invalid position {position} at level {level}")
+            }
+            Self::TooLong { found, limit } => {
+                write!(f, "too long: limit is {limit}, found {found}")
+            }
+            Self::TooManyItems { found, limit } => {
+                write!(f, "too many items: limit is {limit}, found {found}")
+            }
+            Self::Fingerprint(err) => write!(f, "{err}"),
+            Self::Header(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OpeningError {}
+
+/// The error returned when the header of a serialized [`Opening`] doesn't
+/// match the opening it is deserialized as.
+///
+/// This happens when an opening is deserialized for a tree of a different
+/// height or arity than the one it was produced by, or when it was
+/// serialized in another format version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeaderError {
+    /// The format versions differ.
+    Version {
+        /// The expected format version.
+        expected: u32,
+        /// The format version found.
+        found: u32,
+    },
+    /// The heights differ.
+    Height {
+        /// The expected height.
+        expected: u32,
+        /// The height found.
+        found: u32,
+    },
+    /// The arities differ.
+    Arity {
+        /// The expected arity.
+        expected: u32,
+        /// The arity found.
+        found: u32,
+    },
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (parameter, expected, found) = match self {
+            Self::Version { expected, found } => ("version", expected, found),
+            Self::Height { expected, found } => ("height", expected, found),
+            Self::Arity { expected, found } => ("arity", expected, found),
+        };
+        write!(
+            f,
+            "header mismatch: expected {parameter} {expected}, found {found}"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HeaderError {}
+
+impl From<OpeningError> for BytesError {
+    fn from(err: OpeningError) -> Self {
+        match err {
+            OpeningError::BadLength { found, expected } => {
+                BytesError::BadLength { found, expected }
+            }
+            _ => BytesError::InvalidData,
+        }
+    }
+}
+
+/// Limits enforced when deserializing openings, allowing for oversized
+/// inputs from untrusted sources to be rejected before being decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeLimits {
+    /// The maximum length of the serialized input, in bytes.
+    pub max_len: usize,
+    /// The maximum number of items - including the root - to decode.
+    pub max_items: usize,
+}
+
+impl DecodeLimits {
+    /// No limits at all.
+    pub const UNLIMITED: Self = Self::new(usize::MAX, usize::MAX);
+
+    /// Create new limits with the given maximum length and number of items.
+    #[must_use]
+    pub const fn new(max_len: usize, max_items: usize) -> Self {
+        Self { max_len, max_items }
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), OpeningError> {
+        if len > self.max_len {
+            return Err(OpeningError::TooLong {
+                found: len,
+                limit: self.max_len,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_items(&self, items: usize) -> Result<(), OpeningError> {
+        if items > self.max_items {
+            return Err(OpeningError::TooManyItems {
+                found: items,
+                limit: self.max_items,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
 
 /// An opening for a given position in a merkle tree.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+///
+/// The branch is stored on the heap, since for large trees it can grow to
+/// several kilobytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(
     feature = "rkyv-impl",
     derive(Archive, Serialize, Deserialize),
@@ -23,7 +221,7 @@ use rkyv::{Archive, Deserialize, Serialize};
 )]
 pub struct Opening<T, const H: usize, const A: usize> {
     root: T,
-    branch: [[T; A]; H],
+    branch: Box<[[T; A]; H]>,
     positions: [usize; H],
 }
 
@@ -34,19 +232,136 @@ where
     /// # Panics
     /// If the given `position` is not in the `tree`.
     pub(crate) fn new(tree: &Tree<T, H, A>, position: u64) -> Self {
+        trace::span!("opening", position, nodes = H + 1);
+
         let positions = [0; H];
-        let branch = init_array(|_| init_array(|_| T::EMPTY_SUBTREE));
+        let branch = empty_branch();
 
         let mut opening = Self {
             root: tree.root.item().clone(),
             branch,
             positions,
         };
-        fill_opening(&mut opening, &tree.root, 0, position);
+        fill_path(
+            &tree.root,
+            position,
+            H,
+            &mut opening.branch,
+            &mut opening.positions,
+        );
 
         opening
     }
 
+    /// Create an opening for the given `position` in the archived `tree`,
+    /// deserializing only the items on its path, and computing those that
+    /// weren't archived.
+    ///
+    /// # Panics
+    /// If the given `position` is not in the `tree`.
+    #[cfg(feature = "rkyv-impl")]
+    pub(crate) fn from_archived(
+        tree: &ArchivedTree<T, H, A>,
+        mut position: u64,
+    ) -> Self
+    where
+        T: Archive,
+        T::Archived: Deserialize<T, rkyv::Infallible>,
+    {
+        trace::span!("archived_opening", position, nodes = H + 1);
+
+        let mut branch = empty_branch();
+        let mut positions = [0; H];
+
+        let mut node = &tree.root;
+        for height in 0..H {
+            for (index, item) in branch[height].iter_mut().enumerate() {
+                *item = node.child(index).map_or(
+                    T::EMPTY_SUBTREE,
+                    rkyv::Archived::<Node<T, H, A>>::to_item,
+                );
+            }
+
+            let (child_index, child_pos) =
+                Node::<T, H, A>::child_location(height, position);
+            node = node
+                .child(child_index)
+                .expect("There should be a child at this position");
+            positions[height] = child_index;
+            position = child_pos;
+        }
+
+        Self {
+            root: tree.root.to_item(),
+            branch,
+            positions,
+        }
+    }
+
+    /// Create a [`MultiOpening`] for the given `positions` in the `tree`,
+    /// sharing the items common to their paths.
+    ///
+    /// The positions may be given in any order, and duplicates are ignored.
+    /// Returns `None` if no positions are given, or if any of them is not in
+    /// the tree.
+    pub fn batch(
+        tree: &Tree<T, H, A>,
+        positions: &[u64],
+    ) -> Option<MultiOpening<T, H, A>> {
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        positions.dedup();
+
+        if positions.is_empty() || !positions.iter().all(|p| tree.contains(*p))
+        {
+            return None;
+        }
+
+        Some(MultiOpening::new(tree, positions))
+    }
+
+    /// Create an opening from its parts, with the given `fill` function
+    /// returning the items of each level of the branch.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn from_fn<F>(root: T, positions: [usize; H], fill: F) -> Self
+    where
+        F: Fn(usize) -> [T; A],
+    {
+        let mut branch = empty_branch();
+        for (height, level) in branch.iter_mut().enumerate() {
+            *level = fill(height);
+        }
+
+        Self {
+            root,
+            branch,
+            positions,
+        }
+    }
+
+    /// Recomputes the root and the top `levels` levels of the branch from the
+    /// given `tree`.
+    ///
+    /// # Panics
+    /// If the given `position` is not in the `tree`.
+    pub(crate) fn refresh(
+        &mut self,
+        tree: &Tree<T, H, A>,
+        position: u64,
+        levels: usize,
+    ) {
+        trace::span!("refresh_opening", position, nodes = levels + 1);
+
+        self.root = tree.root.item().clone();
+        fill_path(
+            &tree.root,
+            position,
+            levels,
+            &mut self.branch,
+            &mut self.positions,
+        );
+    }
+
     /// Returns the root of the opening.
     pub fn root(&self) -> &T {
         &self.root
@@ -62,56 +377,168 @@ where
         &self.positions
     }
 
+    /// Returns the position of the leaf in the tree, reconstructed from the
+    /// indices of the path.
+    ///
+    /// The indices are part of the serialized opening, meaning the position
+    /// is kept when an opening is serialized and can be bound to a claimed
+    /// one after deserializing it.
+    pub fn position(&self) -> u64 {
+        self.positions
+            .iter()
+            .fold(0, |position, &index| position * A as u64 + index as u64)
+    }
+
+    /// Returns the leaf of the opening, i.e. the item on the path at the
+    /// bottom of the branch.
+    pub(crate) fn leaf(&self) -> Option<&T> {
+        let level = self.branch.last()?;
+        level.get(self.positions[H - 1])
+    }
+
     /// Verify the given item is the leaf of the opening, and that the opening
     /// is cryptographically correct.
     pub fn verify(&self, item: impl Into<T>) -> bool
     where
         T: PartialEq,
+    {
+        verify_path(&self.root, 0, &self.branch, &self.positions, item.into())
+    }
+
+    /// Verify the given item is the leaf of the opening, and that the opening
+    /// is cryptographically correct, in constant time with respect to the
+    /// position of the leaf.
+    ///
+    /// This is meant for openings of secret positions verified natively, e.g.
+    /// of private notes. Unlike [`verify`], all levels of the branch are
+    /// checked without returning early, and every item of each level is
+    /// compared with the computed item, meaning neither the timing nor the
+    /// memory access pattern depend on the path. The aggregation and the
+    /// comparison of the items are only as constant time as the
+    /// implementations of [`Aggregate`] and [`ConstantTimeEq`] for `T`.
+    ///
+    /// [`verify`]: Opening::verify
+    #[cfg(feature = "subtle")]
+    pub fn verify_ct(&self, item: impl Into<T>) -> bool
+    where
+        T: ConstantTimeEq,
     {
         let mut item = item.into();
+        let mut valid = Choice::from(1);
 
         for h in (0..H).rev() {
             let level = &self.branch[h];
-            let position = self.positions[h];
+            let position = self.positions[h] as u64;
 
-            // if the computed item doesn't match the stored item at the given
-            // position, the opening is incorrect
-            if item != level[position] {
-                return false;
+            // the computed item must be at the position, which itself must be
+            // one of the positions of the level
+            let mut found = Choice::from(0);
+            for (i, level_item) in level.iter().enumerate() {
+                let at_position = (i as u64).ct_eq(&position);
+                found |= at_position;
+                valid &= !at_position | item.ct_eq(level_item);
             }
+            valid &= found;
 
-            let empty_subtree = &T::EMPTY_SUBTREE;
+            item = T::aggregate(level.each_ref());
+        }
 
-            let mut item_refs = [empty_subtree; A];
-            item_refs.iter_mut().zip(&self.branch[h]).for_each(
-                |(r, item_ref)| {
-                    *r = item_ref;
-                },
-            );
+        bool::from(valid & self.root.ct_eq(&item))
+    }
 
-            item = T::aggregate(item_refs);
-        }
+    /// Verify the given item is the leaf of the opening, checking only the
+    /// levels between the leaf and the given `node` at the given `height` on
+    /// its path - the root being at height zero.
+    ///
+    /// This is meant for verifiers that already trust the item of an inner
+    /// node - e.g. the root of a chunk of the tree validated earlier - and
+    /// need not check the upper levels of the path again for each leaf below
+    /// it. The first `height` entries of [`positions`] locate the node, and
+    /// should be checked to be the ones of the trusted node.
+    ///
+    /// Returns `false` if the `height` is larger than the height of the tree.
+    ///
+    /// [`positions`]: Opening::positions
+    pub fn verify_partial(
+        &self,
+        item: impl Into<T>,
+        height: usize,
+        node: &T,
+    ) -> bool
+    where
+        T: PartialEq,
+    {
+        height <= H
+            && verify_path(
+                node,
+                height,
+                &self.branch,
+                &self.positions,
+                item.into(),
+            )
+    }
+
+    /// Returns the number of top levels of the branch whose siblings are all
+    /// empty subtrees.
+    ///
+    /// The item on the path of such a level is the aggregate of the level
+    /// below, so the whole level can be recomputed from the levels below it
+    /// and its position. The leaf level is never counted.
+    fn trimmable_levels(&self) -> usize
+    where
+        T: PartialEq,
+    {
+        let empty_subtree = &T::EMPTY_SUBTREE;
 
-        self.root == item
+        (0..H.saturating_sub(1))
+            .take_while(|&h| {
+                self.branch[h].iter().enumerate().all(|(i, item)| {
+                    i == self.positions[h] || item == empty_subtree
+                })
+            })
+            .count()
     }
 
     /// Serialize an [`Opening`] to a vector of bytes.
+    ///
+    /// The top levels of the branch whose siblings are all empty subtrees are
+    /// omitted, since they can be recomputed when deserializing. This makes
+    /// openings of sparse trees considerably smaller.
+    ///
+    /// The serialized opening is composed of:
+    /// - a header with the [`FORMAT_VERSION`], the height and the arity, each
+    ///   as a `u32`
+    /// - the root
+    /// - the number of trimmed levels, as a `u32`
+    /// - the items of the levels that were not trimmed
+    /// - the positions of all levels, each as a `u32`
     // Once the new implementation of the `Serializable` trait becomes
     // available, we will want that instead, but for the time being we use
     // this implementation.
     pub fn to_var_bytes<const T_SIZE: usize>(&self) -> Vec<u8>
     where
-        T: Serializable<T_SIZE>,
+        T: Serializable<T_SIZE> + PartialEq,
     {
-        let mut bytes = Vec::with_capacity(
-            (1 + H * A) * T_SIZE + H * (u32::BITS as usize / 8),
-        );
+        let trimmed = self.trimmable_levels();
+
+        let mut bytes =
+            Vec::with_capacity(Self::serialized_len::<T_SIZE>(trimmed));
+
+        // serialize header
+        for field in Self::header() {
+            bytes.extend(&field.to_bytes());
+        }
 
         // serialize root
         bytes.extend(&self.root.to_bytes());
 
+        // serialize the number of trimmed levels, which is always smaller
+        // than the height
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.extend(&(trimmed as u32).to_bytes());
+
         // serialize branch
-        for level in &self.branch {
+        for level in &self.branch[trimmed..] {
             for item in level {
                 bytes.extend(&item.to_bytes());
             }
@@ -128,48 +555,111 @@ where
         bytes
     }
 
-    /// Deserialize an [`Opening`] from a slice of bytes.
+    /// Deserialize an [`Opening`] from a slice of bytes, re-expanding any
+    /// trimmed levels.
+    ///
+    /// Fails to compile under the same conditions as [`Tree::new`].
     ///
     /// # Errors
     ///
-    /// Will return [`dusk_bytes::Error`] in case of a deserialization error.
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed.
     // Once the new implementation of the `Serializable` trait becomes
     // available, we will want that instead, but for the time being we use
     // this implementation.
     pub fn from_slice<const T_SIZE: usize>(
         buf: &[u8],
-    ) -> Result<Self, BytesError>
+    ) -> Result<Self, OpeningError>
     where
         T: Serializable<T_SIZE>,
         <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
-        dusk_bytes::Error: From<<T as Serializable<T_SIZE>>::Error>,
     {
-        let expected_len = (1 + H * A) * T_SIZE + H * (u32::BITS as usize / 8);
-        if buf.len() != expected_len {
-            return Err(BytesError::BadLength {
-                found: (buf.len()),
-                expected: (expected_len),
-            });
-        }
+        Self::from_slice_with_limits(buf, &DecodeLimits::UNLIMITED)
+    }
+
+    /// Deserialize an [`Opening`] from a slice of bytes, like [`from_slice`],
+    /// rejecting inputs exceeding the given `limits` before decoding them.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed, or which limit was exceeded.
+    ///
+    /// [`from_slice`]: Opening::from_slice
+    pub fn from_slice_with_limits<const T_SIZE: usize>(
+        buf: &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, OpeningError>
+    where
+        T: Serializable<T_SIZE>,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = Node::<T, H, A>::VALID_PARAMS;
+
+        limits.check_len(buf.len())?;
 
         let mut bytes = buf;
 
+        // check the header first, since openings of another shape also have
+        // another length
+        Self::read_header(&mut bytes)?;
+
+        let min_len = Self::serialized_len::<T_SIZE>(H);
+        if buf.len() < min_len {
+            return Err(OpeningError::BadLength {
+                found: buf.len(),
+                expected: min_len,
+            });
+        }
+
         // deserialize root
-        let root = T::from_reader(&mut bytes)?;
+        let root = T::from_reader(&mut bytes)
+            .map_err(|_| OpeningError::InvalidRoot)?;
+
+        // deserialize the number of trimmed levels
+        let trimmed = read_u32(&mut bytes);
+        if trimmed >= H.max(1) {
+            return Err(OpeningError::InvalidTrimmedLevels(trimmed));
+        }
+
+        // the root and the items of the levels that were not trimmed
+        limits.check_items(1 + (H - trimmed) * A)?;
+
+        let expected_len = Self::serialized_len::<T_SIZE>(trimmed);
+        if buf.len() != expected_len {
+            return Err(OpeningError::BadLength {
+                found: buf.len(),
+                expected: expected_len,
+            });
+        }
 
         // deserialize branch
-        let mut branch: [[T; A]; H] =
-            init_array(|_| init_array(|_| T::EMPTY_SUBTREE));
-        for level in &mut branch {
-            for item in &mut *level {
-                *item = T::from_reader(&mut bytes)?;
+        let mut branch = empty_branch::<T, H, A>();
+        for (level, items) in branch.iter_mut().enumerate().skip(trimmed) {
+            for (index, item) in items.iter_mut().enumerate() {
+                *item = T::from_reader(&mut bytes)
+                    .map_err(|_| OpeningError::InvalidItem { level, index })?;
             }
         }
 
         // deserialize positions
         let mut positions = [0usize; H];
-        for pos in &mut positions {
-            *pos = u32::from_reader(&mut bytes)? as usize;
+        for (level, pos) in positions.iter_mut().enumerate() {
+            *pos = read_u32(&mut bytes);
+            if *pos >= A {
+                return Err(OpeningError::InvalidPosition {
+                    level,
+                    position: *pos,
+                });
+            }
+        }
+
+        // re-expand the trimmed levels, from the bottom up
+        for h in (0..trimmed).rev() {
+            let (upper, lower) = branch.split_at_mut(h + 1);
+            let item_refs = init_array(|i| &lower[0][i]);
+            upper[h][positions[h]] = T::aggregate(item_refs);
         }
 
         Ok(Self {
@@ -178,102 +668,924 @@ where
             positions,
         })
     }
-}
 
-fn fill_opening<T, const H: usize, const A: usize>(
-    opening: &mut Opening<T, H, A>,
-    node: &Node<T, H, A>,
-    height: usize,
-    position: u64,
-) where
-    T: Aggregate<A> + Clone,
-{
-    if height == H {
-        return;
-    }
+    /// Verify the given `leaf` is the leaf of the opening serialized in
+    /// `proof`, and that the opening leads to the given `root`, without
+    /// deserializing it into an [`Opening`].
+    ///
+    /// The levels of the serialized opening are decoded and checked one at a
+    /// time, from the leaf up to the root, meaning this never allocates. This
+    /// is meant for hot verification paths, such as openings received from
+    /// the network, and for FFI and wasm boundaries where only bytes are at
+    /// hand. Levels above the first one failing the check are not decoded.
+    ///
+    /// The root and the leaf are compared in their serialized form, so `T`
+    /// should have a single byte representation per item.
+    ///
+    /// Fails to compile under the same conditions as [`Tree::new`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed.
+    pub fn verify_raw<const T_SIZE: usize>(
+        root: &[u8; T_SIZE],
+        proof: &[u8],
+        leaf: &[u8; T_SIZE],
+    ) -> Result<bool, OpeningError>
+    where
+        T: Serializable<T_SIZE> + PartialEq,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = Node::<T, H, A>::VALID_PARAMS;
 
-    let (child_index, child_pos) =
-        Node::<T, H, A>::child_location(height, position);
-    let child = node.children[child_index]
-        .as_ref()
-        .expect("There should be a child at this position");
+        let mut bytes = proof;
 
-    fill_opening(opening, child, height + 1, child_pos);
+        Self::read_header(&mut bytes)?;
 
-    for i in 0..A {
-        if let Some(child) = &node.children[i] {
-            opening.branch[height][i] = child.item().clone();
+        let min_len = Self::serialized_len::<T_SIZE>(H);
+        if proof.len() < min_len {
+            return Err(OpeningError::BadLength {
+                found: proof.len(),
+                expected: min_len,
+            });
         }
-    }
-    opening.positions[height] = child_index;
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let proof_root = &bytes[..T_SIZE];
+        T::from_reader(&mut bytes).map_err(|_| OpeningError::InvalidRoot)?;
 
-    const H: usize = 4;
-    const A: usize = 2;
-    const TREE_CAP: usize = A.pow(H as u32);
+        let trimmed = read_u32(&mut bytes);
+        if trimmed >= H.max(1) {
+            return Err(OpeningError::InvalidTrimmedLevels(trimmed));
+        }
 
-    /// A string type that is on the stack, and holds a string of a size as
-    /// large as the tree.
-    #[derive(Clone, Copy, PartialEq)]
-    struct String {
-        chars: [char; TREE_CAP],
-        len: usize,
-    }
+        let expected_len = Self::serialized_len::<T_SIZE>(trimmed);
+        if proof.len() != expected_len {
+            return Err(OpeningError::BadLength {
+                found: proof.len(),
+                expected: expected_len,
+            });
+        }
 
-    impl From<char> for String {
-        fn from(c: char) -> Self {
-            let mut chars = ['0'; TREE_CAP];
-            chars[0] = c;
-            Self { chars, len: 1 }
+        let level_len = A * T_SIZE;
+        let (levels, mut position_bytes) =
+            bytes.split_at((H - trimmed) * level_len);
+
+        let mut positions = [0usize; H];
+        for (level, pos) in positions.iter_mut().enumerate() {
+            *pos = read_u32(&mut position_bytes);
+            if *pos >= A {
+                return Err(OpeningError::InvalidPosition {
+                    level,
+                    position: *pos,
+                });
+            }
         }
-    }
 
-    const EMPTY_ITEM: String = String {
-        chars: ['0'; TREE_CAP],
-        len: 0,
-    };
+        // the root of the opening must be the given one
+        if proof_root != root {
+            return Ok(false);
+        }
 
-    /// A simple aggregator that concatenates strings.
-    impl Aggregate<A> for String {
-        const EMPTY_SUBTREE: Self = EMPTY_ITEM;
+        let empty_subtree = &T::EMPTY_SUBTREE;
+        let mut items: [T; A] = init_array(|_| T::EMPTY_SUBTREE);
+        let mut computed: Option<T> = None;
 
-        fn aggregate(items: [&Self; A]) -> Self {
-            items.into_iter().fold(EMPTY_ITEM, |mut acc, s| {
-                acc.chars[acc.len..acc.len + s.len]
-                    .copy_from_slice(&s.chars[..s.len]);
-                acc.len += s.len;
-                acc
-            })
+        // check the levels that were not trimmed, from the bottom up
+        for level in (trimmed..H).rev() {
+            let offset = (level - trimmed) * level_len;
+            let mut level_bytes = &levels[offset..offset + level_len];
+
+            for (index, item) in items.iter_mut().enumerate() {
+                *item = T::from_reader(&mut level_bytes)
+                    .map_err(|_| OpeningError::InvalidItem { level, index })?;
+            }
+
+            let position = positions[level];
+            let on_path = if let Some(computed) = &computed {
+                *computed == items[position]
+            } else {
+                let start = offset + position * T_SIZE;
+                levels[start..start + T_SIZE] == leaf[..]
+            };
+            if !on_path {
+                return Ok(false);
+            }
+
+            computed = Some(T::aggregate(init_array(|i| &items[i])));
         }
+
+        // only happens with a height of zero, where the leaf is the root
+        let Some(mut computed) = computed else {
+            return Ok(leaf == root);
+        };
+
+        // the trimmed levels hold only empty subtrees besides the path
+        for level in (0..trimmed).rev() {
+            let mut item_refs = [empty_subtree; A];
+            item_refs[positions[level]] = &computed;
+            computed = T::aggregate(item_refs);
+        }
+
+        Ok(computed.to_bytes() == *root)
     }
 
-    type TestTree = Tree<String, H, A>;
+    /// Serialize an [`Opening`] to a vector of bytes, prefixed by the given
+    /// `fingerprint` of the tree it was produced by.
+    ///
+    /// This allows for the opening to be checked for compatibility when
+    /// deserializing it with [`from_fingerprinted_slice`], e.g. after the
+    /// tree has been snapshotted and restored.
+    ///
+    /// [`from_fingerprinted_slice`]: Opening::from_fingerprinted_slice
+    pub fn to_fingerprinted_bytes<const T_SIZE: usize>(
+        &self,
+        fingerprint: &Fingerprint,
+    ) -> Vec<u8>
+    where
+        T: Serializable<T_SIZE> + PartialEq,
+    {
+        let opening = self.to_var_bytes();
 
-    #[test]
-    #[allow(clippy::cast_possible_truncation)]
-    fn opening_verify() {
-        const LETTERS: &[char] = &[
-            'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
-            'N', 'O', 'P',
-        ];
+        let mut bytes = Vec::with_capacity(Fingerprint::SIZE + opening.len());
+        bytes.extend(fingerprint.to_bytes());
+        bytes.extend(opening);
 
-        let mut tree = TestTree::new();
-        let cap = tree.capacity();
+        bytes
+    }
 
-        for i in 0..cap {
-            tree.insert(i, LETTERS[i as usize]);
+    /// Deserialize an [`Opening`] serialized with [`to_fingerprinted_bytes`],
+    /// checking its fingerprint is the `expected` one before decoding it.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OpeningError::Fingerprint`] if the fingerprint doesn't
+    /// match, or another [`OpeningError`] describing which part of the
+    /// opening is malformed.
+    ///
+    /// [`to_fingerprinted_bytes`]: Opening::to_fingerprinted_bytes
+    pub fn from_fingerprinted_slice<const T_SIZE: usize>(
+        buf: &[u8],
+        expected: &Fingerprint,
+    ) -> Result<Self, OpeningError>
+    where
+        T: Serializable<T_SIZE>,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        if buf.len() < Fingerprint::SIZE {
+            return Err(OpeningError::BadLength {
+                found: buf.len(),
+                expected: Fingerprint::SIZE + Self::serialized_len::<T_SIZE>(H),
+            });
         }
 
-        for pos in 0..cap {
-            let opening = tree
-                .opening(pos)
-                .expect("There must be an opening for an existing item");
+        let (fingerprint, opening) = buf.split_at(Fingerprint::SIZE);
+        let Ok(fingerprint) = Fingerprint::from_slice(fingerprint) else {
+            unreachable!("The bytes should have been split at 16");
+        };
+        fingerprint
+            .check(expected)
+            .map_err(OpeningError::Fingerprint)?;
 
-            assert!(
+        Self::from_slice(opening).map_err(|err| match err {
+            OpeningError::BadLength { found, expected } => {
+                OpeningError::BadLength {
+                    found: found + Fingerprint::SIZE,
+                    expected: expected + Fingerprint::SIZE,
+                }
+            }
+            err => err,
+        })
+    }
+
+    /// Returns the length of a serialized opening with the given number of
+    /// trimmed levels.
+    const fn serialized_len<const T_SIZE: usize>(trimmed: usize) -> usize {
+        HEADER_SIZE + (1 + (H - trimmed) * A) * T_SIZE + (1 + H) * U32_SIZE
+    }
+
+    /// Returns the fields of the header of a serialized opening of this
+    /// shape, i.e. the format version, the height and the arity.
+    const fn header() -> [u32; 3] {
+        // The height of a tree is checked to fit in a `u32` when it is
+        // created, and an arity that doesn't would make for unusably large
+        // nodes.
+        #[allow(clippy::cast_possible_truncation)]
+        [FORMAT_VERSION, H as u32, A as u32]
+    }
+
+    /// Reads the header of a serialized opening, checking it matches the
+    /// shape of this opening.
+    fn read_header(bytes: &mut &[u8]) -> Result<(), OpeningError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(OpeningError::BadLength {
+                found: bytes.len(),
+                expected: HEADER_SIZE,
+            });
+        }
+
+        let (header, rest) = bytes.split_at(HEADER_SIZE);
+        let mut found = [0u32; 3];
+        for (field, int) in found.iter_mut().zip(header.chunks_exact(U32_SIZE))
+        {
+            *field = u32::from_le_bytes(match int.try_into() {
+                Ok(int) => int,
+                Err(_) => unreachable!("The chunks should be 4 bytes long"),
+            });
+        }
+
+        let [version, height, arity] = found;
+        let [expected_version, expected_height, expected_arity] =
+            Self::header();
+
+        if version != expected_version {
+            return Err(OpeningError::Header(HeaderError::Version {
+                expected: expected_version,
+                found: version,
+            }));
+        }
+        if height != expected_height {
+            return Err(OpeningError::Header(HeaderError::Height {
+                expected: expected_height,
+                found: height,
+            }));
+        }
+        if arity != expected_arity {
+            return Err(OpeningError::Header(HeaderError::Arity {
+                expected: expected_arity,
+                found: arity,
+            }));
+        }
+
+        *bytes = rest;
+        Ok(())
+    }
+}
+
+/// The error returned when verifying a [`VersionedOpening`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerifyError {
+    /// The version of the opening is not the one expected.
+    StaleVersion {
+        /// The version the opening was expected to have.
+        expected: u64,
+        /// The version of the opening.
+        found: u64,
+    },
+    /// The opening is not valid for the given item.
+    InvalidOpening,
+    /// The root of the opening is not the one expected.
+    UnexpectedRoot,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StaleVersion { expected, found } => {
+                write!(f, "stale version: expected {expected}, found {found}")
+            }
+            Self::InvalidOpening => write!(f, "invalid opening"),
+            Self::UnexpectedRoot => write!(f, "unexpected root"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
+
+/// An [`Opening`] tagged with the version of the tree it was produced from,
+/// such as its [`generation`].
+///
+/// Verifying against an expected version allows for rejecting stale openings
+/// with a meaningful error, instead of a generic mismatch of the root.
+///
+/// [`generation`]: Tree::generation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionedOpening<T, const H: usize, const A: usize> {
+    version: u64,
+    opening: Opening<T, H, A>,
+}
+
+impl<T, const H: usize, const A: usize> VersionedOpening<T, H, A> {
+    /// Tag the given `opening` with the given `version`.
+    pub const fn new(version: u64, opening: Opening<T, H, A>) -> Self {
+        Self { version, opening }
+    }
+
+    /// Returns the version of the opening.
+    pub const fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the opening.
+    pub const fn opening(&self) -> &Opening<T, H, A> {
+        &self.opening
+    }
+
+    /// Returns the opening, discarding the version.
+    pub fn into_opening(self) -> Opening<T, H, A> {
+        self.opening
+    }
+}
+
+impl<T, const H: usize, const A: usize> VersionedOpening<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// Verify the opening has the `expected` version, and that the given
+    /// item is its leaf.
+    ///
+    /// # Errors
+    /// Returns [`VerifyError::StaleVersion`] if the versions differ, and
+    /// [`VerifyError::InvalidOpening`] if the opening doesn't verify.
+    pub fn verify(
+        &self,
+        item: impl Into<T>,
+        expected: u64,
+    ) -> Result<(), VerifyError>
+    where
+        T: PartialEq,
+    {
+        if self.version != expected {
+            return Err(VerifyError::StaleVersion {
+                expected,
+                found: self.version,
+            });
+        }
+        if !self.opening.verify(item) {
+            return Err(VerifyError::InvalidOpening);
+        }
+        Ok(())
+    }
+
+    /// Serialize a [`VersionedOpening`] to a vector of bytes.
+    ///
+    /// The version is serialized as a little endian `u64`, followed by the
+    /// opening as serialized by [`Opening::to_var_bytes`].
+    pub fn to_var_bytes<const T_SIZE: usize>(&self) -> Vec<u8>
+    where
+        T: Serializable<T_SIZE> + PartialEq,
+    {
+        let opening = self.opening.to_var_bytes();
+
+        let mut bytes = Vec::with_capacity(VERSION_SIZE + opening.len());
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend(opening);
+
+        bytes
+    }
+
+    /// Deserialize a [`VersionedOpening`] from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed.
+    pub fn from_slice<const T_SIZE: usize>(
+        buf: &[u8],
+    ) -> Result<Self, OpeningError>
+    where
+        T: Serializable<T_SIZE>,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        Self::from_slice_with_limits(buf, &DecodeLimits::UNLIMITED)
+    }
+
+    /// Deserialize a [`VersionedOpening`] from a slice of bytes, rejecting
+    /// inputs exceeding the given `limits` before decoding them.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`OpeningError`] describing which part of the opening
+    /// is malformed, or which limit was exceeded.
+    pub fn from_slice_with_limits<const T_SIZE: usize>(
+        buf: &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, OpeningError>
+    where
+        T: Serializable<T_SIZE>,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        limits.check_len(buf.len())?;
+
+        if buf.len() < VERSION_SIZE {
+            return Err(OpeningError::BadLength {
+                found: buf.len(),
+                expected: VERSION_SIZE
+                    + Opening::<T, H, A>::serialized_len::<T_SIZE>(H),
+            });
+        }
+
+        let (version, opening) = buf.split_at(VERSION_SIZE);
+        let version = u64::from_le_bytes(match version.try_into() {
+            Ok(version) => version,
+            Err(_) => unreachable!("The bytes should have been split at 8"),
+        });
+
+        let opening = Opening::from_slice_with_limits(opening, limits)
+            .map_err(|err| match err {
+                OpeningError::BadLength { found, expected } => {
+                    OpeningError::BadLength {
+                        found: found + VERSION_SIZE,
+                        expected: expected + VERSION_SIZE,
+                    }
+                }
+                err => err,
+            })?;
+
+        Ok(Self::new(version, opening))
+    }
+}
+
+/// An [`Opening`] that has been verified against a root, together with its
+/// leaf.
+///
+/// This can only be constructed by successfully verifying an opening, and as
+/// such guarantees that the leaf is in the tree with the given root. APIs
+/// consuming openings from untrusted sources should take this type, to
+/// ensure unverified data can't be used by mistake.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VerifiedOpening<T, const H: usize, const A: usize> {
+    opening: Opening<T, H, A>,
+    leaf: T,
+}
+
+impl<T, const H: usize, const A: usize> VerifiedOpening<T, H, A>
+where
+    T: Aggregate<A> + Clone + PartialEq,
+{
+    /// Verify the given `opening` is valid for the given `leaf`, and that its
+    /// root is the given `root`.
+    ///
+    /// # Errors
+    /// Returns [`VerifyError::UnexpectedRoot`] if the root of the opening is
+    /// not `root`, and [`VerifyError::InvalidOpening`] if the opening doesn't
+    /// verify for the leaf.
+    pub fn new(
+        opening: Opening<T, H, A>,
+        leaf: impl Into<T>,
+        root: &T,
+    ) -> Result<Self, VerifyError> {
+        if opening.root() != root {
+            return Err(VerifyError::UnexpectedRoot);
+        }
+
+        let leaf = leaf.into();
+        if !opening.verify(leaf.clone()) {
+            return Err(VerifyError::InvalidOpening);
+        }
+
+        Ok(Self { opening, leaf })
+    }
+}
+
+impl<T, const H: usize, const A: usize> VerifiedOpening<T, H, A> {
+    /// Returns the verified opening.
+    pub const fn opening(&self) -> &Opening<T, H, A> {
+        &self.opening
+    }
+
+    /// Returns the leaf the opening was verified for.
+    pub const fn leaf(&self) -> &T {
+        &self.leaf
+    }
+
+    /// Returns the root the opening was verified against.
+    pub const fn root(&self) -> &T {
+        &self.opening.root
+    }
+
+    /// Returns the opening and the leaf, discarding the verification.
+    pub fn into_parts(self) -> (Opening<T, H, A>, T) {
+        (self.opening, self.leaf)
+    }
+}
+
+/// A caller provided buffer to build openings into, without allocating.
+///
+/// Contrary to [`Opening`], the branch is stored inline, meaning the buffer
+/// can be placed wherever the caller sees fit - e.g. on the stack or in a
+/// static. It is filled by [`Tree::build_opening_into`], and can be reused
+/// for any number of openings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OpeningBuf<T, const H: usize, const A: usize> {
+    root: T,
+    branch: [[T; A]; H],
+    positions: [usize; H],
+}
+
+impl<T, const H: usize, const A: usize> Default for OpeningBuf<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const H: usize, const A: usize> OpeningBuf<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Create a new buffer, filled with empty subtrees.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            root: T::EMPTY_SUBTREE,
+            branch: init_array(|_| init_array(|_| T::EMPTY_SUBTREE)),
+            positions: [0; H],
+        }
+    }
+
+    /// Fill the buffer with the opening for the given `position` in the
+    /// `tree`.
+    ///
+    /// # Panics
+    /// If the given `position` is not in the `tree`.
+    pub(crate) fn fill(&mut self, tree: &Tree<T, H, A>, position: u64)
+    where
+        T: Clone,
+    {
+        trace::span!("build_opening", position, nodes = H + 1);
+
+        self.root = tree.root.item().clone();
+        fill_path(
+            &tree.root,
+            position,
+            H,
+            &mut self.branch,
+            &mut self.positions,
+        );
+    }
+
+    /// Verify the given item is the leaf of the opening in the buffer, and
+    /// that the opening is cryptographically correct.
+    pub fn verify(&self, item: impl Into<T>) -> bool
+    where
+        T: PartialEq,
+    {
+        verify_path(&self.root, 0, &self.branch, &self.positions, item.into())
+    }
+
+    /// Copy the opening in the buffer to an [`Opening`].
+    #[must_use]
+    pub fn to_opening(&self) -> Opening<T, H, A>
+    where
+        T: Clone,
+    {
+        let mut branch = empty_branch();
+        branch.clone_from_slice(&self.branch);
+
+        Opening {
+            root: self.root.clone(),
+            branch,
+            positions: self.positions,
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> OpeningBuf<T, H, A> {
+    /// Returns the root of the opening in the buffer.
+    pub const fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the branch of the opening in the buffer.
+    pub const fn branch(&self) -> &[[T; A]; H] {
+        &self.branch
+    }
+
+    /// Returns the indices for the path of the opening in the buffer.
+    pub const fn positions(&self) -> &[usize; H] {
+        &self.positions
+    }
+}
+
+/// The size of a serialized version.
+const VERSION_SIZE: usize = u64::BITS as usize / 8;
+
+/// The size of a serialized `u32`.
+const U32_SIZE: usize = u32::BITS as usize / 8;
+
+/// The size of the header of a serialized [`Opening`].
+const HEADER_SIZE: usize = 3 * U32_SIZE;
+
+/// Reads a little endian `u32` from the given `bytes`, advancing them.
+///
+/// The caller must ensure there are enough bytes.
+fn read_u32(bytes: &mut &[u8]) -> usize {
+    let (int, rest) = bytes.split_at(U32_SIZE);
+    *bytes = rest;
+
+    let int = u32::from_le_bytes(match int.try_into() {
+        Ok(int) => int,
+        Err(_) => unreachable!("The bytes should have been split at 4"),
+    });
+    int as usize
+}
+
+/// Returns a branch filled with empty subtrees, allocated directly on the
+/// heap.
+// Returning the array by value would defeat the purpose of boxing it.
+#[allow(clippy::unnecessary_box_returns)]
+fn empty_branch<T, const H: usize, const A: usize>() -> Box<[[T; A]; H]>
+where
+    T: Aggregate<A>,
+{
+    let levels: Vec<[T; A]> =
+        (0..H).map(|_| init_array(|_| T::EMPTY_SUBTREE)).collect();
+
+    match levels.into_boxed_slice().try_into() {
+        Ok(branch) => branch,
+        Err(_) => unreachable!("The branch should have exactly H levels"),
+    }
+}
+
+/// Fills the top `levels` levels of the given `branch` and `positions` with
+/// the path to the given `position`, descending iteratively from the `root`.
+///
+/// # Panics
+/// If the given `position` is not in the tree.
+fn fill_path<T, const H: usize, const A: usize>(
+    root: &Node<T, H, A>,
+    mut position: u64,
+    levels: usize,
+    branch: &mut [[T; A]; H],
+    positions: &mut [usize; H],
+) where
+    T: Aggregate<A> + Clone,
+{
+    let mut node = root;
+
+    for height in 0..levels {
+        for (item, child) in branch[height].iter_mut().zip(&node.children) {
+            *item = match child {
+                Some(child) => child.item().clone(),
+                None => T::EMPTY_SUBTREE,
+            };
+        }
+
+        let (child_index, child_pos) =
+            Node::<T, H, A>::child_location(height, position);
+        node = node.children[child_index]
+            .as_ref()
+            .expect("There should be a child at this position");
+        positions[height] = child_index;
+        position = child_pos;
+    }
+}
+
+/// Verify the given `item` is the leaf of the path given by `branch` and
+/// `positions`, and that the path leads to `node` at the given `height` -
+/// the root being at height zero.
+fn verify_path<T, const H: usize, const A: usize>(
+    node: &T,
+    height: usize,
+    branch: &[[T; A]; H],
+    positions: &[usize; H],
+    mut item: T,
+) -> bool
+where
+    T: Aggregate<A> + PartialEq,
+{
+    for h in (height..H).rev() {
+        let level = &branch[h];
+        let position = positions[h];
+
+        // if the computed item doesn't match the stored item at the given
+        // position, the opening is incorrect
+        if item != level[position] {
+            return false;
+        }
+
+        let empty_subtree = &T::EMPTY_SUBTREE;
+
+        let mut item_refs = [empty_subtree; A];
+        item_refs
+            .iter_mut()
+            .zip(&branch[h])
+            .for_each(|(r, item_ref)| {
+                *r = item_ref;
+            });
+
+        item = T::aggregate(item_refs);
+    }
+
+    *node == item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H: usize = 4;
+    const A: usize = 2;
+    #[allow(clippy::cast_possible_truncation)]
+    const TREE_CAP: usize = A.pow(H as u32);
+
+    /// A string type that is on the stack, and holds a string of a size as
+    /// large as the tree.
+    #[derive(Clone, Copy, PartialEq)]
+    struct String {
+        chars: [char; TREE_CAP],
+        len: usize,
+    }
+
+    impl From<char> for String {
+        fn from(c: char) -> Self {
+            let mut chars = ['0'; TREE_CAP];
+            chars[0] = c;
+            Self { chars, len: 1 }
+        }
+    }
+
+    const EMPTY_ITEM: String = String {
+        chars: ['0'; TREE_CAP],
+        len: 0,
+    };
+
+    /// A simple aggregator that concatenates strings.
+    impl Aggregate<A> for String {
+        const EMPTY_SUBTREE: Self = EMPTY_ITEM;
+
+        fn aggregate(items: [&Self; A]) -> Self {
+            items.into_iter().fold(EMPTY_ITEM, |mut acc, s| {
+                acc.chars[acc.len..acc.len + s.len]
+                    .copy_from_slice(&s.chars[..s.len]);
+                acc.len += s.len;
+                acc
+            })
+        }
+    }
+
+    type TestTree = Tree<String, H, A>;
+
+    /// A serializable item, with a non-commutative aggregation.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Item(u64);
+
+    impl Aggregate<A> for Item {
+        const EMPTY_SUBTREE: Self = Item(0);
+
+        fn aggregate(items: [&Self; A]) -> Self {
+            Item(items[0].0.wrapping_mul(31).wrapping_add(items[1].0 + 1))
+        }
+    }
+
+    impl Serializable<8> for Item {
+        type Error = BytesError;
+
+        fn from_bytes(buf: &[u8; 8]) -> Result<Self, Self::Error> {
+            Ok(Self(u64::from_bytes(buf)?))
+        }
+
+        fn to_bytes(&self) -> [u8; 8] {
+            self.0.to_bytes()
+        }
+    }
+
+    #[test]
+    fn opening_versioned() {
+        let mut tree = TestTree::new();
+        tree.insert(3, 'A');
+
+        let stale = tree.versioned_opening(3).unwrap();
+        assert_eq!(stale.verify('A', 1), Ok(()));
+        assert_eq!(stale.verify('B', 1), Err(VerifyError::InvalidOpening));
+
+        tree.insert(7, 'B');
+        let generation = tree.generation();
+        assert_eq!(generation, 2);
+
+        assert_eq!(
+            stale.verify('A', generation),
+            Err(VerifyError::StaleVersion {
+                expected: generation,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            tree.versioned_opening(3).unwrap().verify('A', generation),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn opening_verified() {
+        let mut tree = TestTree::new();
+        tree.insert(3, 'A');
+        tree.insert(9, 'B');
+
+        let root = *tree.root();
+        let opening = tree.opening(9).unwrap();
+
+        let verified = VerifiedOpening::new(opening.clone(), 'B', &root)
+            .expect("The opening should verify");
+        assert!(*verified.root() == root);
+        assert!(*verified.leaf() == String::from('B'));
+
+        assert_eq!(
+            VerifiedOpening::new(opening.clone(), 'A', &root).err(),
+            Some(VerifyError::InvalidOpening)
+        );
+
+        tree.remove(3);
+        assert_eq!(
+            VerifiedOpening::new(opening, 'B', &tree.root()).err(),
+            Some(VerifyError::UnexpectedRoot)
+        );
+    }
+
+    #[test]
+    fn opening_buf() {
+        let mut tree = TestTree::new();
+        tree.insert(3, 'A');
+        tree.insert(9, 'B');
+        tree.insert(10, 'C');
+
+        let mut buf = OpeningBuf::new();
+
+        assert!(tree.build_opening_into(9, &mut buf));
+        assert!(buf.verify('B'));
+        assert!(!buf.verify('C'));
+        assert!(buf.to_opening() == tree.opening(9).unwrap());
+
+        // the buffer can be reused, and is left untouched for missing leaves
+        assert!(tree.build_opening_into(3, &mut buf));
+        assert!(!tree.build_opening_into(4, &mut buf));
+        assert!(buf.verify('A'));
+        assert!(buf.to_opening() == tree.opening(3).unwrap());
+    }
+
+    #[test]
+    fn opening_verify_partial() {
+        let mut tree = TestTree::new();
+        tree.insert(3, 'A');
+        tree.insert(9, 'B');
+        tree.insert(10, 'C');
+        tree.insert(12, 'D');
+
+        let opening = tree.opening(9).unwrap();
+        let chunk = *tree.root.descendant(1, 1).unwrap().item();
+
+        assert_eq!(opening.positions()[..1], [1]);
+        assert!(opening.verify_partial('B', 1, &chunk));
+        assert!(!opening.verify_partial('C', 1, &chunk));
+        assert!(!opening.verify_partial('B', 2, &chunk));
+        assert!(opening.verify_partial('B', 0, &tree.root()));
+        assert!(opening.verify_partial('B', H, &String::from('B')));
+        assert!(!opening.verify_partial('B', H + 1, &String::from('B')));
+    }
+
+    #[cfg(feature = "subtle")]
+    impl ConstantTimeEq for Item {
+        fn ct_eq(&self, other: &Self) -> Choice {
+            self.0.ct_eq(&other.0)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "subtle")]
+    fn opening_verify_ct() {
+        let mut tree = Tree::<Item, H, A>::new();
+        tree.insert(4, Item(7));
+        tree.insert(13, Item(9));
+
+        let opening = tree.opening(13).unwrap();
+        assert!(opening.verify_ct(Item(9)));
+        assert!(!opening.verify_ct(Item(7)));
+
+        let mut moved = opening.clone();
+        moved.positions[H - 1] = 0;
+        assert!(!moved.verify_ct(Item(9)));
+
+        let mut invalid = opening;
+        invalid.positions[0] = A;
+        assert!(!invalid.verify_ct(Item(9)));
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn opening_verify() {
+        const LETTERS: &[char] = &[
+            'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
+            'N', 'O', 'P',
+        ];
+
+        let mut tree = TestTree::new();
+        let cap = tree.capacity();
+
+        for i in 0..cap {
+            tree.insert(i, LETTERS[i as usize]);
+        }
+
+        for pos in 0..cap {
+            let opening = tree
+                .opening(pos)
+                .expect("There must be an opening for an existing item");
+
+            assert!(
                 opening.verify(LETTERS[pos as usize]),
                 "The opening should be for the item that was inserted at the given position"
             );
@@ -284,4 +1596,255 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn opening_fingerprinted_bytes() {
+        let mut tree = Tree::<Item, H, A>::new();
+        tree.insert(4, Item(7));
+        tree.insert(13, Item(9));
+
+        let fingerprint = tree.fingerprint();
+        let opening = tree.opening(4).unwrap();
+        let bytes = opening.to_fingerprinted_bytes(&fingerprint);
+        assert_eq!(
+            Opening::<Item, H, A>::from_fingerprinted_slice(
+                &bytes,
+                &fingerprint
+            ),
+            Ok(opening)
+        );
+        assert_eq!(
+            Opening::<Item, H, A>::from_fingerprinted_slice(
+                &bytes,
+                &Fingerprint::new::<H, A>(1)
+            ),
+            Err(OpeningError::Fingerprint(FingerprintError::Backend {
+                expected: 1,
+                found: 0
+            }))
+        );
+        assert_eq!(
+            Opening::<Item, H, A>::from_fingerprinted_slice(
+                &bytes[..bytes.len() - 1],
+                &fingerprint
+            ),
+            Err(OpeningError::BadLength {
+                found: bytes.len() - 1,
+                expected: bytes.len()
+            })
+        );
+    }
+
+    #[test]
+    fn opening_verify_raw() {
+        type Raw = Opening<Item, H, A>;
+
+        let mut tree = Tree::<Item, H, A>::new();
+        tree.insert(5, Item(42));
+
+        // a trimmed opening
+        let root = tree.root().to_bytes();
+        let bytes = tree.opening(5).unwrap().to_var_bytes();
+        assert_eq!(
+            Raw::verify_raw(&root, &bytes, &Item(42).to_bytes()),
+            Ok(true)
+        );
+        assert_eq!(
+            Raw::verify_raw(&root, &bytes, &Item(7).to_bytes()),
+            Ok(false)
+        );
+
+        tree.insert(4, Item(7));
+        tree.insert(13, Item(9));
+
+        let root = tree.root().to_bytes();
+        let bytes = tree.opening(13).unwrap().to_var_bytes();
+        assert_eq!(
+            Raw::verify_raw(&root, &bytes, &Item(9).to_bytes()),
+            Ok(true)
+        );
+        assert_eq!(
+            Raw::verify_raw(&root, &bytes, &Item(7).to_bytes()),
+            Ok(false)
+        );
+
+        // a proof against another root
+        let stale = Item(0).to_bytes();
+        assert_eq!(
+            Raw::verify_raw(&stale, &bytes, &Item(9).to_bytes()),
+            Ok(false)
+        );
+
+        // a tampered branch
+        let mut tampered = bytes.clone();
+        tampered[12] ^= 1;
+        assert_eq!(
+            Raw::verify_raw(&root, &tampered, &Item(9).to_bytes()),
+            Ok(false)
+        );
+
+        assert_eq!(
+            Raw::verify_raw(
+                &root,
+                &bytes[..bytes.len() - 1],
+                &Item(9).to_bytes()
+            ),
+            Err(OpeningError::BadLength {
+                found: bytes.len() - 1,
+                expected: bytes.len()
+            })
+        );
+    }
+
+    #[test]
+    fn opening_position() {
+        let mut tree = Tree::<Item, H, A>::new();
+        for position in [0, 5, 10, 15] {
+            tree.insert(position, Item(position));
+        }
+
+        for position in [0, 5, 10, 15] {
+            let opening = tree.opening(position).unwrap();
+            assert_eq!(opening.position(), position);
+
+            let bytes = opening.to_var_bytes();
+            let deserialized =
+                Opening::<Item, H, A>::from_slice(&bytes).unwrap();
+            assert_eq!(deserialized.position(), position);
+        }
+    }
+
+    #[test]
+    fn opening_header() {
+        let mut tree = Tree::<Item, H, A>::new();
+        tree.insert(3, Item(3));
+
+        let bytes = tree.opening(3).unwrap().to_var_bytes();
+        let root = Item::aggregate([&Item(0); A]).to_bytes();
+
+        // an opening for a tree of another height is rejected early, instead
+        // of failing with an arbitrary error
+        assert_eq!(
+            Opening::<Item, { H + 1 }, A>::from_slice(&bytes),
+            Err(OpeningError::Header(HeaderError::Height {
+                expected: 5,
+                found: 4,
+            }))
+        );
+        assert_eq!(
+            Opening::<Item, { H - 1 }, A>::verify_raw(&root, &bytes, &root),
+            Err(OpeningError::Header(HeaderError::Height {
+                expected: 3,
+                found: 4,
+            }))
+        );
+
+        let mut tagged = bytes.clone();
+        tagged[8..12].copy_from_slice(&3u32.to_bytes());
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice(&tagged),
+            Err(OpeningError::Header(HeaderError::Arity {
+                expected: 2,
+                found: 3,
+            }))
+        );
+
+        let mut tagged = bytes.clone();
+        tagged[0..4].copy_from_slice(&(FORMAT_VERSION - 1).to_bytes());
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice(&tagged),
+            Err(OpeningError::Header(HeaderError::Version {
+                expected: FORMAT_VERSION,
+                found: FORMAT_VERSION - 1,
+            }))
+        );
+
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice(&bytes[..HEADER_SIZE - 1]),
+            Err(OpeningError::BadLength {
+                found: HEADER_SIZE - 1,
+                expected: HEADER_SIZE,
+            })
+        );
+    }
+
+    #[test]
+    fn opening_trimmed_bytes() {
+        let full_len = Opening::<Item, H, A>::serialized_len::<8>(0);
+
+        // a single leaf has only empty siblings, leaving the leaf level alone
+        let mut tree = Tree::<Item, H, A>::new();
+        tree.insert(5, Item(42));
+
+        let opening = tree.opening(5).unwrap();
+        let bytes = opening.to_var_bytes();
+        assert_eq!(bytes.len(), full_len - (H - 1) * A * 8);
+
+        let deserialized = Opening::<Item, H, A>::from_slice(&bytes).unwrap();
+        assert_eq!(opening, deserialized);
+        assert!(deserialized.verify(Item(42)));
+
+        let versioned = VersionedOpening::new(7, opening.clone());
+        let bytes = versioned.to_var_bytes();
+        assert_eq!(
+            VersionedOpening::<Item, H, A>::from_slice(&bytes),
+            Ok(versioned)
+        );
+
+        // a sibling at the leaf level only stops the trimming there
+        tree.insert(4, Item(7));
+        tree.insert(13, Item(9));
+
+        let opening = tree.opening(5).unwrap();
+        let bytes = opening.to_var_bytes();
+        assert_eq!(bytes.len(), full_len);
+        assert_eq!(Opening::<Item, H, A>::from_slice(&bytes), Ok(opening));
+
+        // claiming too many trimmed levels is rejected
+        let mut bytes = tree.opening(4).unwrap().to_var_bytes();
+        bytes[HEADER_SIZE + 8..HEADER_SIZE + 12]
+            .copy_from_slice(&4u32.to_bytes());
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice(&bytes),
+            Err(OpeningError::InvalidTrimmedLevels(4))
+        );
+
+        // as are positions outside of the arity
+        let mut bytes = tree.opening(4).unwrap().to_var_bytes();
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&7u32.to_bytes());
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice(&bytes),
+            Err(OpeningError::InvalidPosition {
+                level: H - 1,
+                position: 7
+            })
+        );
+
+        let limits = DecodeLimits::new(len - 1, usize::MAX);
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice_with_limits(&bytes, &limits),
+            Err(OpeningError::TooLong {
+                found: len,
+                limit: len - 1,
+            })
+        );
+
+        let limits = DecodeLimits::new(len, 1 + H * A - 1);
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice_with_limits(&bytes, &limits),
+            Err(OpeningError::TooManyItems {
+                found: 1 + H * A,
+                limit: 1 + H * A - 1,
+            })
+        );
+
+        assert_eq!(
+            Opening::<Item, H, A>::from_slice(&bytes[..len - 1]),
+            Err(OpeningError::BadLength {
+                found: len - 1,
+                expected: len,
+            })
+        );
+    }
 }
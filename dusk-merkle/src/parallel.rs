@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use rayon::prelude::*;
+
+use crate::{capacity, init_array, Aggregate, Opening, Tree};
+
+/// The items of all the occupied nodes of a tree, indexed by height and by
+/// index within that height.
+///
+/// Unlike the tree itself, this can be shared between threads.
+struct Snapshot<T> {
+    levels: Vec<BTreeMap<u64, T>>,
+}
+
+impl<T> Snapshot<T>
+where
+    T: Clone,
+{
+    fn new<const H: usize, const A: usize>(tree: &Tree<T, H, A>) -> Self
+    where
+        T: Aggregate<A>,
+    {
+        let mut levels: Vec<BTreeMap<u64, T>> =
+            (0..=H).map(|_| BTreeMap::new()).collect();
+
+        // each item is computed once, when its node is visited
+        for (height, index, item) in tree.breadth_first() {
+            levels[height].insert(index, item.clone());
+        }
+
+        Self { levels }
+    }
+
+    fn opening<const H: usize, const A: usize>(
+        &self,
+        root: &T,
+        position: u64,
+    ) -> Opening<T, H, A>
+    where
+        T: Aggregate<A>,
+    {
+        // the index of the node on the path at each height below the root
+        let path_index =
+            |height: usize| position / capacity(A as u64, H - height);
+
+        // positions within a node are smaller than the arity, so casting to
+        // a `usize` is fine
+        #[allow(clippy::cast_possible_truncation)]
+        let positions = init_array(|h| (path_index(h + 1) % A as u64) as usize);
+
+        Opening::from_fn(root.clone(), positions, |h| {
+            let first_child = path_index(h) * A as u64;
+            init_array(|i| {
+                self.levels[h + 1]
+                    .get(&(first_child + i as u64))
+                    .cloned()
+                    .unwrap_or(T::EMPTY_SUBTREE)
+            })
+        })
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A> + Clone + Send + Sync,
+{
+    /// Returns the openings for all the leaves in the tree, together with
+    /// their positions.
+    ///
+    /// Each item of the tree is computed once, and the openings are then
+    /// assembled in parallel. This is considerably faster than calling
+    /// [`opening`] for each leaf of a large tree.
+    ///
+    /// [`opening`]: Tree::opening
+    #[must_use]
+    pub fn openings_par(&self) -> Vec<(u64, Opening<T, H, A>)> {
+        let positions: Vec<u64> = self.positions().collect();
+        self.openings_par_for(&positions)
+            .into_iter()
+            .filter_map(|(position, opening)| Some((position, opening?)))
+            .collect()
+    }
+
+    /// Returns the openings for the leaves at the given `positions`, in the
+    /// same order.
+    ///
+    /// Positions with no leaf have no opening. See [`openings_par`] for
+    /// details.
+    ///
+    /// [`openings_par`]: Tree::openings_par
+    #[must_use]
+    pub fn openings_par_for(
+        &self,
+        positions: &[u64],
+    ) -> Vec<(u64, Option<Opening<T, H, A>>)> {
+        let snapshot = Snapshot::new(self);
+        let root = self.root().clone();
+
+        positions
+            .par_iter()
+            .map(|&position| {
+                let opening = snapshot.levels[H]
+                    .contains_key(&position)
+                    .then(|| snapshot.opening(&root, position));
+                (position, opening)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H: usize = 6;
+    const A: usize = 3;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Concat(Vec<u64>);
+
+    impl Aggregate<A> for Concat {
+        const EMPTY_SUBTREE: Self = Concat(Vec::new());
+
+        fn aggregate(items: [&Self; A]) -> Self {
+            Concat(items.into_iter().flat_map(|c| c.0.clone()).collect())
+        }
+    }
+
+    #[test]
+    fn openings_par() {
+        let mut tree = Tree::<Concat, H, A>::new();
+        for position in (0..tree.capacity()).step_by(7) {
+            tree.insert(position, Concat(alloc::vec![position]));
+        }
+
+        let openings = tree.openings_par();
+        assert_eq!(openings.len() as u64, tree.len());
+
+        for (position, opening) in openings {
+            assert_eq!(Some(opening), tree.opening(position));
+        }
+
+        let openings = tree.openings_par_for(&[14, 15]);
+        assert_eq!(openings[0], (14, tree.opening(14)));
+        assert_eq!(openings[1], (15, None));
+    }
+}
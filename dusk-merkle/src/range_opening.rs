@@ -0,0 +1,264 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "rkyv-impl")]
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{init_array, trace, Aggregate, Tree};
+
+/// An opening for a contiguous range of positions in a merkle tree, proving
+/// both the leaves present in the range and the absence of leaves in the
+/// gaps between them.
+///
+/// Every position in the range without a leaf is taken to be empty when
+/// verifying, meaning omitting a leaf makes the verification fail. Only the
+/// siblings at the edges of the range are stored, so the opening stays small
+/// regardless of how many positions the range spans.
+///
+/// Created using [`Tree::opening_range`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(Archive, Serialize, Deserialize),
+    archive_attr(derive(CheckBytes))
+)]
+pub struct RangeOpening<T, const H: usize, const A: usize> {
+    root: T,
+    start: u64,
+    end: u64,
+    leaves: Vec<(u64, T)>,
+    siblings: Vec<T>,
+}
+
+impl<T, const H: usize, const A: usize> RangeOpening<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// # Panics
+    /// If the range is empty or exceeds the capacity of the `tree`.
+    pub(crate) fn new(tree: &Tree<T, H, A>, start: u64, end: u64) -> Self {
+        trace::span!("range_opening", start, end);
+
+        let leaves: Vec<(u64, T)> = tree
+            .positions
+            .range(start..end)
+            .map(|&position| {
+                let Some(leaf) = tree.leaf(position) else {
+                    unreachable!("the position is in the tree");
+                };
+                (position, leaf.clone())
+            })
+            .collect();
+
+        let mut siblings = Vec::new();
+
+        let mut level: Vec<u64> = leaves.iter().map(|(i, _)| *i).collect();
+        let mut range = (start, end);
+        for height in (1..=H).rev() {
+            let parents = parent_indices::<A>(range, level.iter().copied());
+
+            for parent in &parents {
+                for index in parent * A as u64..(parent + 1) * A as u64 {
+                    if index < range.0 || index >= range.1 {
+                        let sibling = match tree.root.descendant(height, index)
+                        {
+                            Some(node) => node.item().clone(),
+                            None => T::EMPTY_SUBTREE,
+                        };
+                        siblings.push(sibling);
+                    }
+                }
+            }
+
+            level = parents;
+            range = parent_range::<A>(range);
+        }
+
+        Self {
+            root: tree.root.item().clone(),
+            start,
+            end,
+            leaves,
+            siblings,
+        }
+    }
+}
+
+impl<T, const H: usize, const A: usize> RangeOpening<T, H, A> {
+    /// Returns the root of the opening.
+    #[must_use]
+    pub const fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the first position of the opened range.
+    #[must_use]
+    pub const fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the position just past the end of the opened range.
+    #[must_use]
+    pub const fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the leaves in the opened range, together with their positions,
+    /// in ascending order.
+    #[must_use]
+    pub fn leaves(&self) -> &[(u64, T)] {
+        &self.leaves
+    }
+
+    /// Returns the items needed to compute the root from the opened range,
+    /// level by level from the leaves up, and from left to right within each
+    /// level.
+    #[must_use]
+    pub fn siblings(&self) -> &[T] {
+        &self.siblings
+    }
+
+    /// Verify the leaves of the opening are all the leaves in the opened
+    /// range, and that the opening is cryptographically correct.
+    pub fn verify(&self) -> bool
+    where
+        T: Aggregate<A> + Clone + PartialEq,
+    {
+        let range = self.start..self.end;
+        if range.is_empty()
+            || !self.leaves.iter().all(|(i, _)| range.contains(i))
+            || !self.leaves.windows(2).all(|w| w[0].0 < w[1].0)
+        {
+            return false;
+        }
+
+        let empty_subtree = T::EMPTY_SUBTREE;
+        let mut siblings = self.siblings.iter();
+
+        let mut level = self.leaves.clone();
+        let mut range = (self.start, self.end);
+        for _ in 0..H {
+            let indices = level.iter().map(|(i, _)| *i);
+            let parents = parent_indices::<A>(range, indices);
+
+            let mut children = level.iter().peekable();
+            let mut items = Vec::with_capacity(parents.len());
+            for parent in parents {
+                let mut level_items = Vec::with_capacity(A);
+                for index in parent * A as u64..(parent + 1) * A as u64 {
+                    let item = match children.next_if(|(i, _)| *i == index) {
+                        Some((_, item)) => item,
+                        // positions in the range not holding a leaf are
+                        // proven to be empty
+                        None if index >= range.0 && index < range.1 => {
+                            &empty_subtree
+                        }
+                        None => match siblings.next() {
+                            Some(sibling) => sibling,
+                            None => return false,
+                        },
+                    };
+                    level_items.push(item);
+                }
+
+                let item = T::aggregate(init_array(|i| level_items[i]));
+                items.push((parent, item));
+            }
+
+            level = items;
+            range = parent_range::<A>(range);
+        }
+
+        match (level.as_slice(), siblings.next()) {
+            ([(0, root)], None) => *root == self.root,
+            _ => false,
+        }
+    }
+}
+
+/// Returns the indices of the nodes one level up from the given ascending
+/// `indices`, always including the first and last nodes covering the given
+/// `range` of the level.
+fn parent_indices<const A: usize>(
+    range: (u64, u64),
+    indices: impl Iterator<Item = u64>,
+) -> Vec<u64> {
+    let (first, last) = (range.0 / A as u64, (range.1 - 1) / A as u64);
+
+    let mut parents = Vec::new();
+    parents.push(first);
+    parents.extend(indices.map(|i| i / A as u64));
+    parents.push(last);
+    parents.dedup();
+
+    parents
+}
+
+/// Returns the range of the nodes one level up covering the given `range`.
+const fn parent_range<const A: usize>(range: (u64, u64)) -> (u64, u64) {
+    (range.0 / A as u64, (range.1 - 1) / A as u64 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::Sum;
+    use crate::{Tree, TreeBuilder};
+
+    const H: usize = 4;
+    const A: usize = 3;
+
+    #[test]
+    fn range_opening() {
+        let positions = [0, 1, 5, 26, 40, 41, 80];
+        let tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves(
+                positions.map(|position| (position, Sum(position + 1))),
+            )
+            .build();
+
+        let opening = tree.opening_range(1, 41).unwrap();
+        assert_eq!(opening.root(), &*tree.root());
+        assert_eq!((opening.start(), opening.end()), (1, 41));
+        assert_eq!(
+            opening.leaves(),
+            [(1, Sum(2)), (5, Sum(6)), (26, Sum(27)), (40, Sum(41))]
+        );
+        assert!(opening.verify());
+
+        // omitting or altering a leaf is detected
+        let mut omitted = opening.clone();
+        omitted.leaves.remove(2);
+        assert!(!omitted.verify());
+        let mut altered = opening.clone();
+        altered.leaves[0].1 = Sum(3);
+        assert!(!altered.verify());
+        let mut outside = opening.clone();
+        outside.leaves.push((41, Sum(42)));
+        assert!(!outside.verify());
+
+        // a range with no leaves proves its emptiness
+        let empty = tree.opening_range(6, 26).unwrap();
+        assert!(empty.leaves().is_empty());
+        assert!(empty.verify());
+
+        // the whole tree needs no siblings
+        let whole = tree.opening_range(0, tree.capacity()).unwrap();
+        assert_eq!(whole.leaves().len(), positions.len());
+        assert!(whole.siblings().is_empty());
+        assert!(whole.verify());
+
+        let single = tree.opening_range(80, 81).unwrap();
+        assert_eq!(single.siblings().len(), (A - 1) * H);
+        assert!(single.verify());
+
+        assert!(tree.opening_range(5, 5).is_none());
+        assert!(tree.opening_range(0, tree.capacity() + 1).is_none());
+    }
+}
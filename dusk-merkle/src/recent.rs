@@ -0,0 +1,243 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::VecDeque;
+
+use crate::{Aggregate, Tree};
+
+/// A ring buffer holding the last roots of a tree.
+///
+/// Openings received from the network may have been produced against a
+/// slightly older state of the tree. Recording the root after each batch of
+/// mutations allows for checking whether the root of such an opening is
+/// recent enough to be accepted.
+///
+/// A tree records its own roots once [`Tree::track_roots`] has been called,
+/// each time the mutations made since its outermost checkpoint are
+/// committed - see [`Tree::commit`].
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, Tree};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl Aggregate<2> for Sum {
+///     const EMPTY_SUBTREE: Self = Sum(0);
+///
+///     fn aggregate(items: [&Self; 2]) -> Self {
+///         Sum(items[0].0 + items[1].0)
+///     }
+/// }
+///
+/// let mut tree = Tree::<Sum, 8, 2>::new();
+/// tree.track_roots(2);
+///
+/// for position in 0..3 {
+///     let id = tree.checkpoint();
+///     tree.insert(position, Sum(1));
+///     tree.commit(id);
+/// }
+///
+/// assert!(tree.is_recent_root(&Sum(3)));
+/// assert!(tree.is_recent_root(&Sum(2)));
+/// assert!(!tree.is_recent_root(&Sum(1)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentRoots<T> {
+    roots: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RecentRoots<T> {
+    /// Create a new, empty, buffer holding at most `capacity` roots.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            roots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record the given `root` as the latest, evicting the oldest root if the
+    /// buffer is full.
+    pub fn push(&mut self, root: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.roots.len() == self.capacity {
+            self.roots.pop_back();
+        }
+        self.roots.push_front(root);
+    }
+
+    /// Returns `true` if the given `root` is one of the recorded roots.
+    pub fn is_recent_root(&self, root: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.roots.contains(root)
+    }
+
+    /// Returns the latest root recorded, if any.
+    #[must_use]
+    pub fn latest(&self) -> Option<&T> {
+        self.roots.front()
+    }
+
+    /// Returns an iterator over the recorded roots, from the newest to the
+    /// oldest.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.roots.iter()
+    }
+
+    /// Returns the number of recorded roots.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns `true` if no root has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the maximum number of roots held by the buffer.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A>,
+{
+    /// Start recording the roots of the tree, keeping the last `n` of them.
+    ///
+    /// The current root is recorded right away, and the root is then recorded
+    /// each time the mutations made since the outermost checkpoint are
+    /// committed, i.e. at the end of each batch of mutations applied between
+    /// [`checkpoint`] and [`commit`]. Mutations made while there are no
+    /// checkpoints, and the ones that are rolled back, are not recorded.
+    ///
+    /// Calling this again discards the roots recorded so far.
+    ///
+    /// [`checkpoint`]: Tree::checkpoint
+    /// [`commit`]: Tree::commit
+    pub fn track_roots(&mut self, n: usize)
+    where
+        T: Clone,
+    {
+        let mut recent = RecentRoots::new(n);
+        recent.push(self.root().clone());
+        self.recent_roots = Some(recent);
+    }
+
+    /// Returns the recorded roots of the tree, if they are tracked.
+    #[must_use]
+    pub const fn recent_roots(&self) -> Option<&RecentRoots<T>> {
+        self.recent_roots.as_ref()
+    }
+
+    /// Returns `true` if the given `root` is one of the recorded roots of
+    /// the tree.
+    ///
+    /// This is always `false` unless [`track_roots`] has been called.
+    ///
+    /// [`track_roots`]: Tree::track_roots
+    pub fn is_recent_root(&self, root: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.recent_roots
+            .as_ref()
+            .is_some_and(|recent| recent.is_recent_root(root))
+    }
+
+    /// Records the current root of the tree, cloned with the given function,
+    /// if the roots are tracked.
+    pub(crate) fn record_root(&mut self, clone: fn(&T) -> T) {
+        if self.recent_roots.is_none() {
+            return;
+        }
+
+        let root = clone(&self.root());
+        if let Some(recent) = &mut self.recent_roots {
+            recent.push(root);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    use crate::test_util::Sum;
+
+    #[test]
+    fn ring_buffer() {
+        let mut recent = RecentRoots::<u32>::new(3);
+        assert!(recent.is_empty());
+        assert_eq!(recent.latest(), None);
+
+        recent.push(1);
+        recent.push(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent.latest(), Some(&2));
+        assert_eq!(recent.iter().copied().collect::<Vec<_>>(), [2, 1]);
+
+        recent.push(3);
+        recent.push(4);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent.latest(), Some(&4));
+        assert_eq!(recent.iter().copied().collect::<Vec<_>>(), [4, 3, 2]);
+
+        assert!(!recent.is_recent_root(&1));
+        assert!(recent.is_recent_root(&2));
+        assert!(recent.is_recent_root(&4));
+
+        let mut none = RecentRoots::<u32>::new(0);
+        none.push(1);
+        assert!(none.is_empty());
+        assert!(!none.is_recent_root(&1));
+    }
+
+    #[test]
+    fn tree_roots() {
+        let mut tree = Tree::<Sum, 3, 2>::new();
+        tree.insert(0, Sum(1));
+        assert!(!tree.is_recent_root(&Sum(1)));
+        assert_eq!(tree.recent_roots(), None);
+
+        tree.track_roots(3);
+        assert!(tree.is_recent_root(&Sum(1)));
+
+        // only the outermost commit ends a batch
+        let outer = tree.checkpoint();
+        tree.insert(1, Sum(2));
+        let inner = tree.checkpoint();
+        tree.insert(2, Sum(4));
+        tree.commit(inner);
+        assert!(!tree.is_recent_root(&Sum(7)));
+        tree.commit(outer);
+        assert!(tree.is_recent_root(&Sum(7)));
+
+        // rolled back and unchecked mutations are not recorded
+        let id = tree.checkpoint();
+        tree.insert(3, Sum(8));
+        tree.rollback(id);
+        tree.insert(4, Sum(16));
+
+        let roots: Vec<Sum> =
+            tree.recent_roots().unwrap().iter().copied().collect();
+        assert_eq!(roots, [Sum(7), Sum(1)]);
+    }
+}
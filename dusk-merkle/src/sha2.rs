@@ -0,0 +1,137 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! A ready-made item for trees using the SHA-256 hash function.
+//!
+//! Leaves and inner nodes are domain separated as specified by [RFC 6962]:
+//! leaves are hashed prefixed with a `0x00` byte, and the children of inner
+//! nodes are hashed prefixed with a `0x01` byte. Empty subtrees are
+//! represented by the zero hash.
+//!
+//! [RFC 6962]: https://www.rfc-editor.org/rfc/rfc6962#section-2.1
+
+use ::sha2::{Digest, Sha256};
+use dusk_bytes::{Error as BytesError, Serializable};
+
+use crate::{Aggregate, LeafHash};
+
+/// The prefix of the bytes hashed for a leaf.
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of the children hashed for an inner node.
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// An alias for a tree containing SHA-256 [`Item`]s.
+pub type Tree<const H: usize, const A: usize> = crate::Tree<Item, H, A>;
+
+/// An alias for an opening of a tree containing SHA-256 [`Item`]s.
+pub type Opening<const H: usize, const A: usize> = crate::Opening<Item, H, A>;
+
+/// A SHA-256 hash, aggregated by hashing the concatenation of the children's
+/// hashes, prefixed with [`NODE_PREFIX`].
+///
+/// Empty subtrees are represented by the zero hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive_attr(derive(bytecheck::CheckBytes))
+)]
+pub struct Item([u8; 32]);
+
+impl Item {
+    /// Create a new item from the given hash `bytes`.
+    #[must_use]
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Create the item for a leaf holding the given `bytes`, by hashing them
+    /// prefixed with [`LEAF_PREFIX`].
+    #[must_use]
+    pub fn leaf(bytes: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(bytes);
+        Self(hasher.finalize().into())
+    }
+
+    /// Returns the bytes of the hash.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl<const A: usize> Aggregate<A> for Item {
+    const EMPTY_SUBTREE: Self = Item([0; 32]);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update([NODE_PREFIX]);
+        for item in items {
+            hasher.update(item.0);
+        }
+        Self(hasher.finalize().into())
+    }
+}
+
+impl Serializable<32> for Item {
+    type Error = BytesError;
+
+    fn from_bytes(buf: &[u8; 32]) -> Result<Self, Self::Error> {
+        Ok(Self(*buf))
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl LeafHash<32> for Item {
+    fn leaf(bytes: &[u8]) -> Self {
+        Self::leaf(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domain_separation() {
+        // SHA-256 of the single byte `0x00`, i.e. the leaf of no bytes
+        assert_eq!(
+            Item::leaf(b"").as_bytes(),
+            &[
+                0x6e, 0x34, 0x0b, 0x9c, 0xff, 0xb3, 0x7a, 0x98, 0x9c, 0xa5,
+                0x44, 0xe6, 0xbb, 0x78, 0x0a, 0x2c, 0x78, 0x90, 0x1d, 0x3f,
+                0xb3, 0x37, 0x38, 0x76, 0x85, 0x11, 0xa3, 0x06, 0x17, 0xaf,
+                0xa0, 0x1d,
+            ]
+        );
+
+        let leaf = Item::leaf(b"hello");
+        let node = <Item as Aggregate<2>>::aggregate([&leaf, &leaf]);
+
+        let mut bytes = [NODE_PREFIX; 65];
+        bytes[1..33].copy_from_slice(leaf.as_bytes());
+        bytes[33..].copy_from_slice(leaf.as_bytes());
+        assert_eq!(node.as_bytes()[..], Sha256::digest(bytes)[..]);
+        assert_ne!(node, Item::leaf(&bytes[1..]));
+    }
+
+    #[test]
+    fn insert_bytes() {
+        let mut tree = Tree::<8, 4>::new();
+        tree.insert_bytes(3, b"hello");
+        tree.insert_bytes(42, b"world");
+
+        let opening = tree.opening(42).unwrap();
+        assert!(opening.verify(Item::leaf(b"world")));
+        assert!(!opening.verify(Item::leaf(b"hello")));
+    }
+}
@@ -0,0 +1,183 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::{Aggregate, Opening, Tree};
+
+/// A leaf moved by a shuffle, with its openings in the old and the new tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShuffleLink<T, const H: usize, const A: usize> {
+    old_opening: Opening<T, H, A>,
+    new_opening: Opening<T, H, A>,
+}
+
+impl<T, const H: usize, const A: usize> ShuffleLink<T, H, A> {
+    /// Returns the opening of the leaf in the old tree.
+    pub const fn old_opening(&self) -> &Opening<T, H, A> {
+        &self.old_opening
+    }
+
+    /// Returns the opening of the leaf in the new tree.
+    pub const fn new_opening(&self) -> &Opening<T, H, A> {
+        &self.new_opening
+    }
+}
+
+impl<T, const H: usize, const A: usize> ShuffleLink<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// Returns the position of the leaf in the old tree.
+    pub fn old_position(&self) -> u64 {
+        self.old_opening.position()
+    }
+
+    /// Returns the position of the leaf in the new tree.
+    pub fn new_position(&self) -> u64 {
+        self.new_opening.position()
+    }
+}
+
+/// A proof that a tree is a permutation of the leaves of another tree.
+///
+/// The proof links each leaf of the old tree to a leaf of the new tree,
+/// with an opening into each. It is verified against the roots of both
+/// trees and the number of leaves they hold, ensuring every leaf is moved
+/// exactly once and no leaf is added or dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShuffleProof<T, const H: usize, const A: usize> {
+    links: Vec<ShuffleLink<T, H, A>>,
+}
+
+impl<T, const H: usize, const A: usize> ShuffleProof<T, H, A>
+where
+    T: Aggregate<A> + Clone + PartialEq,
+{
+    /// Prove that the `new` tree is the `old` tree with its leaves moved
+    /// according to the given `permutation`, mapping old positions to new
+    /// positions.
+    ///
+    /// Returns `None` if the permutation doesn't cover all leaves of both
+    /// trees exactly once, or if a leaf is not the same in both trees.
+    pub fn new(
+        old: &Tree<T, H, A>,
+        new: &Tree<T, H, A>,
+        permutation: &[(u64, u64)],
+    ) -> Option<Self> {
+        let len = permutation.len() as u64;
+        if old.len() != len || new.len() != len {
+            return None;
+        }
+
+        let mut links = Vec::with_capacity(permutation.len());
+        for &(old_position, new_position) in permutation {
+            let old_opening = old.opening(old_position)?;
+            let new_opening = new.opening(new_position)?;
+
+            if old_opening.leaf() != new_opening.leaf() {
+                return None;
+            }
+
+            links.push(ShuffleLink {
+                old_opening,
+                new_opening,
+            });
+        }
+
+        let proof = Self { links };
+        proof.is_bijective().then_some(proof)
+    }
+
+    /// Returns the links between the leaves of the old and the new tree.
+    #[must_use]
+    pub fn links(&self) -> &[ShuffleLink<T, H, A>] {
+        &self.links
+    }
+
+    /// Verify the proof links all the `len` leaves of the tree with
+    /// `old_root` to the leaves of the tree with `new_root`.
+    pub fn verify(&self, old_root: &T, new_root: &T, len: u64) -> bool {
+        if self.links.len() as u64 != len || !self.is_bijective() {
+            return false;
+        }
+
+        self.links.iter().all(|link| {
+            let Some(leaf) = link.old_opening.leaf() else {
+                return false;
+            };
+
+            link.old_opening.root() == old_root
+                && link.new_opening.root() == new_root
+                && link.old_opening.verify(leaf.clone())
+                && link.new_opening.verify(leaf.clone())
+        })
+    }
+
+    /// Returns `true` if no position is linked twice, in either tree.
+    fn is_bijective(&self) -> bool {
+        let mut old_positions = BTreeSet::new();
+        let mut new_positions = BTreeSet::new();
+
+        self.links.iter().all(|link| {
+            old_positions.insert(link.old_position())
+                && new_positions.insert(link.new_position())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H: usize = 4;
+    const A: usize = 2;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Xor(u64);
+
+    impl Aggregate<A> for Xor {
+        const EMPTY_SUBTREE: Self = Xor(0);
+
+        fn aggregate(items: [&Self; A]) -> Self {
+            Xor(items[0].0.rotate_left(7) ^ items[1].0.wrapping_add(1))
+        }
+    }
+
+    type TestTree = Tree<Xor, H, A>;
+
+    #[test]
+    fn shuffle() {
+        let permutation = [(1, 14), (4, 2), (9, 3)];
+
+        let mut old = TestTree::new();
+        let mut new = TestTree::new();
+        for (i, &(old_position, new_position)) in permutation.iter().enumerate()
+        {
+            old.insert(old_position, Xor(i as u64 + 10));
+            new.insert(new_position, Xor(i as u64 + 10));
+        }
+
+        let proof = ShuffleProof::new(&old, &new, &permutation)
+            .expect("The permutation should be valid");
+
+        assert_eq!(proof.links()[0].old_position(), 1);
+        assert_eq!(proof.links()[0].new_position(), 14);
+
+        assert!(proof.verify(&old.root(), &new.root(), 3));
+        assert!(!proof.verify(&new.root(), &old.root(), 3));
+        assert!(!proof.verify(&old.root(), &new.root(), 4));
+
+        // leaves can't be dropped, swapped, or linked twice
+        assert!(ShuffleProof::new(&old, &new, &permutation[..2]).is_none());
+        assert!(
+            ShuffleProof::new(&old, &new, &[(1, 2), (4, 14), (9, 3)]).is_none()
+        );
+        assert!(ShuffleProof::new(&old, &new, &[(1, 14), (1, 14), (9, 3)])
+            .is_none());
+    }
+}
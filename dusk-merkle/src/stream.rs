@@ -0,0 +1,409 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::Hasher;
+
+use dusk_bytes::{DeserializableSlice, Serializable};
+
+use crate::{Aggregate, Tree};
+
+/// A compact message announcing the root of a tree after a batch of
+/// mutations, meant to be pushed to light clients following the tree.
+///
+/// Created using [`Tree::root_update`] or [`Tree::root_update_with_digest`],
+/// and applied by clients to a [`RootTracker`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RootUpdate<T> {
+    root: T,
+    generation: u64,
+    changed: u64,
+    digest: Option<u64>,
+}
+
+impl<T> RootUpdate<T> {
+    /// Returns the root of the tree.
+    #[must_use]
+    pub const fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the generation of the tree.
+    #[must_use]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the number of leaves changed since the previous update.
+    #[must_use]
+    pub const fn changed(&self) -> u64 {
+        self.changed
+    }
+
+    /// Returns the digest of the positions changed since the previous
+    /// update, if it was computed.
+    #[must_use]
+    pub const fn digest(&self) -> Option<u64> {
+        self.digest
+    }
+
+    /// Serialize the update to a vector of bytes.
+    ///
+    /// The serialized update is composed of:
+    /// - the root
+    /// - the generation, as a `u64`
+    /// - the number of changed leaves, as a `u64`
+    /// - the digest, as a `u64`, if any
+    #[must_use]
+    pub fn to_var_bytes<const T_SIZE: usize>(&self) -> Vec<u8>
+    where
+        T: Serializable<T_SIZE>,
+    {
+        let mut bytes = Vec::with_capacity(T_SIZE + 24);
+
+        bytes.extend(&self.root.to_bytes());
+        bytes.extend(&self.generation.to_bytes());
+        bytes.extend(&self.changed.to_bytes());
+        if let Some(digest) = self.digest {
+            bytes.extend(&digest.to_bytes());
+        }
+
+        bytes
+    }
+
+    /// Deserialize an update from a slice of bytes, returning `None` if it is
+    /// malformed.
+    #[must_use]
+    pub fn from_slice<const T_SIZE: usize>(buf: &[u8]) -> Option<Self>
+    where
+        T: Serializable<T_SIZE>,
+        <T as Serializable<T_SIZE>>::Error: dusk_bytes::BadLength,
+    {
+        let mut buf = buf;
+
+        let root = T::from_reader(&mut buf).ok()?;
+        let generation = u64::from_reader(&mut buf).ok()?;
+        let changed = u64::from_reader(&mut buf).ok()?;
+        let digest = match buf.len() {
+            0 => None,
+            8 => Some(u64::from_reader(&mut buf).ok()?),
+            _ => return None,
+        };
+
+        Some(Self {
+            root,
+            generation,
+            changed,
+            digest,
+        })
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A> {
+    /// Start tracking the positions of the leaves changed by mutations, to
+    /// be reported by [`root_update`].
+    ///
+    /// Changes are not tracked by default, since the changed positions are
+    /// kept until the next update. Trees streaming their root updates should
+    /// call this once, before the first batch of mutations.
+    ///
+    /// [`root_update`]: Tree::root_update
+    pub fn track_changes(&mut self) {
+        self.changed.get_or_insert_with(BTreeSet::new);
+    }
+
+    /// Returns `true` if the positions of the changed leaves are tracked.
+    #[must_use]
+    pub const fn tracks_changes(&self) -> bool {
+        self.changed.is_some()
+    }
+}
+
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    /// Returns a [`RootUpdate`] with the current root and generation of the
+    /// tree, and the number of leaves changed since the previous update.
+    ///
+    /// This is meant to be called after each batch of mutations - e.g. after
+    /// processing a block. Leaves changed by a merge are counted as all the
+    /// leaves of the merged tree. Changed leaves are only counted once
+    /// [`track_changes`] has been called, and are otherwise reported as zero.
+    ///
+    /// [`track_changes`]: Tree::track_changes
+    pub fn root_update(&mut self) -> RootUpdate<T> {
+        let update = RootUpdate {
+            root: self.root().clone(),
+            generation: self.generation,
+            changed: self
+                .changed
+                .as_ref()
+                .map_or(0, |changed| changed.len() as u64),
+            digest: None,
+        };
+        if let Some(changed) = &mut self.changed {
+            changed.clear();
+        }
+        update
+    }
+
+    /// Returns a [`RootUpdate`] like [`root_update`], including a digest of
+    /// the changed positions computed with the [`Hasher`] `S`.
+    ///
+    /// Clients holding openings may use the digest to check whether the
+    /// changes they were told about are the ones the tree went through. The
+    /// digest is only computed once [`track_changes`] has been called, and is
+    /// otherwise left out of the update, rather than being the digest of no
+    /// changes.
+    ///
+    /// [`root_update`]: Tree::root_update
+    /// [`track_changes`]: Tree::track_changes
+    pub fn root_update_with_digest<S>(&mut self) -> RootUpdate<T>
+    where
+        S: Hasher + Default,
+    {
+        let digest = self.changed.as_ref().map(|changed| {
+            let mut hasher = S::default();
+            for &position in changed {
+                hasher.write_u64(position);
+            }
+            hasher.finish()
+        });
+
+        let mut update = self.root_update();
+        update.digest = digest;
+        update
+    }
+}
+
+/// Tracks the root of a tree on the client side, as it evolves through
+/// [`RootUpdate`]s.
+///
+/// Updates are only accepted when they are newer than the last one applied,
+/// guarding against stale or replayed messages.
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, RootTracker, RootUpdateError, Tree};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl Aggregate<2> for Sum {
+///     const EMPTY_SUBTREE: Self = Sum(0);
+///
+///     fn aggregate(items: [&Self; 2]) -> Self {
+///         Sum(items[0].0 + items[1].0)
+///     }
+/// }
+///
+/// let mut tree = Tree::<Sum, 8, 2>::new();
+/// tree.track_changes();
+/// let mut tracker = RootTracker::new(*tree.root(), tree.generation());
+///
+/// tree.insert(3, Sum(2));
+/// tree.insert(5, Sum(3));
+/// let update = tree.root_update();
+/// assert_eq!(update.changed(), 2);
+///
+/// tracker.apply(&update).unwrap();
+/// assert_eq!(tracker.root(), &Sum(5));
+///
+/// // the same update can't be applied twice
+/// assert_eq!(
+///     tracker.apply(&update),
+///     Err(RootUpdateError::Stale { current: 2, received: 2 })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RootTracker<T> {
+    root: T,
+    generation: u64,
+}
+
+impl<T> RootTracker<T> {
+    /// Create a new tracker, starting at the given `root` and `generation`.
+    #[must_use]
+    pub const fn new(root: T, generation: u64) -> Self {
+        Self { root, generation }
+    }
+
+    /// Returns the root being tracked.
+    #[must_use]
+    pub const fn root(&self) -> &T {
+        &self.root
+    }
+
+    /// Returns the generation of the tracked root.
+    #[must_use]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Apply the given `update`, advancing the tracked root.
+    ///
+    /// # Errors
+    /// If the update is not newer than the tracked root, a
+    /// [`RootUpdateError::Stale`] is returned and the tracker is left
+    /// untouched.
+    pub fn apply(
+        &mut self,
+        update: &RootUpdate<T>,
+    ) -> Result<(), RootUpdateError>
+    where
+        T: Clone,
+    {
+        if update.generation <= self.generation {
+            return Err(RootUpdateError::Stale {
+                current: self.generation,
+                received: update.generation,
+            });
+        }
+
+        self.root = update.root.clone();
+        self.generation = update.generation;
+
+        Ok(())
+    }
+}
+
+/// The error returned when a [`RootUpdate`] can't be applied to a
+/// [`RootTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RootUpdateError {
+    /// The update is not newer than the tracked root.
+    Stale {
+        /// The generation of the tracked root.
+        current: u64,
+        /// The generation of the update.
+        received: u64,
+    },
+}
+
+impl fmt::Display for RootUpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stale { current, received } => write!(
+                f,
+                "stale root update: at generation {current}, \
+                 received generation {received}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RootUpdateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::Sum;
+    use crate::TreeBuilder;
+
+    const H: usize = 3;
+    const A: usize = 2;
+
+    /// A hasher summing the positions, to make digests easy to predict.
+    #[derive(Default)]
+    struct SumHasher(u64);
+
+    impl Hasher for SumHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for chunk in bytes.chunks(8) {
+                let mut buf = [0u8; 8];
+                buf[..chunk.len()].copy_from_slice(chunk);
+                self.0 += u64::from_le_bytes(buf);
+            }
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.0 += n;
+        }
+    }
+
+    #[test]
+    fn root_updates() {
+        let mut tree = Tree::<Sum, H, A>::new();
+        tree.track_changes();
+        tree.insert(1, Sum(3));
+        tree.insert(4, Sum(5));
+        let mut tracker = RootTracker::new(Sum(0), 0);
+
+        let update = tree.root_update_with_digest::<SumHasher>();
+        assert_eq!(update.root(), &Sum(8));
+        assert_eq!(update.generation(), 2);
+        assert_eq!(update.changed(), 2);
+        assert_eq!(update.digest(), Some(5));
+        assert_eq!(tracker.apply(&update), Ok(()));
+
+        // changing the same leaf twice counts it once
+        tree.insert(6, Sum(1));
+        tree.insert(6, Sum(2));
+        tree.remove(1);
+        let update = tree.root_update();
+        assert_eq!(update.changed(), 2);
+        assert_eq!(update.digest(), None);
+
+        let bytes = update.to_var_bytes();
+        assert_eq!(bytes.len(), 24);
+        let decoded = RootUpdate::<Sum>::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, update);
+        assert_eq!(RootUpdate::<Sum>::from_slice(&bytes[..20]), None);
+
+        let mut merged: Tree<Sum, H, A> =
+            TreeBuilder::new().with_leaf(0, Sum(1)).build();
+        merged.track_changes();
+        merged.merge(tree).unwrap();
+        let digested = merged.root_update_with_digest::<SumHasher>();
+        assert_eq!(digested.changed(), 2);
+        assert_eq!(digested.digest(), Some(10));
+        let bytes = digested.to_var_bytes();
+        assert_eq!(RootUpdate::from_slice(&bytes), Some(digested));
+
+        assert_eq!(tracker.apply(&update), Ok(()));
+        assert_eq!(tracker.root(), &Sum(7));
+        assert_eq!(tracker.generation(), 5);
+        assert_eq!(
+            tracker.apply(&update),
+            Err(RootUpdateError::Stale {
+                current: 5,
+                received: 5
+            })
+        );
+    }
+
+    #[test]
+    fn untracked_changes() {
+        let mut tree: Tree<Sum, H, A> = TreeBuilder::new()
+            .with_leaves([(1, Sum(3)), (4, Sum(5))])
+            .build();
+        assert!(!tree.tracks_changes());
+        assert_eq!(tree.changed, None);
+
+        let update = tree.root_update_with_digest::<SumHasher>();
+        assert_eq!(update.root(), &Sum(8));
+        assert_eq!(update.changed(), 0);
+        assert_eq!(update.digest(), None);
+
+        tree.track_changes();
+        tree.insert(2, Sum(1));
+        assert_eq!(tree.root_update().changed(), 1);
+
+        // tracking with no changes still computes the digest
+        let update = tree.root_update_with_digest::<SumHasher>();
+        assert_eq!(update.changed(), 0);
+        assert_eq!(update.digest(), Some(0));
+    }
+}
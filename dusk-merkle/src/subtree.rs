@@ -0,0 +1,258 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::{capacity, trace, Aggregate, Ref, Tree, Walk};
+
+/// A handle to a subtree of a [`Tree`], allowing for operations addressed
+/// relative to the subtree.
+///
+/// Positions given to the handle are relative to the start of the subtree,
+/// and operations only traverse the levels below it. The path from the
+/// subtree to the root of the tree is only invalidated once, when the handle
+/// is dropped, making it cheap to perform many operations local to a shard
+/// of a tree.
+///
+/// Created using [`Tree::subtree_mut`].
+#[derive(Debug)]
+pub struct SubtreeMut<'a, T, const H: usize, const A: usize>
+where
+    T: Aggregate<A>,
+{
+    tree: &'a mut Tree<T, H, A>,
+    height: usize,
+    index: u64,
+    touched: bool,
+}
+
+impl<'a, T, const H: usize, const A: usize> SubtreeMut<'a, T, H, A>
+where
+    T: Aggregate<A>,
+{
+    pub(crate) fn new(
+        tree: &'a mut Tree<T, H, A>,
+        height: usize,
+        index: u64,
+    ) -> Self {
+        Self {
+            tree,
+            height,
+            index,
+            touched: false,
+        }
+    }
+
+    /// Returns the height of the subtree in the tree.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the index of the subtree within its height.
+    #[must_use]
+    pub const fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the number of leaves the subtree can hold.
+    #[must_use]
+    pub const fn capacity(&self) -> u64 {
+        capacity(A as u64, H - self.height)
+    }
+
+    /// Returns the position in the tree of the given `position` in the
+    /// subtree.
+    ///
+    /// # Panics
+    /// If `position >= capacity`.
+    #[must_use]
+    pub fn absolute_position(&self, position: u64) -> u64 {
+        let capacity = self.capacity();
+        assert!(
+            position < capacity,
+            "position out of bounds: \
+             the capacity is {capacity} but the position is {position}"
+        );
+        self.index * capacity + position
+    }
+
+    /// Insert an `item` at the given `position` in the subtree.
+    ///
+    /// # Panics
+    /// If `position >= capacity`.
+    pub fn insert(&mut self, position: u64, item: impl Into<T>) {
+        let absolute = self.absolute_position(position);
+
+        trace::span!("subtree_insert", position = absolute);
+
+        self.tree.record_leaf(absolute);
+        self.tree
+            .root
+            .descendant_or_insert(self.height, self.index)
+            .insert(self.height, position, item);
+        self.touched = true;
+
+        self.tree.positions.insert(absolute);
+        self.tree.generation = self.tree.generation.wrapping_add(1);
+        self.tree.invalidate_openings(&[absolute]);
+    }
+
+    /// Remove and return the item at the given `position` in the subtree if
+    /// it exists.
+    pub fn remove(&mut self, position: u64) -> Option<T> {
+        if position >= self.capacity() {
+            return None;
+        }
+
+        let absolute = self.absolute_position(position);
+        if !self.tree.positions.contains(&absolute) {
+            return None;
+        }
+
+        trace::span!("subtree_remove", position = absolute);
+
+        self.tree.record_leaf(absolute);
+        let Some(node) = self.tree.root.descendant_mut(self.height, self.index)
+        else {
+            unreachable!("the subtree holds an occupied position");
+        };
+        let (item, _) = node.remove(self.height, position);
+        self.touched = true;
+
+        self.tree.positions.remove(&absolute);
+        self.tree.generation = self.tree.generation.wrapping_add(1);
+        self.tree.invalidate_openings(&[absolute]);
+
+        Some(item)
+    }
+
+    /// Returns a reference to the item of the leaf at the given `position` in
+    /// the subtree, if it exists.
+    #[must_use]
+    pub fn leaf(&self, position: u64) -> Option<Ref<'_, T>> {
+        if position >= self.capacity() {
+            return None;
+        }
+
+        let node = self.tree.root.descendant(self.height, self.index)?;
+        let leaf = node.descendant(H - self.height, position)?;
+        Some(leaf.item())
+    }
+
+    /// Returns true if the subtree contains a leaf at the given `position`.
+    #[must_use]
+    pub fn contains(&self, position: u64) -> bool {
+        position < self.capacity()
+            && self
+                .tree
+                .positions
+                .contains(&self.absolute_position(position))
+    }
+
+    /// Returns the number of leaves in the subtree.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        let start = self.index * self.capacity();
+        self.tree
+            .positions
+            .range(start..start + self.capacity())
+            .count() as u64
+    }
+
+    /// Returns true if the subtree has no leaves.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a [`Walk`] through the subtree, proceeding according to the
+    /// `walker` function.
+    ///
+    /// The leaves are yielded together with their positions in the tree, not
+    /// in the subtree.
+    pub fn walk<W>(&self, walker: W) -> Walk<'_, T, W, H, A>
+    where
+        W: Fn(&T) -> bool,
+    {
+        Walk::from_node(
+            self.tree.root.descendant(self.height, self.index),
+            self.height,
+            self.index,
+            walker,
+        )
+    }
+}
+
+impl<T, const H: usize, const A: usize> Drop for SubtreeMut<'_, T, H, A>
+where
+    T: Aggregate<A>,
+{
+    fn drop(&mut self) {
+        if self.touched {
+            trace::span!("subtree_invalidate", nodes = self.height + 1);
+            self.tree.root.invalidate_path(self.height, self.index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::Sum;
+    use alloc::vec::Vec;
+
+    const H: usize = 4;
+    const A: usize = 2;
+
+    type SumTree = Tree<Sum, H, A>;
+
+    #[test]
+    fn subtree_mut() {
+        let mut tree = SumTree::new();
+        tree.insert(0, Sum(1));
+        let _ = tree.root();
+
+        let mut expected = SumTree::new();
+        expected.insert(0, Sum(1));
+
+        {
+            let mut subtree = tree.subtree_mut(2, 2);
+            assert_eq!(subtree.capacity(), 4);
+            assert_eq!(subtree.absolute_position(3), 11);
+            assert!(subtree.is_empty());
+
+            subtree.insert(1, Sum(2));
+            subtree.insert(3, Sum(4));
+            subtree.insert(2, Sum(8));
+            assert_eq!(subtree.remove(2), Some(Sum(8)));
+            assert_eq!(subtree.remove(0), None);
+            assert_eq!(subtree.remove(4), None);
+
+            assert_eq!(subtree.len(), 2);
+            assert!(subtree.contains(3));
+            assert_eq!(subtree.leaf(1).as_deref(), Some(&Sum(2)));
+
+            let leaves: Vec<(u64, Sum)> =
+                subtree.walk(|_| true).map(|(p, s)| (p, *s)).collect();
+            assert_eq!(leaves, [(9, Sum(2)), (11, Sum(4))]);
+        }
+        expected.insert(9, Sum(2));
+        expected.insert(11, Sum(4));
+
+        assert_eq!(*tree.root(), Sum(7));
+        assert!(tree.positions().eq(expected.positions()));
+        assert_eq!(tree.opening(11), expected.opening(11));
+
+        // emptying the subtree prunes it from the tree
+        {
+            let mut subtree = tree.subtree_mut(2, 2);
+            subtree.remove(1);
+            subtree.remove(3);
+        }
+        assert_eq!(*tree.root(), Sum(1));
+        assert!(tree.root.children[1].is_none());
+    }
+}
@@ -0,0 +1,41 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Fixtures shared by the tests of the crate.
+
+use dusk_bytes::{Error as BytesError, Serializable};
+
+use crate::Aggregate;
+
+/// An item aggregated by summing the children, usable with any arity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sum(pub u64);
+
+impl From<u64> for Sum {
+    fn from(n: u64) -> Self {
+        Self(n)
+    }
+}
+
+impl<const A: usize> Aggregate<A> for Sum {
+    const EMPTY_SUBTREE: Self = Sum(0);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        Self(items.into_iter().map(|s| s.0).sum())
+    }
+}
+
+impl Serializable<8> for Sum {
+    type Error = BytesError;
+
+    fn from_bytes(buf: &[u8; 8]) -> Result<Self, Self::Error> {
+        Ok(Self(u64::from_le_bytes(*buf)))
+    }
+
+    fn to_bytes(&self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+}
@@ -0,0 +1,25 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Instrumentation emitted using `tracing` when the feature of the same name
+//! is enabled. Without it, the macro expands to nothing, and its arguments
+//! is never evaluated.
+
+/// Enters a trace level span with the given name and fields, until the end of
+/// the enclosing block.
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($arg:tt)*) => {
+        let _span = ::tracing::trace_span!($($arg)*).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($arg:tt)*) => {};
+}
+
+pub(crate) use span;
@@ -4,13 +4,45 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use alloc::collections::BTreeSet;
-use core::cell::Ref;
-
-use crate::{capacity, Aggregate, Node, Opening, Walk};
+use alloc::collections::{btree_set, BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::Range;
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "rand")]
+use rand_core::RngCore;
+#[cfg(feature = "rkyv-impl")]
+use rkyv::validation::validators::DefaultValidator;
+
+#[cfg(feature = "rand")]
+use crate::allocator::random_below;
+use crate::trace;
+use crate::{
+    capacity, Aggregate, Bitmap, BreadthFirst, CacheCell, CachePolicy, Error,
+    Fingerprint, Journal, Leaves, Node, NonMembershipProof, Opening,
+    OpeningBuf, PositionAllocator, RangeOpening, RecentRoots, Ref, SubtreeMut,
+    VersionedOpening, Walk, WalkCloned, Weighted, WithHeight,
+};
 
 /// A sparse Merkle tree.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Cloning a tree copies all of its nodes, unless the `shared` feature is
+/// enabled. With it, the nodes are shared between the clones of a tree and
+/// only copied once mutated, making a clone - e.g. a read-only snapshot -
+/// constant time. Since the nodes of a tree may then be read from several
+/// threads through its clones, the feature enables `sync`.
+///
+/// Items are computed lazily when the tree is queried, and are therefore held
+/// in [`RefCell`]s, making the tree not [`Sync`]. With the `sync` feature
+/// they are held behind locks instead, allowing for a tree to be queried
+/// concurrently from multiple threads, at the cost of taking a lock for each
+/// item read. [`Ref`] then holds a read lock on the item it refers to.
+///
+/// [`RefCell`]: core::cell::RefCell
+#[derive(Debug, Clone)]
 #[cfg_attr(
     feature = "rkyv-impl",
     derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
@@ -18,9 +50,42 @@ use crate::{capacity, Aggregate, Node, Opening, Walk};
 )]
 pub struct Tree<T, const H: usize, const A: usize> {
     pub(crate) root: Node<T, H, A>,
-    positions: BTreeSet<u64>,
+    pub(crate) positions: BTreeSet<u64>,
+    pub(crate) generation: u64,
+    fingerprint: Fingerprint,
+    #[cfg_attr(feature = "rkyv-impl", with(rkyv::with::Skip))]
+    cache_policy: CachePolicy,
+    #[cfg_attr(feature = "rkyv-impl", with(rkyv::with::Skip))]
+    openings: CacheCell<BTreeMap<u64, CachedOpening<T, H, A>>>,
+    #[cfg_attr(feature = "rkyv-impl", with(rkyv::with::Skip))]
+    subscriptions: BTreeMap<u64, bool>,
+    #[cfg_attr(feature = "rkyv-impl", with(rkyv::with::Skip))]
+    pub(crate) changed: Option<BTreeSet<u64>>,
+    #[cfg_attr(feature = "rkyv-impl", with(rkyv::with::Skip))]
+    pub(crate) journal: Option<Journal<T>>,
+    #[cfg_attr(feature = "rkyv-impl", with(rkyv::with::Skip))]
+    pub(crate) recent_roots: Option<RecentRoots<T>>,
+}
+
+/// An opening memoized by [`Tree::opening_cached`], together with the number
+/// of its top levels that have been invalidated by mutations.
+#[derive(Debug, Clone)]
+struct CachedOpening<T, const H: usize, const A: usize> {
+    opening: Opening<T, H, A>,
+    stale_levels: usize,
+}
+
+impl<T, const H: usize, const A: usize> PartialEq for Tree<T, H, A>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root && self.positions == other.positions
+    }
 }
 
+impl<T, const H: usize, const A: usize> Eq for Tree<T, H, A> where T: Eq {}
+
 impl<T, const H: usize, const A: usize> Default for Tree<T, H, A>
 where
     T: Aggregate<A>,
@@ -34,12 +99,52 @@ impl<T, const H: usize, const A: usize> Tree<T, H, A>
 where
     T: Aggregate<A>,
 {
-    /// Create a new merkle tree with the given initial `root`.
+    /// Create a new, empty, merkle tree.
+    ///
+    /// Fails to compile if the height is zero, the arity is smaller than two,
+    /// or the capacity of the tree doesn't fit in a `u64`, i.e. if
+    /// `H * log2(A) >= 64`:
+    ///
+    /// ```compile_fail
+    /// use dusk_merkle::{Aggregate, Tree};
+    ///
+    /// struct Item;
+    ///
+    /// impl Aggregate<4> for Item {
+    ///     const EMPTY_SUBTREE: Self = Item;
+    ///
+    ///     fn aggregate(_: [&Self; 4]) -> Self {
+    ///         Item
+    ///     }
+    /// }
+    ///
+    /// // a tree of this shape would hold 2^128 leaves
+    /// let tree = Tree::<Item, 64, 4>::new();
+    /// ```
     #[must_use]
     pub const fn new() -> Self {
+        Self::with_backend(0)
+    }
+
+    /// Create a new, empty, merkle tree, whose [`Fingerprint`] records the
+    /// given `backend` identifier.
+    ///
+    /// Fails to compile under the same conditions as [`new`].
+    ///
+    /// [`new`]: Tree::new
+    #[must_use]
+    pub const fn with_backend(backend: u32) -> Self {
         Self {
             root: Node::new(),
             positions: BTreeSet::new(),
+            generation: 0,
+            fingerprint: Fingerprint::new::<H, A>(backend),
+            cache_policy: CachePolicy::Always,
+            openings: CacheCell::new(BTreeMap::new()),
+            subscriptions: BTreeMap::new(),
+            changed: None,
+            journal: None,
+            recent_roots: None,
         }
     }
 
@@ -56,8 +161,92 @@ where
              the capacity is {capacity} but the index is {index}"
         );
 
+        trace::span!("insert", position = index, nodes = H + 1);
+
+        self.record_leaf(index);
         self.root.insert(0, index, item);
         self.positions.insert(index);
+        self.generation = self.generation.wrapping_add(1);
+        self.invalidate_openings(&[index]);
+    }
+
+    /// Insert an `item` at the given `position` in the tree, like
+    /// [`insert`], but without panicking.
+    ///
+    /// # Errors
+    /// If `position >= capacity` an [`Error::OutOfBounds`] is returned, and
+    /// the tree is left untouched.
+    ///
+    /// [`insert`]: Tree::insert
+    pub fn try_insert(
+        &mut self,
+        position: u64,
+        item: impl Into<T>,
+    ) -> Result<(), Error> {
+        let capacity = self.capacity();
+        if position >= capacity {
+            return Err(Error::OutOfBounds { position, capacity });
+        }
+
+        self.insert(position, item);
+        Ok(())
+    }
+
+    /// Insert an `item` at the given `position` in the tree, refusing to
+    /// replace an existing leaf.
+    ///
+    /// # Errors
+    /// If the position is already occupied, an [`OccupiedError`] holding the
+    /// stored item and the rejected one is returned, and the tree is left
+    /// untouched.
+    ///
+    /// # Panics
+    /// If `position >= capacity`.
+    pub fn insert_unique(
+        &mut self,
+        position: u64,
+        item: impl Into<T>,
+    ) -> Result<(), OccupiedError<T>>
+    where
+        T: Clone,
+    {
+        if self.positions.contains(&position) {
+            let Some(leaf) = self.root.descendant(H, position) else {
+                unreachable!("occupied positions always have a leaf");
+            };
+
+            return Err(OccupiedError {
+                position,
+                existing: leaf.item().clone(),
+                item: item.into(),
+            });
+        }
+
+        self.insert(position, item);
+        Ok(())
+    }
+
+    /// Insert an `item` at a position chosen by the given `allocator`,
+    /// returning the position used.
+    ///
+    /// If the allocator can't provide a position the item is not inserted,
+    /// and `None` is returned. An item already in the allocated position is
+    /// replaced.
+    ///
+    /// # Panics
+    /// If the allocator returns a position larger or equal to the capacity.
+    pub fn insert_alloc<P>(
+        &mut self,
+        allocator: &mut P,
+        item: impl Into<T>,
+    ) -> Option<u64>
+    where
+        P: PositionAllocator<T>,
+    {
+        let item = item.into();
+        let position = allocator.allocate(&item, self.capacity())?;
+        self.insert(position, item);
+        Some(position)
     }
 
     /// Remove and return the item at the given `position` in the tree if it
@@ -67,12 +256,224 @@ where
             return None;
         }
 
+        trace::span!("remove", position, nodes = H + 1);
+
+        self.record_leaf(position);
         let (item, _) = self.root.remove(0, position);
         self.positions.remove(&position);
+        self.generation = self.generation.wrapping_add(1);
+        self.invalidate_openings(&[position]);
 
         Some(item)
     }
 
+    /// Remove and return the item at the given `position` in the tree if it
+    /// exists, like [`remove`], but never panicking.
+    ///
+    /// # Errors
+    /// If `position >= capacity` an [`Error::OutOfBounds`] is returned. If
+    /// the position is recorded as occupied but holds no leaf, an
+    /// [`Error::Inconsistent`] is returned. In both cases the leaves of the
+    /// tree are left untouched.
+    ///
+    /// [`remove`]: Tree::remove
+    pub fn try_remove(&mut self, position: u64) -> Result<Option<T>, Error> {
+        let capacity = self.capacity();
+        if position >= capacity {
+            return Err(Error::OutOfBounds { position, capacity });
+        }
+        if !self.positions.contains(&position) {
+            return Ok(None);
+        }
+
+        trace::span!("try_remove", position, nodes = H + 1);
+
+        self.record_leaf(position);
+        let Some((item, _)) = self.root.try_remove(0, position) else {
+            return Err(Error::Inconsistent { position });
+        };
+        self.positions.remove(&position);
+        self.generation = self.generation.wrapping_add(1);
+        self.invalidate_openings(&[position]);
+
+        Ok(Some(item))
+    }
+
+    /// Remove the items at the given `positions` in the tree, returning the
+    /// ones that existed together with their positions, in ascending order.
+    ///
+    /// The tree is descended once for all the positions, meaning the nodes
+    /// shared by their paths are only visited - and emptied subtrees only
+    /// pruned - once. Positions holding no item, or given more than once,
+    /// are ignored.
+    pub fn remove_batch<I>(&mut self, positions: I) -> Vec<(u64, T)>
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let mut positions: Vec<u64> = positions
+            .into_iter()
+            .filter(|position| self.positions.contains(position))
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+
+        if positions.is_empty() {
+            return Vec::new();
+        }
+
+        trace::span!(
+            "remove_batch",
+            leaves = positions.len(),
+            nodes = path_nodes::<H, A>(&positions)
+        );
+
+        self.record_leaves(&positions);
+
+        let mut removed = Vec::with_capacity(positions.len());
+        self.root.remove_batch(0, &positions, &mut removed);
+
+        for position in &positions {
+            self.positions.remove(position);
+        }
+        self.generation = self.generation.wrapping_add(1);
+        self.invalidate_openings(&positions);
+
+        removed
+    }
+
+    /// Merge the `other` tree into this one, calling `resolve` with this
+    /// tree's item and the other tree's item for each position where both
+    /// trees hold a different leaf.
+    ///
+    /// Both trees are descended simultaneously, and subtrees present in only
+    /// one of the trees are moved without being traversed.
+    ///
+    /// # Errors
+    /// If the trees have different fingerprints, a [`MergeError`] is returned
+    /// and this tree is left untouched.
+    pub fn merge_with<F>(
+        &mut self,
+        other: Self,
+        resolve: F,
+    ) -> Result<(), MergeError>
+    where
+        T: PartialEq,
+        F: Fn(T, T) -> T,
+    {
+        other
+            .fingerprint
+            .check(&self.fingerprint)
+            .map_err(MergeError::Fingerprint)?;
+
+        trace::span!("merge_with", leaves = other.positions.len());
+
+        self.record_leaves(&other.positions);
+        self.root.merge_with(other.root, 0, &resolve);
+        self.track_changed(&other.positions);
+        self.positions.extend(other.positions);
+        self.generation = self.generation.wrapping_add(1);
+        self.openings.get_mut().clear();
+        self.mark_subscriptions();
+
+        Ok(())
+    }
+
+    /// Merge the `other` tree into this one, if they hold no leaves at the
+    /// same positions - e.g. shards of the same tree built over disjoint
+    /// position ranges.
+    ///
+    /// Subtrees present in only one of the trees are grafted without being
+    /// traversed, and only the items of the nodes shared by both are
+    /// recomputed the next time the root is computed.
+    ///
+    /// # Errors
+    /// If the trees have different fingerprints, or hold a leaf at the same
+    /// position, a [`MergeError`] is returned and this tree is left
+    /// untouched.
+    pub fn merge(&mut self, other: Self) -> Result<(), MergeError> {
+        other
+            .fingerprint
+            .check(&self.fingerprint)
+            .map_err(MergeError::Fingerprint)?;
+
+        if let Some(&position) = self.positions_intersection(&other).first() {
+            return Err(MergeError::Overlap { position });
+        }
+
+        trace::span!("merge", leaves = other.positions.len());
+
+        self.record_leaves(&other.positions);
+        self.root.graft(other.root);
+        self.track_changed(&other.positions);
+        self.positions.extend(other.positions);
+        self.generation = self.generation.wrapping_add(1);
+        self.openings.get_mut().clear();
+        self.mark_subscriptions();
+
+        Ok(())
+    }
+
+    /// Returns a tree with the same leaf positions as this one, with each
+    /// leaf converted using `f`.
+    ///
+    /// The structure of the tree is copied as is, and the aggregates are
+    /// recomputed in the domain of `U` the next time the root is computed.
+    /// This allows for migrating a tree to another [`Aggregate`] - e.g.
+    /// another hash function - without going through its leaves one by one.
+    ///
+    /// The returned tree keeps the [`CachePolicy`] of this one, but has a new
+    /// generation and the default [`Fingerprint`], since both describe the
+    /// items of this tree.
+    pub fn map<U, F>(&self, f: F) -> Tree<U, H, A>
+    where
+        U: Aggregate<A>,
+        F: Fn(&T) -> U,
+    {
+        trace::span!("map", leaves = self.positions.len());
+
+        let mut tree = Tree::new();
+        tree.root = self.root.map(0, &f);
+        tree.positions.clone_from(&self.positions);
+        tree.cache_policy = self.cache_policy;
+        tree
+    }
+
+    /// Returns the generation of the tree, i.e. the number of mutations it
+    /// went through.
+    ///
+    /// The generation is incremented on each insertion, removal, and merge,
+    /// and can be used to version the openings of the tree.
+    #[must_use]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the [`Fingerprint`] of the tree, recording the parameters it
+    /// was built with.
+    ///
+    /// The fingerprint is kept when the tree is archived, meaning openings
+    /// tagged with it remain valid against the tree after it is restored, as
+    /// long as the fingerprints match.
+    #[must_use]
+    pub const fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    /// Returns the [`Opening`] for the given `position` if it exists, tagged
+    /// with the current [`generation`] of the tree.
+    ///
+    /// [`generation`]: Tree::generation
+    pub fn versioned_opening(
+        &self,
+        position: u64,
+    ) -> Option<VersionedOpening<T, H, A>>
+    where
+        T: Clone,
+    {
+        self.opening(position)
+            .map(|opening| VersionedOpening::new(self.generation, opening))
+    }
+
     /// Returns the [`Opening`] for the given `position` if it exists.
     pub fn opening(&self, position: u64) -> Option<Opening<T, H, A>>
     where
@@ -84,27 +485,490 @@ where
         Some(Opening::new(self, position))
     }
 
+    /// Returns the [`RangeOpening`] for the positions from `start` up to, but
+    /// not including, `end`.
+    ///
+    /// Returns `None` if the range is empty or exceeds the capacity of the
+    /// tree.
+    pub fn opening_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Option<RangeOpening<T, H, A>>
+    where
+        T: Clone,
+    {
+        if start >= end || end > self.capacity() {
+            return None;
+        }
+        Some(RangeOpening::new(self, start, end))
+    }
+
+    /// Returns a [`NonMembershipProof`] showing there is no leaf at the given
+    /// `position`.
+    ///
+    /// Returns `None` if the position holds a leaf, or is larger or equal to
+    /// the capacity of the tree.
+    pub fn non_membership(
+        &self,
+        position: u64,
+    ) -> Option<NonMembershipProof<T, H, A>>
+    where
+        T: Clone,
+    {
+        if position >= self.capacity() || self.positions.contains(&position) {
+            return None;
+        }
+        Some(NonMembershipProof::new(self, position))
+    }
+
+    /// Build the opening for the given `position` into the given `buf`,
+    /// returning `false` if the position is not in the tree.
+    ///
+    /// The path to the leaf is descended iteratively and nothing is
+    /// allocated, making this suitable for generating openings in constrained
+    /// environments. Items that weren't computed since the last mutation are
+    /// computed as usual, so computing the [`root`] beforehand ensures no
+    /// work is done here beyond copying them. If the position is not in the
+    /// tree, the buffer is left untouched.
+    ///
+    /// [`root`]: Tree::root
+    pub fn build_opening_into(
+        &self,
+        position: u64,
+        buf: &mut OpeningBuf<T, H, A>,
+    ) -> bool
+    where
+        T: Clone,
+    {
+        if !self.positions.contains(&position) {
+            return false;
+        }
+        buf.fill(self, position);
+        true
+    }
+
+    /// Returns the [`Opening`] for the given `position` if it exists,
+    /// memoizing it for subsequent calls.
+    ///
+    /// Memoized openings are kept up to date by the tree: when a leaf is
+    /// mutated, only the levels of each opening that share the path of the
+    /// mutated leaf are invalidated, and only those are recomputed the next
+    /// time the opening is requested.
+    ///
+    /// Openings are kept in memory until their leaf is removed, or until
+    /// [`clear_openings`] is called.
+    ///
+    /// [`clear_openings`]: Tree::clear_openings
+    pub fn opening_cached(&self, position: u64) -> Option<Opening<T, H, A>>
+    where
+        T: Clone,
+    {
+        if !self.positions.contains(&position) {
+            return None;
+        }
+
+        self.openings.with_mut(|openings| {
+            let cached =
+                openings.entry(position).or_insert_with(|| CachedOpening {
+                    opening: Opening::new(self, position),
+                    stale_levels: 0,
+                });
+
+            if cached.stale_levels > 0 {
+                cached.opening.refresh(self, position, cached.stale_levels);
+                cached.stale_levels = 0;
+            }
+
+            Some(cached.opening.clone())
+        })
+    }
+
+    /// Drop all the openings memoized by [`opening_cached`].
+    ///
+    /// [`opening_cached`]: Tree::opening_cached
+    pub fn clear_openings(&mut self) {
+        self.openings.get_mut().clear();
+    }
+
+    /// Subscribe to the opening of the given `position`.
+    ///
+    /// The openings of subscribed positions are kept up to date by the tree,
+    /// and are returned by [`refreshed_openings`] whenever they change.
+    ///
+    /// [`refreshed_openings`]: Tree::refreshed_openings
+    pub fn subscribe(&mut self, position: u64) {
+        self.subscriptions.insert(position, true);
+    }
+
+    /// Unsubscribe from the opening of the given `position`, returning `true`
+    /// if there was a subscription.
+    pub fn unsubscribe(&mut self, position: u64) -> bool {
+        self.subscriptions.remove(&position).is_some()
+    }
+
+    /// Returns the openings of the subscribed positions that changed since
+    /// they were last returned, or since the subscription was made.
+    ///
+    /// This is meant to be called after each batch of mutations - e.g. after
+    /// processing a block - to obtain the refreshed proofs. Subscribed
+    /// positions with no leaf in the tree are skipped until a leaf is
+    /// inserted.
+    pub fn refreshed_openings(&mut self) -> Vec<(u64, Opening<T, H, A>)>
+    where
+        T: Clone,
+    {
+        let pending: Vec<u64> = self
+            .subscriptions
+            .iter()
+            .filter(|(position, pending)| {
+                **pending && self.positions.contains(position)
+            })
+            .map(|(position, _)| *position)
+            .collect();
+
+        let mut openings = Vec::with_capacity(pending.len());
+        for position in pending {
+            if let Some(opening) = self.opening_cached(position) {
+                openings.push((position, opening));
+            }
+            self.subscriptions.insert(position, false);
+        }
+
+        openings
+    }
+
+    /// Records the given `positions` as changed, if changes are tracked.
+    fn track_changed<'a, I>(&mut self, positions: I)
+    where
+        I: IntoIterator<Item = &'a u64>,
+    {
+        if let Some(changed) = &mut self.changed {
+            changed.extend(positions);
+        }
+    }
+
+    /// Marks all subscribed openings as changed.
+    fn mark_subscriptions(&mut self) {
+        self.subscriptions
+            .values_mut()
+            .for_each(|pending| *pending = true);
+    }
+
+    /// Invalidates the levels of the memoized openings that are affected by a
+    /// mutation of the leaves at the given sorted `positions`.
+    pub(crate) fn invalidate_openings(&mut self, positions: &[u64]) {
+        // every mutation changes the root, and therefore every opening
+        self.mark_subscriptions();
+        self.track_changed(positions);
+
+        self.openings.get_mut().retain(|&cached_position, cached| {
+            if positions.binary_search(&cached_position).is_ok() {
+                return false;
+            }
+
+            // the paths share the nodes above the height where they diverge,
+            // and the node where they diverge holds the mutated sibling
+            for &position in positions {
+                let mut height = 0;
+                while position / capacity(A as u64, H - height - 1)
+                    == cached_position / capacity(A as u64, H - height - 1)
+                {
+                    height += 1;
+                }
+
+                cached.stale_levels = cached.stale_levels.max(height + 1);
+            }
+            true
+        });
+    }
+
+    /// Select a leaf at random, with a probability proportional to its
+    /// weight, returning its position together with its opening.
+    ///
+    /// The tree is descended from the root, choosing each child with a
+    /// probability proportional to the weight of its subtree. Returns `None`
+    /// if the tree has no weight.
+    #[cfg(feature = "rand")]
+    pub fn sample_weighted<R>(
+        &self,
+        rng: &mut R,
+    ) -> Option<(u64, Opening<T, H, A>)>
+    where
+        T: Weighted + Clone,
+        R: RngCore,
+    {
+        let total = self.root.item().weight();
+        if total == 0 {
+            return None;
+        }
+
+        let target = random_below(|| rng.next_u64(), total);
+        let position = self.find_by_cumulative(target)?;
+
+        self.opening(position).map(|opening| (position, opening))
+    }
+
+    /// Returns the position of the leaf where the running sum of the weights,
+    /// taken in position order, crosses the given `target`.
+    ///
+    /// This is the first leaf whose weight, added to the weights of all the
+    /// leaves before it, is larger than `target`. The tree is descended using
+    /// the aggregated weights, meaning the search takes `O(H)` steps. Returns
+    /// `None` if `target` is not smaller than the weight of the tree.
+    pub fn find_by_cumulative(&self, mut target: u64) -> Option<u64>
+    where
+        T: Weighted,
+    {
+        let mut node = &self.root;
+        let mut position = 0;
+
+        for height in 0..H {
+            let child_cap = capacity(A as u64, H - height - 1);
+
+            let (index, child) =
+                node.children.iter().enumerate().find_map(|(i, child)| {
+                    let child = child.as_ref()?;
+                    let weight = child.item().weight();
+                    if target < weight {
+                        return Some((i, child));
+                    }
+                    target -= weight;
+                    None
+                })?;
+
+            position += index as u64 * child_cap;
+            node = child;
+        }
+
+        Some(position)
+    }
+
+    /// Returns `true` if the weight of the subtree at the given `height` and
+    /// `index` within that height is at least `threshold`.
+    ///
+    /// The weight is read from the aggregated item of the subtree, meaning no
+    /// leaves are enumerated. Since the aggregated items are committed to by
+    /// the root, the opening of any leaf in the subtree proves its weight: the
+    /// item of the subtree is in the branch, at level `height - 1`. Empty
+    /// subtrees have no weight.
+    ///
+    /// # Panics
+    /// If `height > H`, or the index is out of bounds for the height.
+    pub fn subtree_meets_threshold(
+        &self,
+        height: usize,
+        index: u64,
+        threshold: u64,
+    ) -> bool
+    where
+        T: Weighted,
+    {
+        assert!(height <= H, "height out of bounds: the height is {H}");
+        let level_cap = capacity(A as u64, height);
+        assert!(
+            index < level_cap,
+            "index out of bounds: \
+             the level capacity is {level_cap} but the index is {index}"
+        );
+
+        let weight = self
+            .root
+            .descendant(height, index)
+            .map_or(0, |node| node.item().weight());
+        weight >= threshold
+    }
+
     /// Returns a [`Walk`] through the tree, proceeding according to the
     /// `walker` function.
     ///
     /// A walk starts from the root of the tree, and "drills down" according to
     /// the output of the walker function. The function should return `true` or
     /// `false`, indicating whether the iterator should continue along the
-    /// tree's path.
-    pub fn walk<W>(&self, walker: W) -> Walk<T, W, H, A>
+    /// tree's path. Leaves are yielded together with their positions.
+    pub fn walk<W>(&self, walker: W) -> Walk<'_, T, W, H, A>
     where
         W: Fn(&T) -> bool,
     {
         Walk::new(self, walker)
     }
 
+    /// Returns a [`Walk`] through the tree, proceeding according to the
+    /// `walker` function, which is passed the height of each node together
+    /// with its item.
+    ///
+    /// This behaves like [`walk`], but allows the walker to tell the leaves -
+    /// at height `H` - apart from the subtrees - at heights `1..H` - e.g. when
+    /// their items are to be interpreted differently.
+    ///
+    /// [`walk`]: Tree::walk
+    pub fn walk_with_height<W>(
+        &self,
+        walker: W,
+    ) -> Walk<'_, T, WithHeight<W>, H, A>
+    where
+        W: Fn(&T, usize) -> bool,
+    {
+        Walk::new(self, WithHeight(walker))
+    }
+
+    /// Returns a [`Walk`] through the subtree at the given `height` and
+    /// `index`, proceeding according to the `walker` function.
+    ///
+    /// The walk starts from the root of the subtree instead of the root of the
+    /// tree, meaning only the leaves under that subtree are visited. If the
+    /// subtree is empty, the walk yields no items.
+    ///
+    /// # Panics
+    /// If `height >= H`, or if `index` is out of bounds for the given height.
+    pub fn walk_subtree<W>(
+        &self,
+        height: usize,
+        index: u64,
+        walker: W,
+    ) -> Walk<'_, T, W, H, A>
+    where
+        W: Fn(&T) -> bool,
+    {
+        assert!(
+            height < H,
+            "height out of bounds: \
+             the height of the tree is {H} but the height is {height}"
+        );
+
+        let level_cap = capacity(A as u64, height);
+        assert!(
+            index < level_cap,
+            "index out of bounds: \
+             the capacity is {level_cap} but the index is {index}"
+        );
+
+        Walk::from_node(
+            self.root.descendant(height, index),
+            height,
+            index,
+            walker,
+        )
+    }
+
+    /// Returns a [`SubtreeMut`] handle to the subtree at the given `height`
+    /// and `index` within that height, allowing for operations addressed
+    /// relative to it.
+    ///
+    /// The subtree holds the positions starting with the given `index` when
+    /// written in base `A` with `H` digits, i.e. the positions in
+    /// `index * subtree_capacity..(index + 1) * subtree_capacity`.
+    ///
+    /// # Panics
+    /// If `height >= H`, or if `index` is out of bounds for the given height.
+    pub fn subtree_mut(
+        &mut self,
+        height: usize,
+        index: u64,
+    ) -> SubtreeMut<'_, T, H, A> {
+        assert!(
+            height < H,
+            "height out of bounds: \
+             the height of the tree is {H} but the height is {height}"
+        );
+
+        let level_cap = capacity(A as u64, height);
+        assert!(
+            index < level_cap,
+            "index out of bounds: \
+             the capacity is {level_cap} but the index is {index}"
+        );
+
+        SubtreeMut::new(self, height, index)
+    }
+
+    /// Returns a [`WalkCloned`] through the tree, proceeding according to the
+    /// `walker` function.
+    ///
+    /// This behaves like [`walk`], but yields clones of the items instead of
+    /// references into the tree, allowing them to outlive the tree.
+    ///
+    /// [`walk`]: Tree::walk
+    pub fn walk_cloned<W>(&self, walker: W) -> WalkCloned<'_, T, W, H, A>
+    where
+        T: Clone,
+        W: Fn(&T) -> bool,
+    {
+        WalkCloned::new(self, walker)
+    }
+
     /// Get the root of the merkle tree.
-    pub fn root(&self) -> Ref<T> {
-        self.root.item()
+    ///
+    /// Mutations only mark the items on the paths they touch as stale, while
+    /// the items of their siblings stay cached. Computing the root therefore
+    /// aggregates at most `H` nodes per path mutated since it was last
+    /// computed, and mutations sharing a path share the cost.
+    pub fn root(&self) -> Ref<'_, T> {
+        trace::span!("root", nodes = self.root.stale_nodes());
+        self.root.item_with(0, self.cache_policy)
+    }
+
+    /// Returns the aggregated item of the subtree at the given `height` and
+    /// `index` within that height, or `None` if the subtree is empty.
+    ///
+    /// This is the root of the subtree, with the subtree at height zero being
+    /// the whole tree and those at height `H` its leaves. The items are
+    /// computed, and kept according to the [`CachePolicy`] of the tree, just
+    /// as for [`root`].
+    ///
+    /// # Panics
+    /// If `height > H`, or the index is out of bounds for the height.
+    ///
+    /// [`root`]: Tree::root
+    pub fn subtree_root(
+        &self,
+        height: usize,
+        index: u64,
+    ) -> Option<Ref<'_, T>> {
+        assert!(height <= H, "height out of bounds: the height is {H}");
+        let level_cap = capacity(A as u64, height);
+        assert!(
+            index < level_cap,
+            "index out of bounds: \
+             the level capacity is {level_cap} but the index is {index}"
+        );
+
+        let node = self.root.descendant(height, index)?;
+        Some(node.item_with(height, self.cache_policy))
+    }
+
+    /// Returns the [`CachePolicy`] of the tree.
+    #[must_use]
+    pub const fn cache_policy(&self) -> CachePolicy {
+        self.cache_policy
+    }
+
+    /// Set the [`CachePolicy`] of the tree, discarding any items it doesn't
+    /// keep.
+    ///
+    /// The policy is applied when computing the root. Other operations, such
+    /// as building openings or walking the tree, may compute and keep items
+    /// regardless of it - these can be discarded with [`evict_items`].
+    ///
+    /// [`evict_items`]: Tree::evict_items
+    pub fn set_cache_policy(&mut self, policy: CachePolicy) {
+        self.cache_policy = policy;
+        self.evict_items();
+    }
+
+    /// Discard the computed items of the inner nodes that the
+    /// [`CachePolicy`] of the tree doesn't keep.
+    ///
+    /// This visits every node in the tree. Items that are still borrowed -
+    /// e.g. through a [`Ref`] returned by [`subtree_root`] - are kept.
+    ///
+    /// [`subtree_root`]: Tree::subtree_root
+    pub fn evict_items(&self) {
+        self.root.evict(0, self.cache_policy);
     }
 
     /// Returns the root of the smallest sub-tree that holds all the leaves.
-    pub fn smallest_subtree(&self) -> (Ref<T>, usize) {
+    pub fn smallest_subtree(&self) -> (Ref<'_, T>, usize) {
         let mut smallest_node = &self.root;
         let mut height = H;
         loop {
@@ -137,6 +1001,268 @@ where
         self.positions.contains(&position)
     }
 
+    /// Returns true if the tree contains a leaf at each of the given
+    /// `positions`.
+    ///
+    /// This is answered from the index of occupied positions, without
+    /// touching the nodes of the tree.
+    pub fn contains_all<I>(&self, positions: I) -> bool
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        positions
+            .into_iter()
+            .all(|position| self.positions.contains(&position))
+    }
+
+    /// Returns a [`Bitmap`] of whether each position in the given `range`
+    /// holds a leaf, with the `i`th bit being for the position
+    /// `range.start + i`.
+    ///
+    /// This is answered from the index of occupied positions, visiting only
+    /// those within the range, without touching the nodes of the tree. The
+    /// range is clamped to the capacity of the tree, meaning the bitmap holds
+    /// no bits for the positions past it.
+    ///
+    /// # Panics
+    /// If the length of the clamped range doesn't fit in a `usize`.
+    #[must_use]
+    pub fn membership_bitmap(&self, range: Range<u64>) -> Bitmap {
+        let range = range.start..range.end.min(self.capacity());
+        let len = range.end.saturating_sub(range.start);
+        let Ok(len) = usize::try_from(len) else {
+            panic!("range too long: the length is {len}");
+        };
+
+        let mut bitmap = Bitmap::new(len);
+        // inverted ranges are empty, and must not be used to index the set
+        if len == 0 {
+            return bitmap;
+        }
+
+        for position in self.positions.range(range.clone()) {
+            // the offset is smaller than the length, so it fits in a usize
+            #[allow(clippy::cast_possible_truncation)]
+            let offset = (position - range.start) as usize;
+            bitmap.set(offset);
+        }
+
+        bitmap
+    }
+
+    /// Returns a reference to the item of the leaf at the given `position`,
+    /// if it exists.
+    pub fn leaf(&self, position: u64) -> Option<Ref<'_, T>> {
+        if !self.positions.contains(&position) {
+            return None;
+        }
+        self.root.descendant(H, position).map(Node::item)
+    }
+
+    /// Returns a mutable reference to the item of the leaf at the given
+    /// `position`, if it exists.
+    ///
+    /// The items of the ancestors of the leaf are marked as stale before the
+    /// reference is returned, so the root reflects any change made through it
+    /// the next time it is computed. Cached openings of the position and its
+    /// neighbours are invalidated accordingly.
+    pub fn leaf_mut(&mut self, position: u64) -> Option<&mut T> {
+        if !self.positions.contains(&position) {
+            return None;
+        }
+
+        trace::span!("leaf_mut", position, nodes = H + 1);
+
+        self.record_leaf(position);
+        self.generation = self.generation.wrapping_add(1);
+        self.invalidate_openings(&[position]);
+        self.root.leaf_mut(0, position)
+    }
+
+    /// Update the item of the leaf at the given `position` in place with the
+    /// given function, returning `false` if there is no leaf at the position.
+    ///
+    /// The tree is descended once, and only the items of the ancestors of the
+    /// leaf are recomputed the next time the root is computed. Should `f`
+    /// panic, the leaf is left in the tree in whatever state `f` left it.
+    pub fn update<F>(&mut self, position: u64, f: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        match self.leaf_mut(position) {
+            Some(leaf) => {
+                f(leaf);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of leaves held by each occupied subtree at the given
+    /// `depth`, as pairs of the subtree index and its leaf count.
+    ///
+    /// Subtrees are listed in ascending order of their index, and empty
+    /// subtrees are omitted. The counts are computed from the positions
+    /// index, so no nodes are visited.
+    ///
+    /// # Panics
+    /// If `depth > H`.
+    #[must_use]
+    pub fn occupancy(&self, depth: usize) -> Vec<(u64, u64)> {
+        assert!(
+            depth <= H,
+            "depth out of bounds: the height is {H} but the depth is {depth}"
+        );
+
+        let subtree_cap = capacity(A as u64, H - depth);
+
+        let mut histogram: Vec<(u64, u64)> = Vec::new();
+        for position in &self.positions {
+            let index = position / subtree_cap;
+            match histogram.last_mut() {
+                Some((last, count)) if *last == index => *count += 1,
+                _ => histogram.push((index, 1)),
+            }
+        }
+
+        histogram
+    }
+
+    /// Returns an iterator over the leaves of the tree, in the order of their
+    /// positions.
+    #[must_use]
+    pub fn leaves(&self) -> Leaves<'_, T, H, A> {
+        Leaves::new(self, self.positions.len())
+    }
+
+    /// Returns an iterator over the occupied nodes of the tree in
+    /// breadth-first order, together with their height and index.
+    ///
+    /// See [`BreadthFirst`] for details.
+    #[must_use]
+    pub fn breadth_first(&self) -> BreadthFirst<'_, T, H, A> {
+        BreadthFirst::new(self)
+    }
+
+    /// Returns an iterator over the positions of the leaves in the tree, in
+    /// ascending order.
+    #[must_use]
+    pub fn positions(&self) -> Positions<'_> {
+        Positions {
+            inner: self.positions.iter(),
+        }
+    }
+
+    /// Returns the smallest occupied position in the tree, or `None` if the
+    /// tree is empty.
+    #[must_use]
+    pub fn min_position(&self) -> Option<u64> {
+        self.positions.first().copied()
+    }
+
+    /// Returns the largest occupied position in the tree, or `None` if the
+    /// tree is empty.
+    #[must_use]
+    pub fn max_position(&self) -> Option<u64> {
+        self.positions.last().copied()
+    }
+
+    /// Returns a [`PositionCursor`] placed just before the given `position`,
+    /// moving over the occupied positions of the tree in either direction.
+    ///
+    /// The position need not be occupied, nor within the capacity of the
+    /// tree.
+    #[must_use]
+    pub fn cursor_from(&self, position: u64) -> PositionCursor<'_> {
+        PositionCursor {
+            positions: &self.positions,
+            gap: position,
+        }
+    }
+
+    /// Returns the positions occupied in both this tree and the `other`, in
+    /// ascending order.
+    ///
+    /// Both trees are descended simultaneously, and subtrees that are empty
+    /// in either of them are not descended into.
+    #[must_use]
+    pub fn positions_intersection(&self, other: &Self) -> Vec<u64> {
+        let mut positions = Vec::new();
+        self.compare_occupancy(
+            &self.root,
+            Some(&other.root),
+            0,
+            0,
+            true,
+            &mut positions,
+        );
+        positions
+    }
+
+    /// Returns the positions occupied in this tree but not in the `other`, in
+    /// ascending order.
+    ///
+    /// Both trees are descended simultaneously. Subtrees that are empty in
+    /// the `other` tree are not descended into, with all their positions
+    /// taken directly from the position index of this tree.
+    #[must_use]
+    pub fn positions_difference(&self, other: &Self) -> Vec<u64> {
+        let mut positions = Vec::new();
+        self.compare_occupancy(
+            &self.root,
+            Some(&other.root),
+            0,
+            0,
+            false,
+            &mut positions,
+        );
+        positions
+    }
+
+    /// Pushes the positions of the subtree of `ours` at the given `height`
+    /// and starting at the `start` position that are either shared with, or
+    /// missing from, `theirs`.
+    fn compare_occupancy(
+        &self,
+        ours: &Node<T, H, A>,
+        theirs: Option<&Node<T, H, A>>,
+        height: usize,
+        start: u64,
+        shared: bool,
+        positions: &mut Vec<u64>,
+    ) {
+        let Some(theirs) = theirs else {
+            if !shared {
+                let end = start + capacity(A as u64, H - height);
+                positions.extend(self.positions.range(start..end));
+            }
+            return;
+        };
+
+        if height == H {
+            if shared {
+                positions.push(start);
+            }
+            return;
+        }
+
+        let child_cap = capacity(A as u64, H - height - 1);
+        for (i, (ours, theirs)) in
+            ours.children.iter().zip(&theirs.children).enumerate()
+        {
+            if let Some(ours) = ours {
+                self.compare_occupancy(
+                    ours,
+                    theirs.as_deref(),
+                    height + 1,
+                    start + i as u64 * child_cap,
+                    shared,
+                    positions,
+                );
+            }
+        }
+    }
+
     /// Returns the number of elements that have been inserted into the tree.
     #[must_use]
     pub fn len(&self) -> u64 {
@@ -150,16 +1276,350 @@ where
     }
 
     /// The maximum number of leaves in the tree, i.e. its capacity.
+    ///
+    /// Fails to compile under the same conditions as [`new`], meaning the
+    /// bounds checks of the tree never rely on an overflowed capacity.
+    ///
+    /// [`new`]: Tree::new
     #[must_use]
     pub const fn capacity(&self) -> u64 {
+        #[allow(clippy::let_unit_value)]
+        let () = Node::<T, H, A>::VALID_PARAMS;
+
         capacity(A as u64, H)
     }
 }
 
+/// Returns the number of distinct nodes on the paths to the given sorted
+/// `positions`, including the root.
+#[cfg(feature = "tracing")]
+fn path_nodes<const H: usize, const A: usize>(positions: &[u64]) -> usize {
+    (0..=H)
+        .map(|height| {
+            let cap = capacity(A as u64, H - height);
+            let mut nodes = 0;
+            let mut last = None;
+            for position in positions {
+                let index = Some(position / cap);
+                if index != last {
+                    nodes += 1;
+                    last = index;
+                }
+            }
+            nodes
+        })
+        .sum()
+}
+
+/// The error returned when [`Tree::merge`] or [`Tree::merge_with`] refuses to
+/// merge two trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergeError {
+    /// Both trees hold a leaf at the given position.
+    Overlap {
+        /// The first position occupied in both trees.
+        position: u64,
+    },
+    /// The trees have different fingerprints.
+    Fingerprint(crate::FingerprintError),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overlap { position } => {
+                write!(f, "both trees hold a leaf at position {position}")
+            }
+            Self::Fingerprint(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MergeError {}
+
+/// The error returned by [`Tree::insert_unique`] when the position is already
+/// occupied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OccupiedError<T> {
+    /// The occupied position.
+    pub position: u64,
+    /// The item stored at the position.
+    pub existing: T,
+    /// The item that was not inserted.
+    pub item: T,
+}
+
+impl<T> fmt::Display for OccupiedError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "position {} is already occupied", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for OccupiedError<T> where T: fmt::Debug {}
+
+/// Iterator over the positions of the leaves in a tree, in ascending order.
+///
+/// This is produced by [`Tree::positions`].
+#[derive(Debug, Clone)]
+pub struct Positions<'a> {
+    inner: btree_set::Iter<'a, u64>,
+}
+
+impl Iterator for Positions<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().copied()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Positions<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().copied()
+    }
+}
+
+impl ExactSizeIterator for Positions<'_> {}
+
+impl FusedIterator for Positions<'_> {}
+
+/// A cursor over the positions of the leaves in a tree, moving in either
+/// direction.
+///
+/// The cursor sits between two positions. Iterating it yields the occupied
+/// positions after it in ascending order, and [`prev`] yields the ones before
+/// it in descending order, each moving the cursor over the position yielded.
+///
+/// This is produced by [`Tree::cursor_from`].
+///
+/// [`prev`]: PositionCursor::prev
+#[derive(Debug, Clone)]
+pub struct PositionCursor<'a> {
+    positions: &'a BTreeSet<u64>,
+    // The cursor sits just before this position. Occupied positions are
+    // always smaller than `u64::MAX`, so the cursor can always be placed
+    // after the last of them.
+    gap: u64,
+}
+
+impl PositionCursor<'_> {
+    /// Returns the position the cursor sits just before.
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.gap
+    }
+
+    /// Returns the next occupied position after the cursor, without moving
+    /// it.
+    #[must_use]
+    pub fn peek_next(&self) -> Option<u64> {
+        self.positions.range(self.gap..).next().copied()
+    }
+
+    /// Returns the previous occupied position before the cursor, without
+    /// moving it.
+    #[must_use]
+    pub fn peek_prev(&self) -> Option<u64> {
+        self.positions.range(..self.gap).next_back().copied()
+    }
+
+    /// Moves the cursor back over the previous occupied position, returning
+    /// it.
+    pub fn prev(&mut self) -> Option<u64> {
+        let position = self.peek_prev()?;
+        self.gap = position;
+        Some(position)
+    }
+}
+
+impl Iterator for PositionCursor<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.peek_next()?;
+        self.gap = position + 1;
+        Some(position)
+    }
+}
+
+impl FusedIterator for PositionCursor<'_> {}
+
+#[cfg(feature = "rkyv-impl")]
+impl<T, const H: usize, const A: usize> Tree<T, H, A>
+where
+    T: rkyv::Archive,
+{
+    /// Returns a reference to the tree archived in the given `bytes`, skipping
+    /// any validation.
+    ///
+    /// Validating a large archive with [`rkyv::check_archived_root`] requires
+    /// walking every node in the tree, which may take a long time. This
+    /// function is meant to be used when loading archives the caller produced
+    /// itself, and trusts to not have been tampered with - e.g. a snapshot
+    /// written to local disk.
+    ///
+    /// # Safety
+    /// The `bytes` must contain a valid archived tree with the same `T`, `H`,
+    /// and `A`, produced by serializing with the same `rkyv` size feature. The
+    /// caller is responsible for ensuring this, by validating the archive at
+    /// least once or authenticating its source.
+    #[must_use]
+    pub unsafe fn archived_unchecked(bytes: &[u8]) -> &ArchivedTree<T, H, A> {
+        trace::span!("archived_unchecked", bytes = bytes.len());
+        rkyv::archived_root::<Self>(bytes)
+    }
+
+    /// Read the root of the tree archived in the given `bytes`, validating
+    /// and deserializing only the root item and leaving the rest of the
+    /// archive untouched.
+    ///
+    /// This is meant for quickly deciding whether to process a snapshot
+    /// received from an untrusted source - e.g. when validating gossip -
+    /// before paying the cost of validating the whole archive.
+    ///
+    /// Returns `None` if the `bytes` are too short or misaligned to contain
+    /// an archived tree, if the archived root is malformed, or if the root
+    /// was not computed when the tree was archived. Calling [`Tree::root`]
+    /// before archiving ensures it is present.
+    ///
+    /// Note that the root is read as it was committed to by the archiver, and
+    /// is not checked against the leaves of the archive.
+    pub fn read_root_only(bytes: &[u8]) -> Option<T>
+    where
+        for<'a> T::Archived: CheckBytes<DefaultValidator<'a>>,
+        T::Archived: rkyv::Deserialize<T, rkyv::Infallible>,
+    {
+        use rkyv::Deserialize;
+
+        trace::span!("read_root_only", bytes = bytes.len());
+
+        // archives have their root object at the end of the buffer
+        let pos = bytes
+            .len()
+            .checked_sub(core::mem::size_of::<ArchivedTree<T, H, A>>())?;
+        let tree_ptr = bytes[pos..].as_ptr();
+        if tree_ptr.align_offset(core::mem::align_of::<ArchivedTree<T, H, A>>())
+            != 0
+        {
+            return None;
+        }
+        let tree_ptr = tree_ptr.cast::<ArchivedTree<T, H, A>>();
+
+        let mut validator = DefaultValidator::new(bytes);
+
+        // SAFETY: the pointer is in bounds of the buffer and aligned, and
+        // the root item is validated before being referenced.
+        let item = unsafe {
+            let root_ptr = core::ptr::addr_of!((*tree_ptr).root);
+            let item_ptr = rkyv::Archived::<Node<T, H, A>>::item_ptr(root_ptr);
+            CheckBytes::check_bytes(item_ptr, &mut validator).ok()?
+        };
+
+        match item.as_ref()?.deserialize(&mut rkyv::Infallible) {
+            Ok(root) => Some(root),
+            Err(infallible) => match infallible {},
+        }
+    }
+}
+
+#[cfg(feature = "rkyv-impl")]
+impl<T, const H: usize, const A: usize> ArchivedTree<T, H, A>
+where
+    T: rkyv::Archive,
+{
+    /// Returns the [`Fingerprint`] of the archived tree.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        use rkyv::Deserialize;
+
+        match self.fingerprint.deserialize(&mut rkyv::Infallible) {
+            Ok(fingerprint) => fingerprint,
+            Err(infallible) => match infallible {},
+        }
+    }
+
+    /// Check the archived tree has the `expected` [`Fingerprint`], ensuring
+    /// it is compatible with openings produced by the tree it was archived
+    /// from.
+    ///
+    /// # Errors
+    /// Returns a [`FingerprintError`] describing the first mismatching
+    /// parameter.
+    ///
+    /// [`FingerprintError`]: crate::FingerprintError
+    pub fn check_fingerprint(
+        &self,
+        expected: &Fingerprint,
+    ) -> Result<(), crate::FingerprintError> {
+        self.fingerprint().check(expected)
+    }
+
+    /// Returns true if the archived tree contains a leaf at the given
+    /// `position`.
+    pub fn contains(&self, position: u64) -> bool {
+        self.positions.contains_key(&position)
+    }
+
+    /// Returns the number of leaves in the archived tree.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.positions.len() as u64
+    }
+
+    /// Returns `true` if the archived tree is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "rkyv-impl")]
+impl<T, const H: usize, const A: usize> ArchivedTree<T, H, A>
+where
+    T: Aggregate<A> + rkyv::Archive,
+    T::Archived: rkyv::Deserialize<T, rkyv::Infallible>,
+{
+    /// Returns the root of the archived tree, without deserializing the tree.
+    ///
+    /// The root is deserialized if it was computed when the tree was
+    /// archived, and computed from the archived nodes otherwise. Only the
+    /// items that weren't archived are computed, meaning calling
+    /// [`Tree::root`] before archiving makes this constant time.
+    #[must_use]
+    pub fn root(&self) -> T {
+        self.root.to_item()
+    }
+
+    /// Returns the opening for the given `position` in the archived tree, if
+    /// it exists, without deserializing the tree.
+    ///
+    /// Only the items on the path to the leaf are deserialized, and the ones
+    /// that weren't archived are computed as [`root`] does.
+    ///
+    /// [`root`]: ArchivedTree::root
+    pub fn opening(&self, position: u64) -> Option<Opening<T, H, A>>
+    where
+        T: Clone,
+    {
+        if !self.contains(position) {
+            return None;
+        }
+        Some(Opening::from_archived(self, position))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::{SequentialAllocator, TreeBuilder};
+
     impl Aggregate<A> for u8 {
         const EMPTY_SUBTREE: Self = 0;
 
@@ -168,6 +1628,20 @@ mod tests {
         }
     }
 
+    impl Aggregate<A> for u64 {
+        const EMPTY_SUBTREE: Self = 0;
+
+        fn aggregate(items: [&Self; A]) -> Self {
+            items.into_iter().sum()
+        }
+    }
+
+    impl Weighted for u8 {
+        fn weight(&self) -> u64 {
+            u64::from(*self)
+        }
+    }
+
     const H: usize = 3;
     const A: usize = 2;
 
@@ -188,6 +1662,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tree_clone() {
+        let mut tree = SumTree::new();
+        tree.insert(1, 1);
+        tree.insert(2, 2);
+        tree.insert(6, 6);
+        assert_eq!(*tree.root(), 9);
+
+        let mut clone = tree.clone();
+        clone.insert(2, 20);
+        clone.remove(6);
+        *clone.leaf_mut(1).unwrap() = 10;
+        tree.insert(7, 7);
+
+        assert_eq!(*tree.root(), 16);
+        assert_eq!(*clone.root(), 30);
+        assert_eq!(tree.positions().collect::<Vec<_>>(), [1, 2, 6, 7]);
+        assert_eq!(clone.positions().collect::<Vec<_>>(), [1, 2]);
+
+        // only the nodes mutated since the clone are copied
+        #[cfg(feature = "shared")]
+        {
+            let mut clone = tree.clone();
+            clone.insert(0, 1);
+
+            let child = |tree: &SumTree, i: usize| {
+                tree.root.children[i].as_deref().map(core::ptr::from_ref)
+            };
+            assert_ne!(child(&tree, 0), child(&clone, 0));
+            assert_eq!(child(&tree, 1), child(&clone, 1));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn tree_sync() {
+        let mut tree = SumTree::new();
+        tree.insert(1, 1);
+        tree.insert(4, 4);
+        tree.insert(6, 6);
+
+        // items are evicted and recomputed while other threads read them
+        tree.set_cache_policy(CachePolicy::Never);
+        let tree = &tree;
+
+        std::thread::scope(|scope| {
+            for position in [1, 4, 6] {
+                scope.spawn(move || {
+                    for _ in 0..100 {
+                        assert_eq!(*tree.root(), 11);
+                        let opening: Opening<u8, H, A> =
+                            tree.opening_cached(position).unwrap();
+                        assert!(opening.verify(*tree.leaf(position).unwrap()));
+                        tree.evict_items();
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn tree_map() {
+        let mut tree = SumTree::new();
+        tree.insert(1, 1);
+        tree.insert(2, 2);
+        tree.insert(6, 6);
+        tree.set_cache_policy(CachePolicy::Never);
+
+        let mapped: Tree<u64, H, A> = tree.map(|item| u64::from(*item) * 100);
+        assert_eq!(*mapped.root(), 900);
+        assert_eq!(mapped.cache_policy(), CachePolicy::Never);
+        assert_eq!(
+            mapped.positions().collect::<Vec<_>>(),
+            tree.positions().collect::<Vec<_>>()
+        );
+
+        let mut expected = Tree::<u64, H, A>::new();
+        expected.insert(1, 100u64);
+        expected.insert(2, 200u64);
+        expected.insert(6, 600u64);
+        assert_eq!(*mapped.root(), *expected.root());
+        assert_eq!(mapped.opening(6), expected.opening(6));
+    }
+
     #[test]
     fn tree_deletion() {
         let mut tree = SumTree::new();
@@ -216,6 +1774,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tree_deletion_batch() {
+        let mut tree = SumTree::new();
+        let mut expected = SumTree::new();
+
+        for position in 0..8 {
+            tree.insert(position, 1 << position);
+            expected.insert(position, 1 << position);
+        }
+
+        let cached = tree.opening_cached(7).unwrap();
+
+        let removed = tree.remove_batch([6, 0, 1, 6, 3, 42]);
+        assert_eq!(removed, [(0, 1), (1, 2), (3, 8), (6, 64)]);
+
+        for position in [0, 1, 3, 6] {
+            expected.remove(position);
+        }
+
+        assert_eq!(tree.len(), 4);
+        assert!(tree.positions().eq(expected.positions()));
+        assert_eq!(*tree.root(), *expected.root());
+
+        let opening = tree.opening_cached(7).unwrap();
+        assert_ne!(opening, cached);
+        assert_eq!(opening, expected.opening(7).unwrap());
+
+        assert!(tree.remove_batch([0, 1]).is_empty());
+
+        let removed = tree.remove_batch(0..8);
+        assert_eq!(removed, [(2, 4), (4, 16), (5, 32), (7, 128)]);
+        assert!(tree.is_empty());
+        assert_eq!(*tree.root(), u8::EMPTY_SUBTREE);
+    }
+
     #[test]
     #[should_panic(
         expected = "index out of bounds: the capacity is 8 but the index is 8"
@@ -225,6 +1818,549 @@ mod tests {
         tree.insert(tree.capacity(), 42);
     }
 
+    #[test]
+    fn tree_try_insert_remove() {
+        let mut tree = SumTree::new();
+
+        assert_eq!(tree.try_insert(3, 2), Ok(()));
+        assert_eq!(
+            tree.try_insert(8, 1),
+            Err(Error::OutOfBounds {
+                position: 8,
+                capacity: 8
+            })
+        );
+        assert_eq!(tree.len(), 1);
+
+        assert_eq!(tree.try_remove(2), Ok(None));
+        assert_eq!(
+            tree.try_remove(9),
+            Err(Error::OutOfBounds {
+                position: 9,
+                capacity: 8
+            })
+        );
+        assert_eq!(tree.try_remove(3), Ok(Some(2)));
+        assert!(tree.is_empty());
+
+        // an occupied position without a leaf is reported, not panicked on
+        tree.positions.insert(5);
+        assert_eq!(
+            tree.try_remove(5),
+            Err(Error::Inconsistent { position: 5 })
+        );
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn tree_insertion_unique() {
+        let mut tree = SumTree::new();
+
+        assert_eq!(tree.insert_unique(5, 42), Ok(()));
+        assert_eq!(tree.insert_unique(6, 1), Ok(()));
+
+        let generation = tree.generation();
+        assert_eq!(
+            tree.insert_unique(5, 7),
+            Err(OccupiedError {
+                position: 5,
+                existing: 42,
+                item: 7,
+            })
+        );
+
+        assert_eq!(tree.generation(), generation);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(*tree.root(), 43);
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn tree_touched_nodes() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(1, 1);
+        tree.insert(7, 1);
+
+        // the root, and the nodes above positions 0-3, 0-1, 4-7, and 6-7
+        assert_eq!(tree.root.stale_nodes(), 5);
+        let _ = tree.root();
+        assert_eq!(tree.root.stale_nodes(), 0);
+
+        tree.insert(5, 1);
+        assert_eq!(tree.root.stale_nodes(), 3);
+
+        assert_eq!(path_nodes::<H, A>(&[0, 1, 7]), 8);
+        assert_eq!(path_nodes::<H, A>(&[]), 0);
+    }
+
+    #[test]
+    fn tree_insertion_allocated() {
+        let mut tree = SumTree::new();
+        let mut allocator = SequentialAllocator::starting_at(6);
+
+        assert_eq!(tree.insert_alloc(&mut allocator, 1), Some(6));
+        assert_eq!(tree.insert_alloc(&mut allocator, 2), Some(7));
+        assert_eq!(tree.insert_alloc(&mut allocator, 4), None);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(*tree.root(), 3);
+    }
+
+    #[test]
+    fn tree_merge() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(1, 2);
+        let _ = tree.root();
+
+        let mut shard = SumTree::new();
+        shard.insert(5, 3);
+        shard.insert(7, 4);
+
+        let expected = TreeBuilder::new()
+            .with_leaves([(0, 1), (1, 2), (5, 3), (7, 4)])
+            .build();
+
+        assert_eq!(tree.merge(shard), Ok(()));
+        assert_eq!(*tree.root(), 10);
+        assert!(tree.positions().eq(expected.positions()));
+        assert_eq!(tree.opening(5), expected.opening(5));
+
+        let mut overlapping = SumTree::new();
+        overlapping.insert(3, 1);
+        overlapping.insert(7, 1);
+        assert_eq!(
+            tree.merge(overlapping),
+            Err(MergeError::Overlap { position: 7 })
+        );
+
+        assert_eq!(
+            tree.merge(SumTree::with_backend(1)),
+            Err(MergeError::Fingerprint(crate::FingerprintError::Backend {
+                expected: 0,
+                found: 1
+            }))
+        );
+        assert!(tree.positions().eq(expected.positions()));
+        assert_eq!(*tree.root(), 10);
+    }
+
+    #[test]
+    fn tree_merge_with() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(1, 2);
+        tree.insert(5, 3);
+
+        let mut other = SumTree::new();
+        other.insert(0, 1);
+        other.insert(1, 4);
+        other.insert(6, 5);
+
+        let resolved = core::cell::Cell::new(0);
+        tree.merge_with(other, |ours, theirs| {
+            resolved.set(resolved.get() + 1);
+            ours.max(theirs)
+        })
+        .expect("the trees have the same fingerprint");
+
+        let mut expected = SumTree::new();
+        expected.insert(0, 1);
+        expected.insert(1, 4);
+        expected.insert(5, 3);
+        expected.insert(6, 5);
+
+        assert_eq!(resolved.get(), 1, "Only position 1 is in conflict");
+        assert_eq!(tree.len(), expected.len());
+        assert_eq!(*tree.root(), *expected.root());
+        assert_eq!(tree.opening(1), expected.opening(1));
+    }
+
+    #[test]
+    fn tree_merge_with_colliding_sums() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 2);
+
+        let mut other = SumTree::new();
+        other.insert(1, 2);
+
+        // both subtrees aggregate to the same sum, but hold different leaves
+        tree.merge_with(other, |ours, _| ours)
+            .expect("the trees have the same fingerprint");
+
+        let mut expected = SumTree::new();
+        expected.insert(0, 2);
+        expected.insert(1, 2);
+
+        assert_eq!(*tree.root(), *expected.root());
+        assert_eq!(tree.opening(1), expected.opening(1));
+        assert_eq!(tree.remove(1), Some(2));
+        assert_eq!(*tree.root(), 2);
+    }
+
+    #[test]
+    fn tree_merge_with_fingerprint() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        let other = SumTree::with_backend(1);
+
+        assert!(matches!(
+            tree.merge_with(other, |ours, _| ours),
+            Err(MergeError::Fingerprint(_))
+        ));
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn tree_opening_cached() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(3, 2);
+        tree.insert(4, 3);
+
+        for pos in [0, 3, 4] {
+            assert_eq!(tree.opening_cached(pos), tree.opening(pos));
+        }
+
+        tree.insert(1, 4);
+        tree.insert(3, 5);
+        tree.remove(4);
+        tree.insert(7, 6);
+
+        assert_eq!(tree.opening_cached(4), None);
+        for pos in [0, 1, 3, 7] {
+            assert_eq!(tree.opening_cached(pos), tree.opening(pos));
+        }
+    }
+
+    #[test]
+    fn tree_subscriptions() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(5, 2);
+
+        tree.subscribe(0);
+        tree.subscribe(3);
+
+        let refreshed = tree.refreshed_openings();
+        assert_eq!(refreshed.len(), 1);
+        assert_eq!(refreshed[0], (0, tree.opening(0).unwrap()));
+        assert!(tree.refreshed_openings().is_empty());
+
+        tree.insert(3, 4);
+
+        let refreshed = tree.refreshed_openings();
+        assert_eq!(refreshed.len(), 2);
+        assert_eq!(refreshed[0], (0, tree.opening(0).unwrap()));
+        assert_eq!(refreshed[1], (3, tree.opening(3).unwrap()));
+
+        assert!(tree.unsubscribe(3));
+        tree.remove(5);
+
+        let refreshed = tree.refreshed_openings();
+        assert_eq!(refreshed, [(0, tree.opening(0).unwrap())]);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn tree_sample_weighted() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(0xbeef);
+
+        let mut tree = SumTree::new();
+        assert!(tree.sample_weighted(&mut rng).is_none());
+
+        tree.insert(1, 1);
+        tree.insert(4, 0);
+        tree.insert(6, 3);
+
+        let mut counts = BTreeMap::<u64, u32>::new();
+        for _ in 0..4000 {
+            let (position, opening) = tree.sample_weighted(&mut rng).unwrap();
+            assert_eq!(Some(opening), tree.opening(position));
+            *counts.entry(position).or_default() += 1;
+        }
+
+        assert_eq!(counts.len(), 2, "zero weights are never chosen");
+        assert!(counts[&1] > 800 && counts[&1] < 1200);
+        assert!(counts[&6] > 2800 && counts[&6] < 3200);
+    }
+
+    #[test]
+    fn tree_find_by_cumulative() {
+        let mut tree = SumTree::new();
+        assert_eq!(tree.find_by_cumulative(0), None);
+
+        tree.insert(1, 2);
+        tree.insert(2, 0);
+        tree.insert(3, 1);
+        tree.insert(7, 3);
+
+        let positions: Vec<_> = (0..7)
+            .map(|target| tree.find_by_cumulative(target))
+            .collect();
+        assert_eq!(
+            positions,
+            [Some(1), Some(1), Some(3), Some(7), Some(7), Some(7), None]
+        );
+    }
+
+    #[test]
+    fn tree_subtree_meets_threshold() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 3);
+        tree.insert(1, 4);
+        tree.insert(6, 5);
+
+        assert!(tree.subtree_meets_threshold(0, 0, 12));
+        assert!(!tree.subtree_meets_threshold(0, 0, 13));
+        assert!(tree.subtree_meets_threshold(1, 0, 7));
+        assert!(!tree.subtree_meets_threshold(1, 1, 6));
+        assert!(tree.subtree_meets_threshold(2, 3, 5));
+        assert!(tree.subtree_meets_threshold(3, 1, 4));
+
+        // empty subtrees only meet a zero threshold
+        assert!(!tree.subtree_meets_threshold(2, 2, 1));
+        assert!(tree.subtree_meets_threshold(2, 2, 0));
+    }
+
+    #[test]
+    fn tree_leaf() {
+        let mut tree = SumTree::new();
+        tree.insert(2, 5);
+        tree.insert(7, 3);
+
+        assert_eq!(tree.leaf(2).as_deref(), Some(&5));
+        assert_eq!(tree.leaf(7).as_deref(), Some(&3));
+        assert!(tree.leaf(3).is_none());
+        assert!(tree.leaf(10).is_none());
+        assert_eq!(*tree.root(), 8);
+
+        let generation = tree.generation();
+        *tree.leaf_mut(2).unwrap() = 1;
+        assert_eq!(tree.generation(), generation + 1);
+        assert_eq!(tree.leaf(2).as_deref(), Some(&1));
+        assert_eq!(*tree.root(), 4);
+
+        assert!(tree.leaf_mut(3).is_none());
+        assert_eq!(tree.generation(), generation + 1);
+
+        let mut expected = SumTree::new();
+        expected.insert(2, 1);
+        expected.insert(7, 3);
+        assert_eq!(tree.opening(2), expected.opening(2));
+    }
+
+    #[test]
+    fn tree_update() {
+        let mut tree = SumTree::new();
+        tree.insert(2, 5);
+        tree.insert(7, 3);
+        let _ = tree.opening_cached(7);
+
+        assert!(tree.update(2, |leaf| *leaf *= 2));
+        assert!(!tree.update(3, |_| unreachable!()));
+        assert_eq!(*tree.root(), 13);
+
+        let mut expected = SumTree::new();
+        expected.insert(2, 10);
+        expected.insert(7, 3);
+        assert_eq!(tree.opening_cached(7), expected.opening(7));
+    }
+
+    #[test]
+    fn tree_cache_policy() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(3, 2);
+        tree.insert(6, 3);
+
+        let mut expected = SumTree::new();
+        expected.insert(0, 1);
+        expected.insert(3, 2);
+        expected.insert(6, 3);
+        let _ = expected.root();
+
+        // items are only kept for the root and the leaves
+        tree.set_cache_policy(CachePolicy::Never);
+        assert_eq!(*tree.root(), 6);
+        assert_eq!(tree.root.uncached_nodes(), 5);
+
+        tree.insert(7, 1);
+        expected.insert(7, 1);
+        assert_eq!(*tree.root(), *expected.root());
+        assert_eq!(tree.opening(3), expected.opening(3));
+
+        // building the opening computes items, which are evicted on demand
+        tree.evict_items();
+        assert_eq!(tree.root.uncached_nodes(), 5);
+
+        tree.set_cache_policy(CachePolicy::AboveHeight(2));
+        tree.insert(1, 4);
+        expected.insert(1, 4);
+        assert_eq!(*tree.root(), *expected.root());
+        assert_eq!(tree.root.uncached_nodes(), 3);
+
+        tree.set_cache_policy(CachePolicy::Always);
+        tree.insert(2, 4);
+        expected.insert(2, 4);
+        assert_eq!(*tree.root(), *expected.root());
+        assert_eq!(tree.root.uncached_nodes(), 1);
+    }
+
+    #[test]
+    fn tree_cache_policy_borrowed() {
+        let mut tree = SumTree::new();
+        tree.set_cache_policy(CachePolicy::Never);
+        tree.insert(0, 1);
+        tree.insert(3, 2);
+        tree.insert(6, 3);
+
+        // borrowed items are kept when evicting, instead of panicking
+        let subtree = tree.subtree_root(1, 0).unwrap();
+        assert_eq!(*tree.root(), 6);
+        tree.evict_items();
+        assert_eq!(*subtree, 3);
+        drop(subtree);
+
+        tree.evict_items();
+        assert_eq!(tree.root.uncached_nodes(), 5);
+    }
+
+    #[test]
+    fn tree_subtree_root() {
+        let mut tree = SumTree::new();
+        tree.insert(1, 3);
+        tree.insert(6, 5);
+        tree.insert(7, 2);
+
+        assert_eq!(*tree.subtree_root(0, 0).unwrap(), *tree.root());
+        assert_eq!(tree.subtree_root(1, 0).map(|sum| *sum), Some(3));
+        assert_eq!(tree.subtree_root(1, 1).map(|sum| *sum), Some(7));
+        assert_eq!(tree.subtree_root(2, 3).map(|sum| *sum), Some(7));
+        assert_eq!(tree.subtree_root(H, 6).map(|sum| *sum), Some(5));
+        assert!(tree.subtree_root(2, 1).is_none());
+        assert!(tree.subtree_root(H, 0).is_none());
+    }
+
+    #[test]
+    fn tree_membership() {
+        let mut tree = SumTree::new();
+        tree.insert(6, 1);
+        tree.insert(2, 1);
+        tree.insert(4, 1);
+
+        assert!(tree.contains_all([4, 2, 6, 4]));
+        assert!(tree.contains_all([]));
+        assert!(!tree.contains_all([2, 3]));
+
+        let bitmap = tree.membership_bitmap(1..7);
+        assert_eq!(bitmap.len(), 6);
+        assert_eq!(bitmap.words(), [0b10_1010]);
+        assert!(bitmap.iter().eq([false, true, false, true, false, true]));
+        assert!(tree.membership_bitmap(6..7).iter().eq([true]));
+        assert!(tree.membership_bitmap(5..5).is_empty());
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = tree.membership_bitmap(6..2);
+        assert!(inverted.is_empty());
+
+        // the range is clamped to the capacity of the tree
+        let bitmap = tree.membership_bitmap(4..u64::MAX);
+        assert_eq!(bitmap.len(), 4);
+        assert!(bitmap.iter().eq([true, false, true, false]));
+        assert!(tree.membership_bitmap(8..u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn tree_positions() {
+        let mut tree = SumTree::new();
+        tree.insert(6, 1);
+        tree.insert(2, 1);
+        tree.insert(4, 1);
+
+        let positions = tree.positions();
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions.collect::<Vec<_>>(), [2, 4, 6]);
+        assert_eq!(tree.positions().next_back(), Some(6));
+    }
+
+    #[test]
+    fn tree_position_cursor() {
+        let mut tree = SumTree::new();
+        assert_eq!(tree.min_position(), None);
+        assert_eq!(tree.max_position(), None);
+        assert_eq!(tree.cursor_from(0).next(), None);
+
+        tree.insert(6, 1);
+        tree.insert(2, 1);
+        tree.insert(4, 1);
+        assert_eq!(tree.min_position(), Some(2));
+        assert_eq!(tree.max_position(), Some(6));
+
+        let mut cursor = tree.cursor_from(3);
+        assert_eq!(cursor.peek_prev(), Some(2));
+        assert_eq!(cursor.peek_next(), Some(4));
+        assert_eq!(cursor.next(), Some(4));
+        assert_eq!(cursor.next(), Some(6));
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.position(), 7);
+
+        assert_eq!(cursor.prev(), Some(6));
+        assert_eq!(cursor.prev(), Some(4));
+        assert_eq!(cursor.prev(), Some(2));
+        assert_eq!(cursor.prev(), None);
+        assert_eq!(cursor.position(), 2);
+        assert_eq!(cursor.collect::<Vec<_>>(), [2, 4, 6]);
+
+        let mut cursor = tree.cursor_from(4);
+        assert_eq!(cursor.prev(), Some(2));
+        assert_eq!(tree.cursor_from(7).prev(), Some(6));
+    }
+
+    #[test]
+    fn tree_positions_set_operations() {
+        let mut tree = SumTree::new();
+        tree.insert(0, 1);
+        tree.insert(1, 1);
+        tree.insert(4, 1);
+        tree.insert(5, 1);
+        tree.insert(7, 1);
+
+        let mut other = SumTree::new();
+        other.insert(1, 1);
+        other.insert(2, 1);
+        other.insert(7, 1);
+
+        assert_eq!(tree.positions_intersection(&other), [1, 7]);
+        assert_eq!(other.positions_intersection(&tree), [1, 7]);
+        assert_eq!(tree.positions_difference(&other), [0, 4, 5]);
+        assert_eq!(other.positions_difference(&tree), [2]);
+
+        let empty = SumTree::new();
+        assert!(tree.positions_intersection(&empty).is_empty());
+        assert_eq!(tree.positions_difference(&empty), [0, 1, 4, 5, 7]);
+        assert!(empty.positions_difference(&tree).is_empty());
+    }
+
+    #[test]
+    fn tree_occupancy() {
+        let mut tree = SumTree::new();
+        assert!(tree.occupancy(1).is_empty());
+
+        tree.insert(0, 1);
+        tree.insert(1, 1);
+        tree.insert(2, 1);
+        tree.insert(7, 1);
+
+        assert_eq!(tree.occupancy(0), [(0, 4)]);
+        assert_eq!(tree.occupancy(1), [(0, 3), (1, 1)]);
+        assert_eq!(tree.occupancy(2), [(0, 2), (1, 1), (3, 1)]);
+        assert_eq!(tree.occupancy(3), [(0, 1), (1, 1), (2, 1), (7, 1)]);
+    }
+
     // create test tree for shrunken root:
 
     type RangeTree = Tree<Option<Range>, H, A>;
@@ -347,6 +2483,8 @@ mod tests {
     mod rkyv_impl {
         use super::SumTree;
 
+        use alloc::vec::Vec;
+
         #[test]
         fn serde() {
             let mut tree = SumTree::new();
@@ -364,5 +2502,138 @@ mod tests {
 
             assert_eq!(tree, archived_tree);
         }
+
+        #[test]
+        fn archived_walk() {
+            let mut tree = SumTree::new();
+
+            tree.insert(0, 1);
+            tree.insert(1, 2);
+            tree.insert(5, 4);
+            tree.insert(6, 5);
+
+            // computing the root computes the items of all the nodes
+            let _ = tree.root();
+
+            let tree_bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+            let archived = rkyv::check_archived_root::<SumTree>(&tree_bytes)
+                .expect("The archive should be valid");
+
+            let walk = |walker: fn(&u8) -> bool| -> Vec<u8> {
+                archived.walk(walker).copied().collect()
+            };
+
+            assert_eq!(walk(|_| true), [1, 2, 4, 5]);
+            assert_eq!(walk(|sum| *sum > 2), [4, 5]);
+            assert_eq!(walk(|sum| *sum != 3), [4, 5]);
+            assert_eq!(walk(|_| false), []);
+        }
+
+        #[test]
+        fn archived_queries() {
+            let mut tree = SumTree::new();
+
+            tree.insert(0, 1);
+            tree.insert(1, 2);
+            tree.insert(5, 4);
+            tree.insert(6, 5);
+
+            // the items of the inner nodes are left uncomputed
+            let lazy_bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+
+            let _ = tree.root();
+            let bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+
+            for bytes in [lazy_bytes, bytes] {
+                let archived = rkyv::check_archived_root::<SumTree>(&bytes)
+                    .expect("The archive should be valid");
+
+                assert_eq!(archived.len(), 4);
+                assert!(archived.contains(5));
+                assert!(!archived.contains(4));
+                assert_eq!(archived.root(), 12);
+
+                for position in [0, 1, 5, 6] {
+                    let opening = archived.opening(position).unwrap();
+                    assert_eq!(Some(opening), tree.opening(position));
+                }
+                assert!(archived.opening(4).is_none());
+            }
+        }
+
+        #[test]
+        fn archived_fingerprint() {
+            use crate::{Fingerprint, FingerprintError};
+
+            let mut tree = SumTree::with_backend(7);
+            tree.insert(5, 42);
+
+            let tree_bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+            let archived = rkyv::check_archived_root::<SumTree>(&tree_bytes)
+                .expect("The archive should be valid");
+
+            assert_eq!(archived.fingerprint(), tree.fingerprint());
+            assert_eq!(archived.check_fingerprint(&tree.fingerprint()), Ok(()));
+            assert_eq!(
+                archived.check_fingerprint(&Fingerprint::new::<3, 2>(0)),
+                Err(FingerprintError::Backend {
+                    expected: 0,
+                    found: 7
+                })
+            );
+
+            let restored = rkyv::from_bytes::<SumTree>(&tree_bytes)
+                .expect("Deserializing a tree should succeed");
+            assert_eq!(restored.fingerprint(), tree.fingerprint());
+        }
+
+        #[test]
+        fn archived_unchecked() {
+            let mut tree = SumTree::new();
+
+            tree.insert(5, 42);
+            tree.insert(6, 42);
+
+            let tree_bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+
+            let checked = rkyv::check_archived_root::<SumTree>(&tree_bytes)
+                .expect("The archive should be valid");
+            let unchecked = unsafe { SumTree::archived_unchecked(&tree_bytes) };
+
+            assert!(core::ptr::eq(checked, unchecked));
+        }
+
+        #[test]
+        fn read_root_only() {
+            let mut tree = SumTree::new();
+
+            tree.insert(5, 42);
+            tree.insert(6, 21);
+
+            // the root is stale, and therefore not archived
+            let tree_bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+            assert_eq!(SumTree::read_root_only(&tree_bytes), None);
+
+            let root = *tree.root();
+            let tree_bytes = rkyv::to_bytes::<_, 128>(&tree)
+                .expect("Archiving a tree should succeed")
+                .to_vec();
+            assert_eq!(SumTree::read_root_only(&tree_bytes), Some(root));
+
+            assert_eq!(SumTree::read_root_only(&[]), None);
+            assert_eq!(SumTree::read_root_only(&tree_bytes[..3]), None);
+        }
     }
 }
@@ -4,14 +4,61 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use core::cell::Ref;
+use alloc::collections::VecDeque;
+#[cfg(feature = "rkyv-impl")]
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::iter::FusedIterator;
+
+#[cfg(feature = "rkyv-impl")]
+use crate::ArchivedTree;
+use crate::{Aggregate, Node, Ref, Tree};
+
+/// A function deciding whether a [`Walk`] should descend into a subtree, or
+/// yield a leaf.
+///
+/// This is implemented for any `Fn(&T) -> bool`, and for [`WithHeight`],
+/// whose function is additionally passed the height of the node - with `0`
+/// being the root and `H` the leaves.
+pub trait Walker<T> {
+    /// Returns whether the walk should proceed through the node with the
+    /// given `item`, at the given `height`.
+    fn walk(&self, item: &T, height: usize) -> bool;
+}
+
+impl<T, F> Walker<T> for F
+where
+    F: Fn(&T) -> bool,
+{
+    fn walk(&self, item: &T, _height: usize) -> bool {
+        self(item)
+    }
+}
+
+/// A [`Walker`] whose function is passed the height of each node together
+/// with its item.
+///
+/// This is produced by [`Tree::walk_with_height`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithHeight<F>(pub F);
 
-use crate::{Aggregate, Node, Tree};
+impl<T, F> Walker<T> for WithHeight<F>
+where
+    F: Fn(&T, usize) -> bool,
+{
+    fn walk(&self, item: &T, height: usize) -> bool {
+        (self.0)(item, height)
+    }
+}
 
 /// Iterator that walks through a tree's leaves, according to a walker function.
+///
+/// Each leaf is yielded together with its position in the tree.
 #[derive(Debug, Clone)]
 pub struct Walk<'a, T, W, const H: usize, const A: usize> {
-    root: &'a Node<T, H, A>,
+    root: Option<&'a Node<T, H, A>>,
+    height: usize,
+    index: u64,
     walker: W,
 
     // These boots are made for walkin'.
@@ -22,105 +69,425 @@ pub struct Walk<'a, T, W, const H: usize, const A: usize> {
 impl<'a, T, W, const H: usize, const A: usize> Walk<'a, T, W, H, A>
 where
     T: Aggregate<A>,
-    W: Fn(&T) -> bool,
+    W: Walker<T>,
 {
     pub(crate) fn new(tree: &'a Tree<T, H, A>, walker: W) -> Self {
+        Self::from_node(Some(&tree.root), 0, 0, walker)
+    }
+
+    /// Create a walk starting at the given `node`, placed at the given
+    /// `height` and `index` of the tree. If there is no node, the walk yields
+    /// no items.
+    pub(crate) fn from_node(
+        node: Option<&'a Node<T, H, A>>,
+        height: usize,
+        index: u64,
+        walker: W,
+    ) -> Self {
         Self {
-            root: &tree.root,
+            root: node,
+            height,
+            index,
             walker,
             path: [None; H],
             indices: [0; H],
         }
     }
 
-    /// Advances the iterator recursively, returning a new leaf node if it is
-    /// found.
-    pub(crate) fn advance(
-        &mut self,
-        node: &'a Node<T, H, A>,
-        h: usize,
-    ) -> Option<Ref<'a, T>> {
-        // We are at a node before a leaf, therefore we should try to return our
-        // first eligible child.
-        if h == H - 1 {
-            let index = &mut self.indices[h];
-
-            // We keep iterating the stored index to ensure that when/if we
-            // return to this child we start from the previous index.
-            for i in *index..A {
-                *index = i + 1;
-                if let Some(leaf) = &node.children[i] {
-                    let leaf = leaf.item();
-                    if (self.walker)(&*leaf) {
-                        return Some(leaf);
-                    }
+    /// Further restricts the walk to the subtrees and leaves for which the
+    /// given `predicate` also returns `true`.
+    ///
+    /// Subtrees failing the predicate are not descended into, exactly as if
+    /// the walker function had returned `false`.
+    pub fn prune<P>(self, predicate: P) -> Walk<'a, T, impl Walker<T>, H, A>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.with_walker(move |walker| {
+            WithHeight(move |item: &T, height| {
+                walker.walk(item, height) && predicate(item)
+            })
+        })
+    }
+
+    /// Ends the walk as soon as a subtree or leaf it would visit fails the
+    /// given `predicate`.
+    ///
+    /// This is the subtree level equivalent of [`Iterator::take_while`]:
+    /// instead of checking the leaves as they are yielded, the walk stops at
+    /// the first aggregate failing the predicate, without descending into it.
+    pub fn take_while_subtrees<P>(
+        self,
+        predicate: P,
+    ) -> Walk<'a, T, impl Walker<T>, H, A>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.with_walker(move |walker| {
+            let stopped = Cell::new(false);
+            WithHeight(move |item: &T, height| {
+                if stopped.get() || !walker.walk(item, height) {
+                    return false;
                 }
-            }
+                if !predicate(item) {
+                    stopped.set(true);
+                    return false;
+                }
+                true
+            })
+        })
+    }
+
+    /// Maps the leaves of the walk to owned values using the given function.
+    pub fn map_leaves<U, F>(self, f: F) -> impl Iterator<Item = U> + 'a
+    where
+        T: 'a,
+        W: 'a,
+        F: Fn(&T) -> U + 'a,
+    {
+        self.map(move |(_, item)| f(&item))
+    }
 
-            // We will never return here, so we should set this to zero to
-            // ensure our siblings start looking at their first child.
-            *index = 0;
-            return None;
+    /// Replaces the walker function with the one returned by `f`, keeping
+    /// the state of the walk.
+    fn with_walker<V, F>(self, f: F) -> Walk<'a, T, V, H, A>
+    where
+        F: FnOnce(W) -> V,
+    {
+        Walk {
+            root: self.root,
+            height: self.height,
+            index: self.index,
+            walker: f(self.walker),
+            path: self.path,
+            indices: self.indices,
         }
+    }
+
+    /// Returns the position of the leaf the walk is currently at, computed
+    /// from the index of the child taken at each level of the path.
+    fn leaf_position(&self) -> u64 {
+        // the indices were advanced past the children taken
+        self.indices[self.height..]
+            .iter()
+            .fold(self.index, |position, &i| {
+                position * A as u64 + i as u64 - 1
+            })
+    }
+
+    /// Advances the iterator, returning a new leaf node and its position if
+    /// it is found.
+    ///
+    /// The walk is driven by an explicit stack of frames - the nodes on the
+    /// current path, and the index of the next child to try in each of them -
+    /// meaning the stack usage doesn't grow with the height of the tree. The
+    /// items of the children are only borrowed while the walker is called on
+    /// them, except for the leaf being returned.
+    fn advance(&mut self) -> Option<(u64, Ref<'a, T>)> {
+        let root = self.root?;
+
+        // resume from the deepest frame of the path
+        let mut h = self.height;
+        while h < H - 1 && self.path[h].is_some() {
+            h += 1;
+        }
+
+        loop {
+            let node = if h == self.height {
+                root
+            } else {
+                let Some(node) = self.path[h - 1] else {
+                    unreachable!("frames below the start are always entered");
+                };
+                node
+            };
+
+            let mut next = None;
+            while self.indices[h] < A {
+                let i = self.indices[h];
+                self.indices[h] += 1;
 
-        // If there is no child in the path, we have never been here before.
-        // Therefore we try to set the path to one of our children, starting
-        // from the first.
-        if self.path[h].is_none() {
-            for i in 0..A {
-                self.indices[h] = i;
                 if let Some(child) = &node.children[i] {
-                    let child = child.as_ref();
-                    if (self.walker)(&*child.item()) {
-                        self.path[h] = Some(child);
+                    // We are at a node before a leaf, therefore we return the
+                    // first eligible child.
+                    if h == H - 1 {
+                        let leaf = child.item();
+                        if self.walker.walk(&leaf, H) {
+                            return Some((self.leaf_position(), leaf));
+                        }
+                    } else if self.walker.walk(&child.item(), h + 1) {
+                        next = Some(&**child);
                         break;
                     }
                 }
             }
-        }
 
-        // If there is a child in the path, we have either set it previously or
-        // are re-entering. Either way we should advance.
-        //
-        // If the advance returns `Some` we just return the item, otherwise we
-        // try the next child in line, and advance through it.
-        if let Some(child) = self.path[h] {
-            if let Some(item) = self.advance(child, h + 1) {
-                return Some(item);
+            if let Some(child) = next {
+                // enter the child, starting from its first child
+                self.path[h] = Some(child);
+                h += 1;
+            } else {
+                // the node is exhausted, so we leave it ensuring the frame
+                // starts from the first child when it is next entered
+                self.indices[h] = 0;
+                if h == self.height {
+                    self.root = None;
+                    return None;
+                }
+                h -= 1;
+                self.path[h] = None;
             }
+        }
+    }
+}
 
-            for i in self.indices[h] + 1..A {
-                self.indices[h] = i;
+impl<'a, T, W, const H: usize, const A: usize> Iterator for Walk<'a, T, W, H, A>
+where
+    T: Aggregate<A>,
+    W: Walker<T>,
+{
+    type Item = (u64, Ref<'a, T>);
 
-                if let Some(child) = &node.children[i] {
-                    let child = child.as_ref();
-                    if (self.walker)(&*child.item()) {
-                        self.path[h] = Some(child);
-                        match self.advance(child, h + 1) {
-                            Some(item) => return Some(item),
-                            None => continue,
-                        }
-                    }
-                }
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance()
+    }
+}
+
+impl<T, W, const H: usize, const A: usize> FusedIterator
+    for Walk<'_, T, W, H, A>
+where
+    T: Aggregate<A>,
+    W: Walker<T>,
+{
+}
+
+/// Iterator that walks through a tree's leaves according to a walker
+/// function, yielding clones of the items.
+///
+/// This is produced by [`Tree::walk_cloned`].
+#[derive(Debug, Clone)]
+pub struct WalkCloned<'a, T, W, const H: usize, const A: usize> {
+    walk: Walk<'a, T, W, H, A>,
+}
 
-            self.path[h] = None;
-            self.indices[h] = 0;
+impl<'a, T, W, const H: usize, const A: usize> WalkCloned<'a, T, W, H, A>
+where
+    T: Aggregate<A>,
+    W: Walker<T>,
+{
+    pub(crate) fn new(tree: &'a Tree<T, H, A>, walker: W) -> Self {
+        Self {
+            walk: Walk::new(tree, walker),
         }
+    }
+}
 
-        None
+impl<T, W, const H: usize, const A: usize> Iterator
+    for WalkCloned<'_, T, W, H, A>
+where
+    T: Aggregate<A> + Clone,
+    W: Walker<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walk.next().map(|(_, item)| item.clone())
     }
 }
 
-impl<'a, T, W, const H: usize, const A: usize> Iterator for Walk<'a, T, W, H, A>
+/// Iterator over all the leaves of a tree, in the order of their positions.
+///
+/// The number of leaves is known in advance, meaning the iterator reports an
+/// exact size.
+///
+/// This is produced by [`Tree::leaves`].
+#[derive(Debug, Clone)]
+pub struct Leaves<'a, T, const H: usize, const A: usize> {
+    walk: Walk<'a, T, fn(&T) -> bool, H, A>,
+    remaining: usize,
+}
+
+impl<'a, T, const H: usize, const A: usize> Leaves<'a, T, H, A>
+where
+    T: Aggregate<A>,
+{
+    pub(crate) fn new(tree: &'a Tree<T, H, A>, len: usize) -> Self {
+        Self {
+            walk: Walk::new(tree, |_| true),
+            remaining: len,
+        }
+    }
+}
+
+impl<'a, T, const H: usize, const A: usize> Iterator for Leaves<'a, T, H, A>
 where
     T: Aggregate<A>,
-    W: Fn(&T) -> bool,
 {
     type Item = Ref<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.advance(self.root, 0)
+        let (_, item) = self.walk.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, const H: usize, const A: usize> ExactSizeIterator
+    for Leaves<'_, T, H, A>
+where
+    T: Aggregate<A>,
+{
+}
+
+impl<T, const H: usize, const A: usize> FusedIterator for Leaves<'_, T, H, A> where
+    T: Aggregate<A>
+{
+}
+
+/// Iterator over the occupied nodes of a tree in breadth-first order, i.e.
+/// level by level starting from the root.
+///
+/// Each node is yielded together with its height - with `0` being the root
+/// and `H` the leaves - and its index within that height. Within a level the
+/// nodes are yielded from left to right, meaning the leaves come last and in
+/// the order of their positions.
+///
+/// This is produced by [`Tree::breadth_first`].
+#[derive(Debug, Clone)]
+pub struct BreadthFirst<'a, T, const H: usize, const A: usize> {
+    queue: VecDeque<(usize, u64, &'a Node<T, H, A>)>,
+}
+
+impl<'a, T, const H: usize, const A: usize> BreadthFirst<'a, T, H, A>
+where
+    T: Aggregate<A>,
+{
+    pub(crate) fn new(tree: &'a Tree<T, H, A>) -> Self {
+        let mut queue = VecDeque::new();
+        if !tree.is_empty() {
+            queue.push_back((0, 0, &tree.root));
+        }
+        Self { queue }
+    }
+}
+
+impl<'a, T, const H: usize, const A: usize> Iterator
+    for BreadthFirst<'a, T, H, A>
+where
+    T: Aggregate<A>,
+{
+    type Item = (usize, u64, Ref<'a, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (height, index, node) = self.queue.pop_front()?;
+
+        if height < H {
+            for (i, child) in node.children.iter().enumerate() {
+                if let Some(child) = child {
+                    let child_index = index * A as u64 + i as u64;
+                    self.queue.push_back((height + 1, child_index, child));
+                }
+            }
+        }
+
+        Some((height, index, node.item()))
+    }
+}
+
+impl<T, const H: usize, const A: usize> FusedIterator
+    for BreadthFirst<'_, T, H, A>
+where
+    T: Aggregate<A>,
+{
+}
+
+/// Iterator that walks through an archived tree's leaves, according to a
+/// walker function.
+///
+/// This is produced by [`ArchivedTree::walk`], and behaves like [`Walk`],
+/// except it runs directly on the archive, with the walker function being
+/// passed the archived items.
+#[cfg(feature = "rkyv-impl")]
+pub struct ArchivedWalk<'a, T, W, const H: usize, const A: usize>
+where
+    T: rkyv::Archive + 'a,
+{
+    walker: W,
+    // The nodes currently being walked, together with the index of the next
+    // child to visit in each.
+    stack: Vec<(&'a rkyv::Archived<Node<T, H, A>>, usize)>,
+}
+
+#[cfg(feature = "rkyv-impl")]
+impl<'a, T, W, const H: usize, const A: usize> Iterator
+    for ArchivedWalk<'a, T, W, H, A>
+where
+    T: rkyv::Archive,
+    W: Fn(&T::Archived) -> bool,
+{
+    type Item = &'a T::Archived;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let height = self.stack.len();
+            let (node, index) = self.stack.last_mut()?;
+
+            if *index == A {
+                self.stack.pop();
+                continue;
+            }
+
+            let child = node.child(*index);
+            *index += 1;
+
+            let Some(child) = child else {
+                continue;
+            };
+
+            // nodes whose item wasn't archived are always descended into
+            let item = child.item();
+            if let Some(item) = item {
+                if !(self.walker)(item) {
+                    continue;
+                }
+            }
+
+            if height == H {
+                // leaves always have an item
+                if item.is_some() {
+                    return item;
+                }
+                continue;
+            }
+
+            self.stack.push((child, 0));
+        }
+    }
+}
+
+#[cfg(feature = "rkyv-impl")]
+impl<T, const H: usize, const A: usize> ArchivedTree<T, H, A>
+where
+    T: rkyv::Archive,
+{
+    /// Returns an iterator that walks over the leaves of the archived tree,
+    /// as [`Tree::walk`] does for a tree in memory.
+    ///
+    /// The items of the inner nodes are computed lazily, and only those
+    /// computed at the time the tree was serialized are archived. The nodes
+    /// whose item wasn't archived are always descended into. Calling
+    /// [`Tree::root`] before serializing ensures all items are archived.
+    pub fn walk<W>(&self, walker: W) -> ArchivedWalk<'_, T, W, H, A>
+    where
+        W: Fn(&T::Archived) -> bool,
+    {
+        let mut stack = Vec::with_capacity(H);
+        stack.push((&self.root, 0));
+        ArchivedWalk { walker, stack }
     }
 }
 
@@ -128,6 +495,8 @@ where
 mod tests {
     use crate::{Aggregate, Tree};
 
+    use alloc::vec::Vec;
+
     #[derive(Debug, Default, Clone, Copy)]
     struct Max(u64);
 
@@ -172,9 +541,9 @@ mod tests {
 
         let mut walk = tree.walk(is_larger_than);
 
-        assert!(matches!(walk.next(), Some(x) if x.0 == 8));
-        assert!(matches!(walk.next(), Some(x) if x.0 == 16));
-        assert!(matches!(walk.next(), None));
+        assert!(matches!(walk.next(), Some((1, x)) if x.0 == 8));
+        assert!(matches!(walk.next(), Some((2, x)) if x.0 == 16));
+        assert!(walk.next().is_none());
     }
 
     #[test]
@@ -186,8 +555,8 @@ mod tests {
 
         let mut walk = tree.walk(is_larger_than);
 
-        assert!(matches!(walk.next(), Some(x) if x.0 == 8));
-        assert!(matches!(walk.next(), None));
+        assert!(matches!(walk.next(), Some((1, x)) if x.0 == 8));
+        assert!(walk.next().is_none());
     }
 
     #[test]
@@ -203,17 +572,193 @@ mod tests {
 
         let mut walk = tree.walk(is_larger_than);
 
-        assert!(matches!(walk.next(), Some(x) if x.0 == 16));
-        assert!(matches!(walk.next(), Some(x) if x.0 == 25));
-        assert!(matches!(walk.next(), Some(x) if x.0 == 8));
-        assert!(matches!(walk.next(), Some(x) if x.0 == 25));
-        assert!(matches!(walk.next(), None));
+        assert!(matches!(walk.next(), Some((0x42, x)) if x.0 == 16));
+        assert!(matches!(walk.next(), Some((0x1ead, x)) if x.0 == 25));
+        assert!(matches!(walk.next(), Some((0xbeef, x)) if x.0 == 8));
+        assert!(matches!(walk.next(), Some((0xca11, x)) if x.0 == 25));
+        assert!(walk.next().is_none());
+    }
+
+    #[test]
+    fn subtree() {
+        let mut tree = LargeTree::new();
+
+        tree.insert(0x42, 16);
+        tree.insert(0x1ead, 25);
+        tree.insert(0x1eaf, 8);
+        tree.insert(0xbeef, 8);
+
+        // the subtree at height 14 and index `0x7a` holds the leaves in the
+        // range `[0x1e80, 0x1ec0[`
+        let mut walk = tree.walk_subtree(14, 0x7a, is_larger_than);
+
+        assert!(matches!(walk.next(), Some((0x1ead, x)) if x.0 == 25));
+        assert!(matches!(walk.next(), Some((0x1eaf, x)) if x.0 == 8));
+        assert!(walk.next().is_none());
+
+        let mut walk = tree.walk_subtree(14, 0x7b, is_larger_than);
+        assert!(walk.next().is_none());
+
+        let mut walk = tree.walk_subtree(0, 0, is_larger_than);
+        assert_eq!(walk.by_ref().count(), 4);
+    }
+
+    #[test]
+    fn deep_tree() {
+        const HEIGHT_63: usize = 63;
+        let mut tree = Tree::<Max, HEIGHT_63, ARITY_2>::new();
+
+        let positions = [0, 1, 1 << 31, (1 << 63) - 2, (1 << 63) - 1];
+        for position in positions {
+            tree.insert(position, Max(position % 10));
+        }
+
+        let mut walk = tree.walk(|max| max.0 >= 1);
+        let items: Vec<(u64, u64)> = walk
+            .by_ref()
+            .map(|(position, max)| (position, max.0))
+            .collect();
+        assert_eq!(
+            items,
+            [(1, 1), (1 << 31, 8), ((1 << 63) - 2, 6), ((1 << 63) - 1, 7)]
+        );
+
+        // the walk is fused
+        assert!(walk.next().is_none());
+    }
+
+    #[test]
+    fn cloned_items() {
+        let mut tree = SmallTree::new();
+
+        tree.insert(0, 2);
+        tree.insert(1, 8);
+        tree.insert(2, 16);
+
+        let items: Vec<u64> =
+            tree.walk_cloned(is_larger_than).map(|max| max.0).collect();
+        drop(tree);
+
+        assert_eq!(items, [8, 16]);
+    }
+
+    #[test]
+    fn leaves() {
+        let mut tree = LargeTree::new();
+
+        tree.insert(0xbeef, 8);
+        tree.insert(0x42, 16);
+        tree.insert(0x1ead, 25);
+
+        let mut leaves = tree.leaves();
+        assert_eq!(leaves.len(), 3);
+
+        assert!(matches!(leaves.next(), Some(x) if x.0 == 16));
+        assert_eq!(leaves.size_hint(), (2, Some(2)));
+
+        let rest: Vec<u64> = leaves.map(|max| max.0).collect();
+        assert_eq!(rest, [25, 8]);
+
+        assert_eq!(SmallTree::new().leaves().len(), 0);
+    }
+
+    #[test]
+    fn combinators() {
+        let mut tree = SmallTree::new();
+
+        tree.insert(0, 2);
+        tree.insert(1, 8);
+        tree.insert(2, 16);
+        tree.insert(3, 7);
+
+        let pruned: Vec<u64> = tree
+            .walk(is_larger_than)
+            .prune(|max| max.0 < 10)
+            .map_leaves(|max| max.0)
+            .collect();
+        // the second subtree has a max of 16, and is never descended into
+        assert_eq!(pruned, [8]);
+
+        let taken: Vec<u64> = tree
+            .walk(|_| true)
+            .take_while_subtrees(|max| max.0 != 7)
+            .map_leaves(|max| max.0)
+            .collect();
+        assert_eq!(taken, [2, 8, 16]);
+
+        let none = tree.walk(|_| true).take_while_subtrees(|max| max.0 < 5);
+        assert_eq!(none.count(), 0);
+    }
+
+    #[test]
+    fn with_height() {
+        let mut tree = SmallTree::new();
+
+        tree.insert(0, 2);
+        tree.insert(1, 8);
+        tree.insert(2, 16);
+        tree.insert(3, 7);
+
+        // the leaves are only kept if small, while subtrees are always entered
+        let leaves: Vec<u64> = tree
+            .walk_with_height(|max: &Max, height| {
+                height < HEIGHT_2 || max.0 < 10
+            })
+            .map_leaves(|max| max.0)
+            .collect();
+        assert_eq!(leaves, [2, 8, 7]);
+
+        // the subtrees are only entered if large, while leaves are always kept
+        let leaves: Vec<u64> = tree
+            .walk_with_height(|max: &Max, height| {
+                height == HEIGHT_2 || max.0 > 10
+            })
+            .map_leaves(|max| max.0)
+            .collect();
+        assert_eq!(leaves, [16, 7]);
+
+        let pruned: Vec<u64> = tree
+            .walk_with_height(|max: &Max, height| {
+                height == HEIGHT_2 || max.0 > 10
+            })
+            .prune(|max| max.0 != 7)
+            .map_leaves(|max| max.0)
+            .collect();
+        assert_eq!(pruned, [16]);
+    }
+
+    #[test]
+    fn breadth_first() {
+        let mut tree = SmallTree::new();
+
+        tree.insert(0, 2);
+        tree.insert(1, 8);
+        tree.insert(3, 4);
+
+        let nodes: Vec<(usize, u64, u64)> = tree
+            .breadth_first()
+            .map(|(height, index, max)| (height, index, max.0))
+            .collect();
+
+        assert_eq!(
+            nodes,
+            [
+                (0, 0, 8),
+                (1, 0, 8),
+                (1, 1, 4),
+                (2, 0, 2),
+                (2, 1, 8),
+                (2, 3, 4)
+            ]
+        );
+
+        assert_eq!(SmallTree::new().breadth_first().count(), 0);
     }
 
     #[test]
     fn empty_tree() {
         let tree = SmallTree::new();
         let mut walk = tree.walk(is_larger_than);
-        assert!(matches!(walk.next(), None));
+        assert!(walk.next().is_none());
     }
 }
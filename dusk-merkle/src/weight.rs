@@ -0,0 +1,129 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#[cfg(feature = "rkyv-impl")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "rkyv-impl")]
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{Aggregate, Weighted};
+
+/// An item annotated with a weight, such as the voting power of a
+/// provisioner.
+///
+/// The items are aggregated as usual, while the weights are summed, making
+/// the weight of every subtree available in - and committed to by - the
+/// tree. This allows for checking whether a subtree reaches a quorum with
+/// [`Tree::subtree_meets_threshold`], without enumerating its leaves.
+///
+/// [`Tree::subtree_meets_threshold`]: crate::Tree::subtree_meets_threshold
+///
+/// # Example
+/// ```rust
+/// use dusk_merkle::{Aggregate, Tree, WithWeight};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Count(u64);
+///
+/// impl Aggregate<2> for Count {
+///     const EMPTY_SUBTREE: Self = Count(0);
+///
+///     fn aggregate(items: [&Self; 2]) -> Self {
+///         Count(items[0].0 + items[1].0)
+///     }
+/// }
+///
+/// let mut tree = Tree::<WithWeight<Count>, 4, 2>::new();
+/// tree.insert(0, WithWeight::new(Count(1), 40));
+/// tree.insert(1, WithWeight::new(Count(1), 30));
+/// tree.insert(8, WithWeight::new(Count(1), 50));
+///
+/// assert_eq!(tree.root().weight, 120);
+/// assert!(tree.subtree_meets_threshold(1, 0, 70));
+/// assert!(!tree.subtree_meets_threshold(1, 1, 70));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv-impl",
+    derive(Archive, Serialize, Deserialize),
+    archive_attr(derive(CheckBytes))
+)]
+pub struct WithWeight<T> {
+    /// The annotated item.
+    pub item: T,
+    /// The weight of the item.
+    pub weight: u64,
+}
+
+impl<T> WithWeight<T> {
+    /// Annotate the given `item` with the given `weight`.
+    pub const fn new(item: T, weight: u64) -> Self {
+        Self { item, weight }
+    }
+}
+
+impl<T, const A: usize> Aggregate<A> for WithWeight<T>
+where
+    T: Aggregate<A>,
+{
+    const EMPTY_SUBTREE: Self = Self::new(T::EMPTY_SUBTREE, 0);
+
+    fn aggregate(items: [&Self; A]) -> Self {
+        let weight = items
+            .iter()
+            .fold(0u64, |weight, item| weight.saturating_add(item.weight));
+        let items = items.map(|item| &item.item);
+
+        Self::new(T::aggregate(items), weight)
+    }
+}
+
+impl<T> Weighted for WithWeight<T> {
+    fn weight(&self) -> u64 {
+        self.weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Tree;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Count(u64);
+
+    impl Aggregate<2> for Count {
+        const EMPTY_SUBTREE: Self = Count(0);
+
+        fn aggregate(items: [&Self; 2]) -> Self {
+            Count(items[0].0 + items[1].0)
+        }
+    }
+
+    type Item = WithWeight<Count>;
+
+    #[test]
+    fn weighted_tree() {
+        let mut tree = Tree::<Item, 3, 2>::new();
+        assert_eq!(*tree.root(), Item::new(Count(0), 0));
+
+        tree.insert(2, Item::new(Count(1), 10));
+        tree.insert(3, Item::new(Count(1), u64::MAX));
+        tree.insert(7, Item::new(Count(1), 5));
+
+        // the weights saturate instead of overflowing
+        assert_eq!(*tree.root(), Item::new(Count(3), u64::MAX));
+        assert!(tree.subtree_meets_threshold(2, 1, u64::MAX));
+        assert!(tree.subtree_meets_threshold(1, 1, 5));
+        assert!(!tree.subtree_meets_threshold(1, 1, 6));
+
+        let opening = tree.opening(7).unwrap();
+        assert_eq!(opening.branch()[0][1].weight, 5);
+        assert!(opening.verify(Item::new(Count(1), 5)));
+        assert!(!opening.verify(Item::new(Count(1), 6)));
+    }
+}
@@ -7,16 +7,15 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 use dusk_bls12_381::BlsScalar;
-use dusk_poseidon::sponge::hash as poseidon_hash;
+use dusk_poseidon::{Domain, Hash};
 use poseidon_merkle::{Item, Tree};
 
 use rand::{RngCore, SeedableRng};
 
-// set height and arity of the poseidon merkle tree
+// set height of the poseidon merkle tree
 const HEIGHT: usize = 17;
-const ARITY: usize = 4;
 
-type PoseidonTree = Tree<(), HEIGHT, ARITY>;
+type PoseidonTree = Tree<(), HEIGHT>;
 type PoseidonItem = Item<()>;
 
 fn bench_poseidon(c: &mut Criterion) {
@@ -26,11 +25,36 @@ fn bench_poseidon(c: &mut Criterion) {
     c.bench_function("poseidon insertion", |b| {
         b.iter(|| {
             let pos = rng.next_u64() % u32::MAX as u64;
-            let hash = poseidon_hash(&[BlsScalar::from(pos)]);
+            let hash = Hash::digest(Domain::Other, &[BlsScalar::from(pos)])[0];
             let item = PoseidonItem { hash, data: () };
             tree.insert(black_box(pos), black_box(item));
         })
     });
 }
-criterion_group!(benches, bench_poseidon);
+
+fn bench_poseidon_root(c: &mut Criterion) {
+    let tree = &mut PoseidonTree::new();
+    let rng = &mut rand::rngs::StdRng::seed_from_u64(0xbeef);
+
+    for _ in 0..1000 {
+        let pos = rng.next_u64() % u32::MAX as u64;
+        let hash = Hash::digest(Domain::Other, &[BlsScalar::from(pos)])[0];
+        tree.insert(pos, PoseidonItem { hash, data: () });
+    }
+    let _ = tree.root();
+
+    // only the path of the inserted leaf is aggregated, the items of its
+    // siblings being kept from the previous computation
+    c.bench_function("poseidon insertion and root", |b| {
+        b.iter(|| {
+            let pos = rng.next_u64() % u32::MAX as u64;
+            let hash = Hash::digest(Domain::Other, &[BlsScalar::from(pos)])[0];
+            let item = PoseidonItem { hash, data: () };
+            tree.insert(black_box(pos), black_box(item));
+            let _ = black_box(tree.root());
+        })
+    });
+}
+
+criterion_group!(benches, bench_poseidon, bench_poseidon_root);
 criterion_main!(benches);
@@ -10,10 +10,8 @@ use ff::Field;
 use rand::rngs::StdRng;
 use rand::{RngCore, SeedableRng};
 
-use poseidon_merkle::zk::opening_gadget;
-use poseidon_merkle::{
-    Item as PoseidonItem, Opening as PoseidonOpening, Tree as PoseidonTree,
-};
+use poseidon_merkle::zk::OpeningCircuit;
+use poseidon_merkle::{Item as PoseidonItem, Tree as PoseidonTree};
 
 // set max circuit size to 2^16 gates
 const CAPACITY: usize = 16;
@@ -21,85 +19,35 @@ const CAPACITY: usize = 16;
 // set height of the poseidon merkle tree
 const HEIGHT: usize = 17;
 
-// Create a circuit for the opening
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
-struct OpeningCircuit {
-    opening: PoseidonOpening<(), HEIGHT>,
-    leaf: PoseidonItem<()>,
-}
-
-impl Default for OpeningCircuit {
-    fn default() -> Self {
-        let empty_item = PoseidonItem {
-            hash: BlsScalar::zero(),
-            data: (),
-        };
-        let mut tree = PoseidonTree::new();
-        tree.insert(0, empty_item);
-        let opening = tree.opening(0).expect("There is a leaf at position 0");
-        Self {
-            opening,
-            leaf: empty_item,
-        }
-    }
-}
-
-impl OpeningCircuit {
-    /// Create a new OpeningCircuit
-    pub fn new(
-        opening: PoseidonOpening<(), HEIGHT>,
-        leaf: PoseidonItem<()>,
-    ) -> Self {
-        Self { opening, leaf }
-    }
-}
-
-impl Circuit for OpeningCircuit {
-    fn circuit(&self, composer: &mut Composer) -> Result<(), Error> {
-        // append the leaf and opening gadget to the circuit
-        let leaf = composer.append_witness(self.leaf.hash);
-        let computed_root = opening_gadget(composer, &self.opening, leaf);
-
-        // append the public root as public input to the circuit
-        // and ensure it is equal to the computed root
-        let constraint = Constraint::new()
-            .left(-BlsScalar::one())
-            .a(computed_root)
-            .public(self.opening.root().hash);
-        composer.append_gate(constraint);
-
-        Ok(())
-    }
-}
+type PoseidonCircuit = OpeningCircuit<(), HEIGHT>;
 
 fn main() {
     let label = b"merkle poseidon opening";
     let mut rng = StdRng::seed_from_u64(0xdea1);
     let pp = PublicParameters::setup(1 << CAPACITY, &mut rng).unwrap();
 
-    let (prover, verifier) = Compiler::compile::<OpeningCircuit>(&pp, label)
+    let (prover, verifier) = PoseidonCircuit::compile(&pp, label)
         .expect("Circuit should compile successfully");
 
-    let mut tree = PoseidonTree::new();
+    let mut tree = PoseidonTree::<(), HEIGHT>::new();
     let mut leaf = PoseidonItem::<()>::new(BlsScalar::zero(), ());
     let mut position = 0;
     for _ in 0..100 {
         let hash =
             Hash::digest(Domain::Other, &[BlsScalar::random(&mut rng)])[0];
-        position = rng.next_u64() % u8::MAX as u64;
+        position = rng.next_u64() % u64::from(u8::MAX);
         leaf = PoseidonItem::<()>::new(hash, ());
-        tree.insert(position as u64, leaf);
+        tree.insert(position, leaf);
     }
-    let opening = tree.opening(position as u64).unwrap();
-    assert!(opening.verify(leaf.clone()));
+    let opening = tree.opening(position).unwrap();
+    assert!(opening.verify(leaf));
 
-    let circuit = OpeningCircuit::new(opening, leaf);
+    let circuit = PoseidonCircuit::new(opening, leaf);
 
-    let (proof, public_inputs) = prover
-        .prove(&mut rng, &circuit)
+    let (proof, _) = circuit
+        .prove(&prover, &mut rng)
         .expect("Proof generation should succeed");
 
-    verifier
-        .verify(&proof, &public_inputs)
+    PoseidonCircuit::verify(&verifier, &proof, &tree.root().hash)
         .expect("Proof verification should succeed");
 }
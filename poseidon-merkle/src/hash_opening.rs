@@ -0,0 +1,179 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use dusk_bls12_381::BlsScalar;
+use dusk_bytes::{DeserializableSlice, Error as BytesError, Serializable};
+use dusk_merkle::Aggregate;
+use dusk_poseidon::{Domain, Hash};
+
+use crate::{Opening, ARITY};
+
+/// An opening of a poseidon tree holding only the hashes of its items,
+/// leaving out any data they carry.
+///
+/// This allows for openings of trees with any annotation type `T` to be
+/// exchanged in a compact, fixed-size form, and verified against the hash of
+/// a leaf and the hash of the root.
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use poseidon_merkle::{leaf_hash, HashOpening, Item, Tree};
+///
+/// const H: usize = 17;
+///
+/// let hash = leaf_hash(&[BlsScalar::from(42)]);
+///
+/// let mut tree = Tree::<(), H>::new();
+/// tree.insert(42, hash);
+///
+/// let opening = HashOpening::from(&tree.opening(42).unwrap());
+///
+/// let bytes = opening.to_bytes();
+/// assert_eq!(bytes.len(), HashOpening::<H>::SIZE);
+///
+/// let opening = HashOpening::<H>::from_bytes(&bytes).unwrap();
+/// assert_eq!(opening.root(), &tree.root().hash);
+/// assert!(opening.verify(hash));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HashOpening<const H: usize> {
+    root: BlsScalar,
+    branch: Box<[[BlsScalar; ARITY]; H]>,
+    positions: [usize; H],
+}
+
+impl<T, const H: usize> From<&Opening<T, H>> for HashOpening<H>
+where
+    T: Aggregate<ARITY> + Clone,
+{
+    fn from(opening: &Opening<T, H>) -> Self {
+        let mut branch = Box::new([[BlsScalar::zero(); ARITY]; H]);
+        for (level, items) in branch.iter_mut().zip(opening.branch().iter()) {
+            for (hash, item) in level.iter_mut().zip(items) {
+                *hash = item.hash;
+            }
+        }
+
+        Self {
+            root: opening.root().hash,
+            branch,
+            positions: *opening.positions(),
+        }
+    }
+}
+
+impl<const H: usize> HashOpening<H> {
+    /// The size of a serialized opening, in bytes.
+    pub const SIZE: usize = BlsScalar::SIZE * (1 + H * ARITY) + H;
+
+    /// Returns the hash of the root of the opening.
+    #[must_use]
+    pub const fn root(&self) -> &BlsScalar {
+        &self.root
+    }
+
+    /// Returns the hashes of the branch of the opening.
+    #[must_use]
+    pub fn branch(&self) -> &[[BlsScalar; ARITY]; H] {
+        &self.branch
+    }
+
+    /// Returns the positions of the opening.
+    #[must_use]
+    pub const fn positions(&self) -> &[usize; H] {
+        &self.positions
+    }
+
+    /// Verify the given `hash` is the hash of the leaf of the opening, and
+    /// that the opening is cryptographically correct.
+    #[must_use]
+    pub fn verify(&self, hash: BlsScalar) -> bool {
+        let mut hash = hash;
+
+        for h in (0..H).rev() {
+            let level = &self.branch[h];
+            if hash != level[self.positions[h]] {
+                return false;
+            }
+            hash = Hash::digest(Domain::Merkle4, level)[0];
+        }
+
+        self.root == hash
+    }
+
+    /// Serialize the opening to a vector of exactly [`SIZE`] bytes.
+    ///
+    /// The serialized opening is composed of:
+    /// - the hash of the root
+    /// - the hashes of the branch, from the root down
+    /// - the positions, from the root down, each as a single byte
+    ///
+    /// [`SIZE`]: HashOpening::SIZE
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::SIZE);
+
+        bytes.extend(&self.root.to_bytes());
+        for level in self.branch.iter() {
+            for hash in level {
+                bytes.extend(&hash.to_bytes());
+            }
+        }
+        for &position in &self.positions {
+            // positions are always smaller than the arity
+            #[allow(clippy::cast_possible_truncation)]
+            bytes.push(position as u8);
+        }
+
+        bytes
+    }
+
+    /// Deserialize an opening from a slice of bytes.
+    ///
+    /// # Errors
+    /// If the slice is not of exactly [`SIZE`] bytes, any of the hashes is
+    /// not canonically encoded, or any of the positions is not smaller than
+    /// the arity.
+    ///
+    /// [`SIZE`]: HashOpening::SIZE
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, BytesError> {
+        if buf.len() != Self::SIZE {
+            return Err(BytesError::BadLength {
+                found: buf.len(),
+                expected: Self::SIZE,
+            });
+        }
+
+        let mut bytes = buf;
+
+        let root = BlsScalar::from_reader(&mut bytes)?;
+
+        let mut branch = Box::new([[BlsScalar::zero(); ARITY]; H]);
+        for level in branch.iter_mut() {
+            for hash in level {
+                *hash = BlsScalar::from_reader(&mut bytes)?;
+            }
+        }
+
+        let mut positions = [0; H];
+        for (position, &byte) in positions.iter_mut().zip(bytes) {
+            *position = byte as usize;
+            if *position >= ARITY {
+                return Err(BytesError::InvalidData);
+            }
+        }
+
+        Ok(Self {
+            root,
+            branch,
+            positions,
+        })
+    }
+}
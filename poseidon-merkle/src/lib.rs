@@ -8,12 +8,17 @@
 #![no_std]
 #![deny(clippy::pedantic)]
 
+extern crate alloc;
+
 #[cfg(feature = "zk")]
 pub mod zk;
 
+mod hash_opening;
+pub use hash_opening::HashOpening;
+
 use dusk_bls12_381::BlsScalar;
 use dusk_bytes::Serializable;
-use dusk_merkle::Aggregate;
+use dusk_merkle::{Aggregate, OpeningError};
 use dusk_poseidon::{Domain, Hash};
 
 pub const ARITY: usize = 4;
@@ -24,6 +29,473 @@ pub type Tree<T, const H: usize> = dusk_merkle::Tree<Item<T>, H, ARITY>;
 /// An alias for an opening of a tree containing `Item<T>`.
 pub type Opening<T, const H: usize> = dusk_merkle::Opening<Item<T>, H, ARITY>;
 
+/// An alias for a verified opening of a tree containing `Item<T>`.
+pub type VerifiedOpening<T, const H: usize> =
+    dusk_merkle::VerifiedOpening<Item<T>, H, ARITY>;
+
+/// The height of a [`NotesTree`].
+///
+/// With an arity of [`ARITY`], this gives a capacity of `4^17 = 2^34` notes.
+pub const NOTES_TREE_HEIGHT: usize = 17;
+
+/// The height of a [`NullifierTree`].
+///
+/// With an arity of [`ARITY`], this gives a capacity of `4^31 = 2^62`
+/// positions - the largest that fits in a `u64` - allowing for a nullifier to
+/// be placed at the position given by its lowest 62 bits.
+pub const NULLIFIER_TREE_HEIGHT: usize = 31;
+
+/// The hashes of the roots of empty subtrees of a poseidon tree, indexed by
+/// the number of levels below the root of the subtree.
+///
+/// The first entry is the hash of [`Item::EMPTY_SUBTREE`], and each following
+/// one is the hash of [`ARITY`] copies of the previous one - i.e. the root of
+/// a subtree whose leaves all hold the zero hash. The table covers subtrees
+/// up to the height of a [`NullifierTree`].
+///
+/// Only the first entry agrees with [`Item::EMPTY_SUBTREE`]. Trees in this
+/// crate represent an empty subtree by the zero hash whatever its height,
+/// rather than by the matching entry of this table: an empty subtree is then
+/// never hashed, which is what makes sparse trees of this height practical,
+/// and changing it would change the root of every existing tree. The table is
+/// instead meant for interoperating with implementations that hash zero
+/// leaves all the way up - e.g. for checking the root of a tree whose leaves
+/// are all the zero hash without computing it.
+///
+/// [`Item::EMPTY_SUBTREE`]: Aggregate::EMPTY_SUBTREE
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use poseidon_merkle::{Tree, EMPTY_ROOTS};
+///
+/// let mut tree = Tree::<(), 2>::new();
+/// for position in 0..tree.capacity() {
+///     tree.insert(position, BlsScalar::zero());
+/// }
+/// assert_eq!(tree.root().hash, EMPTY_ROOTS[2]);
+/// ```
+pub const EMPTY_ROOTS: [BlsScalar; NULLIFIER_TREE_HEIGHT + 1] = [
+    BlsScalar::from_raw([
+        0x0000_0000_0000_0000,
+        0x0000_0000_0000_0000,
+        0x0000_0000_0000_0000,
+        0x0000_0000_0000_0000,
+    ]),
+    BlsScalar::from_raw([
+        0xc005_03e4_38df_04d8,
+        0x9c78_dbec_96ff_494d,
+        0x72e6_4897_75f1_dd9b,
+        0x51a3_5252_421a_ec5d,
+    ]),
+    BlsScalar::from_raw([
+        0x58f2_639e_3a6c_6837,
+        0xaa47_d290_0b5e_9864,
+        0x1eb4_c044_030b_550e,
+        0x2498_a0f5_a0b9_cddb,
+    ]),
+    BlsScalar::from_raw([
+        0xa195_e07c_2d56_b99f,
+        0x1863_4cda_1faf_69cd,
+        0xf474_27d9_02e3_81be,
+        0x47ab_2a47_e04d_598b,
+    ]),
+    BlsScalar::from_raw([
+        0x709c_6a0a_6aa9_0b5a,
+        0x572e_5882_12b6_766b,
+        0x01a4_0afa_4e68_2dd2,
+        0x6c7f_0db3_03aa_96f1,
+    ]),
+    BlsScalar::from_raw([
+        0xf11e_ff28_1700_48a6,
+        0xeaba_dae3_73ed_e91f,
+        0x36f7_476a_27f4_1982,
+        0x00ea_81ce_2c14_e313,
+    ]),
+    BlsScalar::from_raw([
+        0x19ee_44f8_6a1f_6f85,
+        0xa953_d24d_4463_9a06,
+        0x3f9a_4b25_92cf_ee67,
+        0x055d_1f99_2afb_7abb,
+    ]),
+    BlsScalar::from_raw([
+        0xc0ce_bef9_3258_5a1e,
+        0x27cf_1d5f_013b_0d74,
+        0xb9b9_a60e_7825_d1de,
+        0x2772_0e60_abaa_4138,
+    ]),
+    BlsScalar::from_raw([
+        0xd6d9_d39d_32b0_a4a0,
+        0x94e7_ed7e_76ad_98f8,
+        0xcfb9_79b8_4355_a9e2,
+        0x14be_e71f_a7e6_d465,
+    ]),
+    BlsScalar::from_raw([
+        0xf7ed_dbf2_ae52_ace0,
+        0x559e_1a03_d55d_6c8f,
+        0x5fc8_4707_10fb_887e,
+        0x6860_0b02_4c34_b38e,
+    ]),
+    BlsScalar::from_raw([
+        0x37d6_e77e_2d1b_277d,
+        0x0674_7573_1133_12ed,
+        0x9382_b633_6d3a_e9e1,
+        0x123d_224a_9556_22f3,
+    ]),
+    BlsScalar::from_raw([
+        0x0385_f7ad_5e80_aae2,
+        0x54cf_2d27_e09a_badf,
+        0x9209_5201_3c97_5535,
+        0x019e_d0ab_48d5_0ddf,
+    ]),
+    BlsScalar::from_raw([
+        0xf37c_5814_3da3_4eaa,
+        0x0097_dd49_10f1_3b66,
+        0xb989_9b4d_6c9b_5bdc,
+        0x27a4_214d_0bb4_5434,
+    ]),
+    BlsScalar::from_raw([
+        0x5ea1_a6d3_6cda_15fc,
+        0x9023_f9d9_4426_f51b,
+        0x920b_f461_16a3_fa79,
+        0x6f0a_74ba_a7f1_3eea,
+    ]),
+    BlsScalar::from_raw([
+        0x3d26_9944_30c1_f34e,
+        0x92bf_cc26_ec0e_bb3c,
+        0x9427_d319_aca8_bf1f,
+        0x2ad2_3e44_7ac4_0ee5,
+    ]),
+    BlsScalar::from_raw([
+        0x7e2e_6aeb_2c7a_5f9c,
+        0x08e1_dde2_7500_895e,
+        0xb629_198f_7e05_8e88,
+        0x60b9_c523_592a_c84d,
+    ]),
+    BlsScalar::from_raw([
+        0x9de0_cc47_bd17_0d35,
+        0x8dc2_b57c_3967_79e3,
+        0x613d_4380_d2df_9977,
+        0x13c7_63cd_63a2_1b52,
+    ]),
+    BlsScalar::from_raw([
+        0x0b41_1ef1_7b81_a95b,
+        0xa7fb_cbde_381e_5647,
+        0x7ba2_e401_7770_338e,
+        0x16c8_9aae_40f5_27bf,
+    ]),
+    BlsScalar::from_raw([
+        0x1d19_101d_d973_b4a6,
+        0x9680_c9b4_f0be_78e9,
+        0xdeb7_578b_35e1_6c3c,
+        0x3b16_7409_18eb_4e98,
+    ]),
+    BlsScalar::from_raw([
+        0xc0e7_ae4c_9c6c_3baa,
+        0x0e2d_7b97_6821_a5f2,
+        0x52c7_e2e4_2991_845e,
+        0x461e_c337_0061_e2ae,
+    ]),
+    BlsScalar::from_raw([
+        0xd933_ae8a_6d3e_5c0b,
+        0xcc52_6881_9822_ac6c,
+        0xefa3_42bc_ce9f_260e,
+        0x5292_10dd_8836_4261,
+    ]),
+    BlsScalar::from_raw([
+        0x52ab_9a5d_6c32_ba29,
+        0x4e96_dd17_f43c_7e9b,
+        0x7095_c0a8_4c1a_c646,
+        0x6cb0_5f24_ea06_fe33,
+    ]),
+    BlsScalar::from_raw([
+        0x67b5_c400_9fca_0073,
+        0xead8_9192_8f52_5af7,
+        0x203d_9627_e126_cbfe,
+        0x0070_0d88_2f50_da2f,
+    ]),
+    BlsScalar::from_raw([
+        0x7600_2aa5_404d_fc00,
+        0x4b49_c31a_893b_cc0b,
+        0xfa1b_2630_68df_83bc,
+        0x1dbd_cf27_f4a2_0c3b,
+    ]),
+    BlsScalar::from_raw([
+        0x8e5f_6e58_9395_8365,
+        0x7708_c4a1_774c_ddf4,
+        0xf71a_c977_ca57_4487,
+        0x4e3e_c283_b8a9_de3d,
+    ]),
+    BlsScalar::from_raw([
+        0x17dc_e2a8_519a_8fe5,
+        0x51a4_51d6_fb4e_eeac,
+        0x6db8_9a31_767b_f62d,
+        0x076d_e262_741b_be07,
+    ]),
+    BlsScalar::from_raw([
+        0xb0e0_b875_6f15_f09a,
+        0x4b9f_9268_0b60_761f,
+        0x066a_8c04_6268_ea1d,
+        0x4e6c_3329_c452_b923,
+    ]),
+    BlsScalar::from_raw([
+        0xe083_3b18_cb22_f0f6,
+        0x3016_adf1_0d32_5c37,
+        0xaa41_3beb_7f63_9739,
+        0x6f0e_66e9_6bc9_f829,
+    ]),
+    BlsScalar::from_raw([
+        0xcbf7_ceb1_6c89_01bc,
+        0x4597_dd03_42cc_3c4c,
+        0xe52b_37da_dbd3_c2d2,
+        0x4032_d908_927d_2be8,
+    ]),
+    BlsScalar::from_raw([
+        0x5f4a_ca13_4e4d_0904,
+        0xfbb0_936a_93b1_a1d5,
+        0x14df_78d7_4149_0a84,
+        0x35b2_8f13_82a6_8ef8,
+    ]),
+    BlsScalar::from_raw([
+        0x4b9b_39c2_50d2_4d51,
+        0xd86c_a4ca_6c45_2061,
+        0xcf13_f088_8f81_f3c2,
+        0x45bd_3932_ab74_e3af,
+    ]),
+    BlsScalar::from_raw([
+        0x1958_519a_b1fb_6e4f,
+        0x0984_4c95_5f5f_3f1c,
+        0x43ff_1c6c_1a04_e53a,
+        0x0149_7124_acd6_c82d,
+    ]),
+];
+
+/// A tree of notes, with a height of [`NOTES_TREE_HEIGHT`].
+///
+/// Provers and verifiers should both use this alias, and its
+/// [`NotesOpening`], to ensure they agree on the parameters of the tree.
+///
+/// Empty subtrees of every height are represented by a zero hash, meaning the
+/// zero hash table of the tree is [`Item::EMPTY_SUBTREE`] at every level.
+///
+/// [`Item::EMPTY_SUBTREE`]: Aggregate::EMPTY_SUBTREE
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use dusk_merkle::Aggregate;
+/// use poseidon_merkle::{Item, NotesOpening, NotesTree, ARITY};
+///
+/// let mut tree = NotesTree::<()>::new();
+/// assert_eq!(*tree.root(), <Item<()> as Aggregate<ARITY>>::EMPTY_SUBTREE);
+///
+/// tree.insert(42, BlsScalar::from(42));
+/// let opening: NotesOpening<()> = tree.opening(42).unwrap();
+/// assert!(opening.verify(BlsScalar::from(42)));
+/// ```
+pub type NotesTree<T> = Tree<T, NOTES_TREE_HEIGHT>;
+
+/// An opening of a [`NotesTree`].
+pub type NotesOpening<T> = Opening<T, NOTES_TREE_HEIGHT>;
+
+/// A tree of nullifiers, with a height of [`NULLIFIER_TREE_HEIGHT`].
+///
+/// As with the [`NotesTree`], empty subtrees of every height are represented
+/// by a zero hash.
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use poseidon_merkle::{NullifierOpening, NullifierTree};
+///
+/// let mut tree = NullifierTree::<()>::new();
+/// assert_eq!(tree.capacity(), 1 << 62);
+///
+/// let nullifier = BlsScalar::from(0xdead_beef_dead_beef);
+/// let position = nullifier.reduce().0[0] % tree.capacity();
+///
+/// tree.insert(position, nullifier);
+/// let opening: NullifierOpening<()> = tree.opening(position).unwrap();
+/// assert!(opening.verify(nullifier));
+/// ```
+pub type NullifierTree<T> = Tree<T, NULLIFIER_TREE_HEIGHT>;
+
+/// An opening of a [`NullifierTree`].
+pub type NullifierOpening<T> = Opening<T, NULLIFIER_TREE_HEIGHT>;
+
+/// Returns the hash of a leaf composed of the given `scalars`.
+///
+/// The hash is computed using [`Domain::Other`], which is the domain expected
+/// for the leaves of a poseidon tree.
+#[must_use]
+pub fn leaf_hash(scalars: &[BlsScalar]) -> BlsScalar {
+    Hash::digest(Domain::Other, scalars)[0]
+}
+
+/// Returns the hash of a leaf composed of the given `scalars`, tagged with the
+/// given domain `tag`.
+///
+/// Tags allow for multiple kinds of leaves - e.g. different types of notes -
+/// to share a tree, while ensuring a leaf of one kind can never be passed for
+/// a leaf of another. The hash is computed using [`Domain::Other`], with the
+/// tag absorbed as the first input, followed by the scalars:
+///
+/// ```text
+/// hash = poseidon(Domain::Other, [tag, scalars[0], .., scalars[n - 1]])
+/// ```
+///
+/// This means the hash of a tagged leaf is the [`leaf_hash`] of the scalars
+/// prefixed by the tag. For the tags to be meaningful a tree should
+/// therefore either tag all of its leaves, or none.
+///
+/// The hash can be computed in-circuit using `zk::tagged_leaf_gadget`,
+/// behind the `zk` feature.
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use poseidon_merkle::{leaf_hash, tagged_leaf_hash};
+///
+/// let scalars = [BlsScalar::from(42)];
+///
+/// let tagged = tagged_leaf_hash(1, &scalars);
+/// assert_ne!(tagged, tagged_leaf_hash(2, &scalars));
+/// assert_eq!(tagged, leaf_hash(&[BlsScalar::from(1), scalars[0]]));
+/// ```
+#[must_use]
+pub fn tagged_leaf_hash(tag: u64, scalars: &[BlsScalar]) -> BlsScalar {
+    let tag = [BlsScalar::from(tag)];
+
+    let mut hash = Hash::new(Domain::Other);
+    hash.update(&tag);
+    hash.update(scalars);
+    hash.finalize()[0]
+}
+
+/// Verify the serialized opening `proof` of a tree of height `H` is for the
+/// leaf with the given `leaf` hash and leads to the given `root`, in a single
+/// pass over the bytes and without allocating.
+///
+/// The items of the opening are deserialized strictly, as with
+/// [`Opening::from_slice`]. See [`dusk_merkle::Opening::verify_raw`] for
+/// details.
+///
+/// [`Opening::from_slice`]: dusk_merkle::Opening::from_slice
+///
+/// # Errors
+///
+/// Will return an [`OpeningError`] describing which part of the opening is
+/// malformed.
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use dusk_bytes::Serializable;
+/// use poseidon_merkle::{verify_raw, Tree};
+///
+/// let mut tree = Tree::<(), 17>::new();
+/// tree.insert(42, BlsScalar::from(42));
+///
+/// let root = tree.root().to_bytes();
+/// let proof = tree.opening(42).unwrap().to_var_bytes();
+///
+/// let leaf = BlsScalar::from(42).to_bytes();
+/// assert_eq!(verify_raw::<17>(&root, &proof, &leaf), Ok(true));
+///
+/// let leaf = BlsScalar::from(43).to_bytes();
+/// assert_eq!(verify_raw::<17>(&root, &proof, &leaf), Ok(false));
+/// ```
+pub fn verify_raw<const H: usize>(
+    root: &[u8; 32],
+    proof: &[u8],
+    leaf: &[u8; 32],
+) -> Result<bool, OpeningError> {
+    Opening::<(), H>::verify_raw(root, proof, leaf)
+}
+
+/// Extension trait allowing for raw data to be inserted in a poseidon [`Tree`],
+/// hashing it to produce the leaf.
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use poseidon_merkle::{leaf_hash, InsertData, Item, Tree};
+///
+/// let mut tree = Tree::<(), 17>::new();
+///
+/// let scalars = [BlsScalar::from(42), BlsScalar::from(43)];
+/// tree.insert_data(42, &scalars, ());
+///
+/// let opening = tree.opening(42).unwrap();
+/// assert!(opening.verify(Item::new(leaf_hash(&scalars), ())));
+/// ```
+pub trait InsertData<T> {
+    /// Insert a leaf at the given `position`, with its hash computed from the
+    /// given `scalars` using [`leaf_hash`] and carrying the given `data`.
+    ///
+    /// # Panics
+    /// If `position >= capacity`.
+    fn insert_data(&mut self, position: u64, scalars: &[BlsScalar], data: T);
+}
+
+impl<T, const H: usize> InsertData<T> for Tree<T, H>
+where
+    T: Aggregate<ARITY>,
+{
+    fn insert_data(&mut self, position: u64, scalars: &[BlsScalar], data: T) {
+        self.insert(position, Item::from_data(scalars, data));
+    }
+}
+
+/// Verify an opening of a poseidon tree against the hash of its leaf only.
+///
+/// [`Opening::verify`] requires the leaf to be passed as an [`Item`], carrying
+/// the same data as the one in the tree. Verifiers often only know the hash
+/// of the leaf, in which case only the chain of hashes can be checked: the
+/// data carried by the items of the opening is ignored, and must not be
+/// relied upon.
+///
+/// [`Opening::verify`]: dusk_merkle::Opening::verify
+///
+/// # Example
+/// ```rust
+/// use dusk_bls12_381::BlsScalar;
+/// use poseidon_merkle::{leaf_hash, InsertData, Tree, VerifyLeafHash};
+///
+/// let mut tree = Tree::<(), 17>::new();
+///
+/// let scalars = [BlsScalar::from(42)];
+/// tree.insert_data(42, &scalars, ());
+///
+/// let opening = tree.opening(42).unwrap();
+/// assert!(opening.verify_leaf_hash(leaf_hash(&scalars)));
+/// assert!(!opening.verify_leaf_hash(leaf_hash(&[BlsScalar::from(43)])));
+/// ```
+pub trait VerifyLeafHash {
+    /// Verify the given `hash` is the hash of the leaf of the opening, and
+    /// that the hashes of the opening lead up to the hash of its root.
+    fn verify_leaf_hash(&self, hash: BlsScalar) -> bool;
+}
+
+impl<T, const H: usize, const A: usize> VerifyLeafHash
+    for dusk_merkle::Opening<Item<T>, H, A>
+where
+    T: Aggregate<A> + Clone,
+{
+    fn verify_leaf_hash(&self, hash: BlsScalar) -> bool {
+        let mut hash = hash;
+
+        for h in (0..H).rev() {
+            let level = self.branch()[h].each_ref().map(|item| item.hash);
+            if hash != level[self.positions()[h]] {
+                return false;
+            }
+            hash = Hash::digest(merkle_domain::<A>(), &level)[0];
+        }
+
+        self.root().hash == hash
+    }
+}
+
 /// A type that wraps a piece of data `T` together with a poseidon hash - i.e. a
 /// [`BlsScalar`].
 ///
@@ -122,22 +594,34 @@ impl<T> Item<T> {
     pub fn new(hash: BlsScalar, data: T) -> Self {
         Self { hash, data }
     }
+
+    /// Create a new Item for the merkle tree, with its hash computed from the
+    /// given `scalars` using [`leaf_hash`].
+    pub fn from_data(scalars: &[BlsScalar], data: T) -> Self {
+        Self::new(leaf_hash(scalars), data)
+    }
+
+    /// Create a new Item for the merkle tree, with its hash computed from the
+    /// given `tag` and `scalars` using [`tagged_leaf_hash`].
+    pub fn from_tagged_data(tag: u64, scalars: &[BlsScalar], data: T) -> Self {
+        Self::new(tagged_leaf_hash(tag, scalars), data)
+    }
 }
 
-impl<T> Aggregate<ARITY> for Item<T>
+impl<T, const A: usize> Aggregate<A> for Item<T>
 where
-    T: Aggregate<ARITY>,
+    T: Aggregate<A>,
 {
     const EMPTY_SUBTREE: Self = Item {
         hash: BlsScalar::zero(),
         data: T::EMPTY_SUBTREE,
     };
 
-    fn aggregate(items: [&Self; ARITY]) -> Self {
+    fn aggregate(items: [&Self; A]) -> Self {
         let empty = &T::EMPTY_SUBTREE;
 
-        let mut level_hashes = [BlsScalar::zero(); ARITY];
-        let mut level_data = [empty; ARITY];
+        let mut level_hashes = [BlsScalar::zero(); A];
+        let mut level_data = [empty; A];
 
         // grab hashes and data
         items.into_iter().enumerate().for_each(|(i, item)| {
@@ -148,12 +632,106 @@ where
         // create new aggregated item with the hash being the poseidon hash of
         // the previous level
         Item {
-            hash: Hash::digest(Domain::Merkle4, &level_hashes)[0],
+            hash: Hash::digest(merkle_domain::<A>(), &level_hashes)[0],
             data: T::aggregate(level_data),
         }
     }
 }
 
+/// The domain used for hashing a level of a tree with arity `A`.
+///
+/// Only trees with an arity of 2 or 4 are supported, since these are the only
+/// arities with a dedicated merkle domain. Falling back to [`Domain::Other`]
+/// would let the hash of a level collide with the hash of a leaf.
+struct MerkleDomain<const A: usize>;
+
+impl<const A: usize> MerkleDomain<A> {
+    const DOMAIN: Domain = match A {
+        2 => Domain::Merkle2,
+        4 => Domain::Merkle4,
+        _ => panic!("Poseidon trees only support an arity of 2 or 4"),
+    };
+}
+
+/// Returns the domain used for hashing a level of a tree with arity `A`.
+///
+/// Using it with an arity other than 2 or 4 fails to compile.
+pub(crate) const fn merkle_domain<const A: usize>() -> Domain {
+    MerkleDomain::<A>::DOMAIN
+}
+
+impl<T> AsRef<BlsScalar> for Item<T> {
+    fn as_ref(&self) -> &BlsScalar {
+        &self.hash
+    }
+}
+
+impl From<BlsScalar> for Item<()> {
+    fn from(hash: BlsScalar) -> Self {
+        Self::hash_only(hash)
+    }
+}
+
+impl Item<()> {
+    /// Create a new Item carrying only the given `hash`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use dusk_bls12_381::BlsScalar;
+    /// use poseidon_merkle::{leaf_hash, Item, Tree};
+    ///
+    /// let hash = leaf_hash(&[BlsScalar::from(42)]);
+    ///
+    /// let mut tree = Tree::<(), 17>::new();
+    /// tree.insert(42, hash);
+    ///
+    /// let opening = tree.opening(42).unwrap();
+    /// assert!(opening.verify(Item::hash_only(hash)));
+    /// ```
+    #[must_use]
+    pub const fn hash_only(hash: BlsScalar) -> Self {
+        Self { hash, data: () }
+    }
+
+    /// Deserialize an item from the given bytes, reducing the encoded scalar
+    /// modulo the field order instead of rejecting it.
+    ///
+    /// Unlike [`Serializable::from_bytes`], this accepts non-canonical
+    /// encodings, meaning different byte representations may produce the same
+    /// item. It must not be used on data that is hashed or compared in its
+    /// serialized form, such as consensus data.
+    ///
+    /// # Example
+    /// ```rust
+    /// use dusk_bls12_381::BlsScalar;
+    /// use dusk_bytes::Serializable;
+    /// use poseidon_merkle::Item;
+    ///
+    /// // the encoding of the field order plus one is not canonical
+    /// let mut bytes = (-BlsScalar::one()).to_bytes();
+    /// bytes[0] += 2;
+    ///
+    /// assert!(Item::<()>::from_bytes(&bytes).is_err());
+    /// assert_eq!(Item::from_bytes_lenient(&bytes).hash, BlsScalar::one());
+    /// ```
+    #[must_use]
+    pub fn from_bytes_lenient(buf: &[u8; 32]) -> Self {
+        let mut wide = [0u8; 64];
+        wide[..32].copy_from_slice(buf);
+
+        Item {
+            hash: BlsScalar::from_bytes_wide(&wide),
+            data: (),
+        }
+    }
+}
+
+/// Items are deserialized strictly: an encoding of a scalar that is not
+/// canonical - i.e. not smaller than the field order - is rejected, ensuring
+/// every item has exactly one byte representation. This is also the case for
+/// openings deserialized with [`Opening::from_slice`].
+///
+/// [`Opening::from_slice`]: dusk_merkle::Opening::from_slice
 impl Serializable<32> for Item<()> {
     type Error = <BlsScalar as Serializable<32>>::Error;
 
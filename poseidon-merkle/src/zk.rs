@@ -4,26 +4,133 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use crate::{Opening, ARITY};
+use crate::{merkle_domain, Item, Opening, Tree, VerifiedOpening, ARITY};
+
+use alloc::vec::Vec;
 
 use dusk_merkle::Aggregate;
-use dusk_plonk::prelude::{BlsScalar, Composer, Constraint, Witness};
-use dusk_poseidon::{Domain, HashGadget};
+use dusk_plonk::prelude::{
+    BlsScalar, Circuit, Compiler, Composer, Constraint, Error, Proof, Prover,
+    PublicParameters, Verifier, Witness,
+};
+use dusk_poseidon::{Domain, Hash, HashGadget};
+use rand_core::{CryptoRng, RngCore};
 
 /// Builds the gadget for the poseidon opening and returns the computed
 /// root.
-pub fn opening_gadget<T, const H: usize>(
+///
+/// The gadget works for trees with an arity `A` of 2 or 4, hashing each level
+/// with the same domain as [`Item`] does natively.
+pub fn opening_gadget<T, const H: usize, const A: usize>(
     composer: &mut Composer,
-    opening: &Opening<T, H>,
+    opening: &dusk_merkle::Opening<Item<T>, H, A>,
     leaf: Witness,
 ) -> Witness
 where
-    T: Clone + Aggregate<ARITY>,
+    T: Clone + Aggregate<A>,
+{
+    opening_gadget_with_bits(composer, opening, leaf).0
+}
+
+/// Builds the gadget for the poseidon opening, asserting the computed root is
+/// equal to the given `root` witness.
+///
+/// This allows for the root to be kept private, or to be bound to a public
+/// input in any way the circuit sees fit.
+pub fn opening_gadget_private_root<T, const H: usize, const A: usize>(
+    composer: &mut Composer,
+    opening: &dusk_merkle::Opening<Item<T>, H, A>,
+    leaf: Witness,
+    root: Witness,
+) where
+    T: Clone + Aggregate<A>,
+{
+    let computed_root = opening_gadget(composer, opening, leaf);
+    composer.assert_equal(computed_root, root);
+}
+
+/// Builds the gadget for the poseidon opening, asserting the computed root is
+/// equal to the given `root` witness, and returns the bits encoding the
+/// position of the leaf.
+///
+/// The bits are returned level by level, in the same order as
+/// [`Opening::positions`], with `bits[h][i]` being one if the path goes
+/// through the `i`th child at height `h`, and zero otherwise. Each bit is
+/// constrained to be boolean, and exactly one bit is set per level.
+///
+/// [`Opening::positions`]: dusk_merkle::Opening::positions
+pub fn opening_gadget_private_root_with_position<
+    T,
+    const H: usize,
+    const A: usize,
+>(
+    composer: &mut Composer,
+    opening: &dusk_merkle::Opening<Item<T>, H, A>,
+    leaf: Witness,
+    root: Witness,
+) -> [[Witness; A]; H]
+where
+    T: Clone + Aggregate<A>,
+{
+    let (computed_root, pos_bits) =
+        opening_gadget_with_bits(composer, opening, leaf);
+    composer.assert_equal(computed_root, root);
+    pos_bits
+}
+
+/// Builds the gadget for the poseidon opening of the leaf at the given
+/// `position`, and returns the computed root.
+///
+/// The position is decomposed into its digits in base `A`, one per level,
+/// and the selector bits of each level are constrained to encode the digit.
+/// Since there is exactly one bit set per level, this also constrains the
+/// position to be within the capacity of the tree, meaning a proof can't be
+/// produced for a position other than the one of the path.
+pub fn opening_gadget_with_position<T, const H: usize, const A: usize>(
+    composer: &mut Composer,
+    opening: &dusk_merkle::Opening<Item<T>, H, A>,
+    leaf: Witness,
+    position: Witness,
+) -> Witness
+where
+    T: Clone + Aggregate<A>,
+{
+    let (computed_root, pos_bits) =
+        opening_gadget_with_bits(composer, opening, leaf);
+
+    // accumulate the digits from the root down, weighted by their place
+    // value: position = sum(i * A^(H - 1 - h) * pos_bits[h][i])
+    let arity = BlsScalar::from(A as u64);
+    let mut weight = BlsScalar::one();
+    let mut sum = Composer::ZERO;
+    for bits in pos_bits.iter().rev() {
+        for (i, bit) in bits.iter().enumerate().skip(1) {
+            let coefficient = weight * BlsScalar::from(i as u64);
+            let constraint =
+                Constraint::new().left(coefficient).a(*bit).right(1).b(sum);
+            sum = composer.gate_add(constraint);
+        }
+        weight *= arity;
+    }
+    composer.assert_equal(sum, position);
+
+    computed_root
+}
+
+/// Builds the gadget for the poseidon opening and returns the computed root,
+/// together with the bits encoding the position of the leaf.
+fn opening_gadget_with_bits<T, const H: usize, const A: usize>(
+    composer: &mut Composer,
+    opening: &dusk_merkle::Opening<Item<T>, H, A>,
+    leaf: Witness,
+) -> (Witness, [[Witness; A]; H])
+where
+    T: Clone + Aggregate<A>,
 {
     // append the siblings and position to the circuit
-    let mut level_witnesses = [[Composer::ZERO; ARITY]; H];
+    let mut level_witnesses = [[Composer::ZERO; A]; H];
     // if i == position: pos_bits[i] = 1 else: pos_bits[i] = 0
-    let mut pos_bits = [[Composer::ZERO; ARITY]; H];
+    let mut pos_bits = [[Composer::ZERO; A]; H];
     for h in (0..H).rev() {
         let level = &opening.branch()[h];
         for (i, item) in level.iter().enumerate() {
@@ -40,24 +147,35 @@ where
 
         // ensure there is *exactly* one bit turned on in the array, by
         // checking that the sum of all position bits equals 1
-        let constraint = Constraint::new()
-            .left(1)
-            .a(pos_bits[h][0])
-            .right(1)
-            .b(pos_bits[h][1])
-            .fourth(1)
-            .d(pos_bits[h][2]);
-        let mut sum = composer.gate_add(constraint);
-        let constraint =
-            Constraint::new().left(1).a(sum).right(1).b(pos_bits[h][3]);
-        sum = composer.gate_add(constraint);
+        let sum = if A == 4 {
+            // keep the gate layout of the circuits built for an arity of 4
+            let constraint = Constraint::new()
+                .left(1)
+                .a(pos_bits[h][0])
+                .right(1)
+                .b(pos_bits[h][1])
+                .fourth(1)
+                .d(pos_bits[h][2]);
+            let sum = composer.gate_add(constraint);
+            let constraint =
+                Constraint::new().left(1).a(sum).right(1).b(pos_bits[h][3]);
+            composer.gate_add(constraint)
+        } else {
+            let mut sum = pos_bits[h][0];
+            for bit in &pos_bits[h][1..] {
+                let constraint =
+                    Constraint::new().left(1).a(sum).right(1).b(*bit);
+                sum = composer.gate_add(constraint);
+            }
+            sum
+        };
         composer.assert_equal_constant(sum, BlsScalar::one(), None);
     }
 
     // keep track of the computed hash along our path with needle
     let mut needle = leaf;
     for h in (0..H).rev() {
-        for i in 0..ARITY {
+        for i in 0..A {
             // assert that:
             // pos_bits[h][i] * level_hash[i] = pos_bits[h][i] * needle
             let constraint = Constraint::new()
@@ -73,11 +191,291 @@ where
         }
 
         // hash the current level
-        needle =
-            HashGadget::digest(composer, Domain::Merkle4, &level_witnesses[h])
-                [0];
+        let domain = merkle_domain::<A>();
+        needle = HashGadget::digest(composer, domain, &level_witnesses[h])[0];
     }
 
     // return the computed root as a witness in the circuit
-    needle
+    (needle, pos_bits)
+}
+
+/// Replays the computation of [`opening_gadget`] natively for the given
+/// `opening` and `leaf` hash, returning the values it constrains at each
+/// level.
+///
+/// This allows for finding out why a circuit built with the gadget is not
+/// satisfied without going through the proof system.
+#[must_use]
+pub fn opening_gadget_trace<T, const H: usize, const A: usize>(
+    opening: &dusk_merkle::Opening<Item<T>, H, A>,
+    leaf: BlsScalar,
+) -> OpeningTrace
+where
+    T: Clone + Aggregate<A>,
+{
+    let mut levels = Vec::with_capacity(H);
+
+    let mut needle = leaf;
+    for h in (0..H).rev() {
+        let level = opening.branch()[h].each_ref().map(|item| item.hash);
+        let position = opening.positions()[h];
+        let digest = Hash::digest(merkle_domain::<A>(), &level)[0];
+
+        levels.push(LevelTrace {
+            height: h,
+            position,
+            needle,
+            selected: level[position],
+            digest,
+        });
+        needle = digest;
+    }
+
+    OpeningTrace {
+        levels,
+        root: opening.root().hash,
+    }
+}
+
+/// The values constrained by [`opening_gadget`] for an opening, as computed
+/// by [`opening_gadget_trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpeningTrace {
+    levels: Vec<LevelTrace>,
+    root: BlsScalar,
+}
+
+impl OpeningTrace {
+    /// Returns the traces of the levels of the opening, from the leaf up to
+    /// the root.
+    #[must_use]
+    pub fn levels(&self) -> &[LevelTrace] {
+        &self.levels
+    }
+
+    /// Returns the root computed by the gadget.
+    #[must_use]
+    pub fn computed_root(&self) -> BlsScalar {
+        self.levels.last().map_or(self.root, |level| level.digest)
+    }
+
+    /// Returns the first level, from the leaf up, whose selected hash doesn't
+    /// match the hash computed for it, if any.
+    #[must_use]
+    pub fn divergence(&self) -> Option<&LevelTrace> {
+        self.levels.iter().find(|level| !level.is_consistent())
+    }
+
+    /// Returns `true` if all the levels are consistent and the computed root
+    /// is the root of the opening - i.e. if a circuit built with the gadget
+    /// would be satisfied.
+    #[must_use]
+    pub fn is_satisfied(&self) -> bool {
+        self.divergence().is_none() && self.computed_root() == self.root
+    }
+}
+
+/// The values constrained by [`opening_gadget`] at one level of an opening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelTrace {
+    /// The height of the level in the branch, the level just below the root
+    /// being at height zero.
+    pub height: usize,
+    /// The position of the path within the level.
+    pub position: usize,
+    /// The hash computed from the levels below - or the leaf.
+    pub needle: BlsScalar,
+    /// The hash stored in the branch at the position of the path.
+    pub selected: BlsScalar,
+    /// The hash of the whole level, passed as the needle to the level above.
+    pub digest: BlsScalar,
+}
+
+impl LevelTrace {
+    /// Returns `true` if the needle matches the selected hash.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.needle == self.selected
+    }
+}
+
+/// Appends the hash of a leaf composed of the given `scalars` and tagged with
+/// the given `tag` to the circuit, returning it.
+///
+/// The hash is computed following the scheme of [`tagged_leaf_hash`]. The
+/// gadget doesn't constrain the tag itself: circuits accepting a single kind
+/// of leaf should assert it is equal to the expected constant, while circuits
+/// accepting several kinds should constrain it to the allowed set.
+///
+/// [`tagged_leaf_hash`]: crate::tagged_leaf_hash
+pub fn tagged_leaf_gadget(
+    composer: &mut Composer,
+    tag: Witness,
+    scalars: &[Witness],
+) -> Witness {
+    let mut inputs = Vec::with_capacity(1 + scalars.len());
+    inputs.push(tag);
+    inputs.extend_from_slice(scalars);
+
+    HashGadget::digest(composer, Domain::Other, &inputs)[0]
+}
+
+/// The width of the state of the poseidon permutation.
+const PERMUTATION_WIDTH: usize = 5;
+
+/// The number of gates appended by a poseidon permutation: the round
+/// constants added before the first round, then five gates for each full
+/// round's s-boxes and matrix multiplication, and the same with a single
+/// s-box for each partial round.
+const PERMUTATION_GATES: usize = {
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 60;
+
+    let s_box = 3;
+    let matrix = 2 * PERMUTATION_WIDTH;
+
+    PERMUTATION_WIDTH
+        + FULL_ROUNDS * (PERMUTATION_WIDTH * s_box + matrix)
+        + PARTIAL_ROUNDS * (s_box + matrix)
+};
+
+/// Returns the number of gates [`opening_gadget`] appends to a circuit for an
+/// opening of a tree with height `H` and arity `A`.
+///
+/// This allows for budgeting the capacity of a circuit before compiling it.
+#[must_use]
+pub const fn opening_gadget_gates<const H: usize, const A: usize>() -> usize {
+    // each position bit is constrained to be boolean, and their sum to be one
+    let sum = if A == 4 { 2 } else { A - 1 };
+    let position = A + sum + 1;
+
+    // each item is multiplied by its bit and compared to the needle
+    let selection = 3 * A;
+
+    // the domain tag is appended as a constant, each item is absorbed, and
+    // the state is permuted once when squeezing
+    let hash = 1 + A + PERMUTATION_GATES;
+
+    H * (position + selection + hash)
+}
+
+/// A circuit proving knowledge of a leaf and its opening in a poseidon tree
+/// with a given root.
+///
+/// The circuit has a single public input: the hash of the root of the tree,
+/// as returned by [`OpeningCircuit::public_inputs`].
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct OpeningCircuit<T, const H: usize> {
+    opening: Opening<T, H>,
+    leaf: Item<T>,
+}
+
+impl<T, const H: usize> Default for OpeningCircuit<T, H>
+where
+    T: Aggregate<ARITY> + Clone,
+{
+    fn default() -> Self {
+        let leaf = Item::<T>::EMPTY_SUBTREE;
+
+        let mut tree = Tree::<T, H>::new();
+        tree.insert(0, leaf.clone());
+        let Some(opening) = tree.opening(0) else {
+            unreachable!("There is a leaf at position 0");
+        };
+
+        Self { opening, leaf }
+    }
+}
+
+impl<T, const H: usize> OpeningCircuit<T, H>
+where
+    T: Aggregate<ARITY> + Clone,
+{
+    /// Create a new circuit for the given `opening` of the given `leaf`.
+    pub fn new(opening: Opening<T, H>, leaf: Item<T>) -> Self {
+        Self { opening, leaf }
+    }
+
+    /// Create a new circuit for an opening that has already been verified,
+    /// ensuring proofs are never attempted for invalid data.
+    pub fn from_verified(verified: VerifiedOpening<T, H>) -> Self {
+        let (opening, leaf) = verified.into_parts();
+        Self { opening, leaf }
+    }
+
+    /// Returns the trace of the circuit's opening gadget, as computed by
+    /// [`opening_gadget_trace`].
+    #[must_use]
+    pub fn trace(&self) -> OpeningTrace {
+        opening_gadget_trace(&self.opening, self.leaf.hash)
+    }
+
+    /// Compile the circuit, returning its prover and verifier.
+    ///
+    /// # Errors
+    /// Will return an [`Error`] if the public parameters are too small for the
+    /// circuit.
+    pub fn compile(
+        pp: &PublicParameters,
+        label: &[u8],
+    ) -> Result<(Prover, Verifier), Error> {
+        Compiler::compile::<Self>(pp, label)
+    }
+
+    /// Prove the opening with the given `prover`, returning the proof
+    /// together with the circuit's public inputs.
+    ///
+    /// # Errors
+    /// Will return an [`Error`] if the opening doesn't verify for the leaf,
+    /// or if the proof fails to be generated.
+    pub fn prove<R>(
+        &self,
+        prover: &Prover,
+        rng: &mut R,
+    ) -> Result<(Proof, Vec<BlsScalar>), Error>
+    where
+        R: RngCore + CryptoRng,
+    {
+        prover.prove(rng, self)
+    }
+
+    /// Verify a `proof` of an opening into a tree with the given `root` hash.
+    ///
+    /// # Errors
+    /// Will return an [`Error`] if the proof is invalid.
+    pub fn verify(
+        verifier: &Verifier,
+        proof: &Proof,
+        root: &BlsScalar,
+    ) -> Result<(), Error> {
+        verifier.verify(proof, &Self::public_inputs(root))
+    }
+
+    /// Returns the public inputs of the circuit for a tree with the given
+    /// `root` hash, in the order expected by the verifier.
+    #[must_use]
+    pub fn public_inputs(root: &BlsScalar) -> [BlsScalar; 1] {
+        [*root]
+    }
+}
+
+impl<T, const H: usize> Circuit for OpeningCircuit<T, H>
+where
+    T: Aggregate<ARITY> + Clone,
+{
+    fn circuit(&self, composer: &mut Composer) -> Result<(), Error> {
+        // append the leaf and opening gadget to the circuit
+        let leaf = composer.append_witness(self.leaf.hash);
+        let computed_root = opening_gadget(composer, &self.opening, leaf);
+
+        // append the public root as public input to the circuit
+        // and ensure it is equal to the computed root
+        let constraint = Constraint::new()
+            .left(-BlsScalar::one())
+            .a(computed_root)
+            .public(self.opening.root().hash);
+        composer.append_gate(constraint);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use dusk_merkle::Aggregate;
+use poseidon_merkle::{Item, Tree, ARITY, EMPTY_ROOTS, NULLIFIER_TREE_HEIGHT};
+
+#[test]
+fn empty_roots() {
+    assert_eq!(EMPTY_ROOTS.len(), NULLIFIER_TREE_HEIGHT + 1);
+    assert_eq!(
+        EMPTY_ROOTS[0],
+        <Item<()> as Aggregate<ARITY>>::EMPTY_SUBTREE.hash
+    );
+
+    // fold the empty subtree over each level
+    let mut root = <Item<()> as Aggregate<ARITY>>::EMPTY_SUBTREE;
+    for empty_root in EMPTY_ROOTS {
+        assert_eq!(empty_root, root.hash);
+        root = <Item<()> as Aggregate<ARITY>>::aggregate([&root; ARITY]);
+    }
+}
+
+#[test]
+fn empty_tree_root() {
+    // an empty tree is the empty subtree, and not the hash of empty leaves
+    let tree = Tree::<(), 2>::new();
+    assert_eq!(*tree.root(), <Item<()> as Aggregate<ARITY>>::EMPTY_SUBTREE);
+    assert_ne!(tree.root().hash, EMPTY_ROOTS[2]);
+}
@@ -4,8 +4,12 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use poseidon_merkle::zk::opening_gadget;
-use poseidon_merkle::{Item, Opening, Tree};
+use poseidon_merkle::zk::{
+    opening_gadget, opening_gadget_gates, opening_gadget_private_root,
+    opening_gadget_private_root_with_position, opening_gadget_trace,
+    opening_gadget_with_position, tagged_leaf_gadget, OpeningCircuit,
+};
+use poseidon_merkle::{tagged_leaf_hash, Item, Tree, VerifiedOpening};
 
 use dusk_plonk::prelude::*;
 use dusk_poseidon::{Domain, Hash};
@@ -23,44 +27,153 @@ const HEIGHT: usize = 17;
 
 type PoseidonItem = Item<()>;
 
-// Create a circuit for the opening
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
-struct OpeningCircuit {
-    opening: Opening<(), HEIGHT>,
+type PoseidonCircuit = OpeningCircuit<(), HEIGHT>;
+
+#[test]
+fn opening() {
+    let label = b"merkle opening";
+    let mut rng = StdRng::seed_from_u64(0xdea1);
+    let pp = PublicParameters::setup(1 << CAPACITY, &mut rng).unwrap();
+
+    let (prover, verifier) = PoseidonCircuit::compile(&pp, label)
+        .expect("Circuit should compile successfully");
+
+    let mut tree = Tree::new();
+    let mut leaf = PoseidonItem::new(BlsScalar::zero(), ());
+    let mut position = 0;
+    for _ in 0..100 {
+        let hash =
+            Hash::digest(Domain::Other, &[BlsScalar::random(&mut rng)])[0];
+        position = rng.next_u64() % tree.capacity();
+        leaf = PoseidonItem::new(hash, ());
+        tree.insert(position, leaf);
+    }
+    let opening = tree.opening(position).unwrap();
+    let verified = VerifiedOpening::new(opening, leaf, &tree.root())
+        .expect("The opening should verify");
+
+    let circuit = PoseidonCircuit::from_verified(verified);
+
+    let (proof, public_inputs) = circuit
+        .prove(&prover, &mut rng)
+        .expect("Proof generation should succeed");
+
+    let root = tree.root().hash;
+    assert_eq!(public_inputs, PoseidonCircuit::public_inputs(&root));
+
+    PoseidonCircuit::verify(&verifier, &proof, &root)
+        .expect("Proof verification should succeed");
+
+    let wrong_root = root + BlsScalar::one();
+    PoseidonCircuit::verify(&verifier, &proof, &wrong_root)
+        .expect_err("Proof verification should fail for a different root");
+}
+
+fn gadget_gates_with_arity<const H: usize, const A: usize>() {
+    let leaf = PoseidonItem::new(BlsScalar::zero(), ());
+
+    let mut tree = dusk_merkle::Tree::<PoseidonItem, H, A>::new();
+    tree.insert(0, leaf);
+    let opening = tree.opening(0).unwrap();
+
+    let mut composer = Composer::initialized();
+    let leaf = composer.append_witness(leaf.hash);
+    let gates = composer.constraints();
+    opening_gadget(&mut composer, &opening, leaf);
+
+    assert_eq!(
+        composer.constraints() - gates,
+        opening_gadget_gates::<H, A>(),
+        "The gates should match the ones appended by the gadget"
+    );
+}
+
+#[test]
+fn gadget_gates() {
+    const GATES: usize = opening_gadget_gates::<HEIGHT, 4>();
+    assert!(
+        GATES < 1 << CAPACITY,
+        "The gadget should fit in the circuit used for the opening test"
+    );
+
+    gadget_gates_with_arity::<HEIGHT, 4>();
+    gadget_gates_with_arity::<16, 2>();
+}
+
+#[test]
+fn tagged_leaf() {
+    let tag = 3;
+    let scalars = [BlsScalar::from(42), BlsScalar::from(43)];
+
+    let mut composer = Composer::initialized();
+    let tag_witness = composer.append_witness(BlsScalar::from(tag));
+    let scalar_witnesses = scalars.map(|s| composer.append_witness(s));
+
+    let hash =
+        tagged_leaf_gadget(&mut composer, tag_witness, &scalar_witnesses);
+    composer.assert_equal_constant(tag_witness, BlsScalar::from(tag), None);
+
+    assert_eq!(composer[hash], tagged_leaf_hash(tag, &scalars));
+}
+
+#[test]
+fn gadget_trace() {
+    let leaf = PoseidonItem::new(BlsScalar::from(42), ());
+
+    let mut tree = Tree::<(), HEIGHT>::new();
+    tree.insert(7, leaf);
+    tree.insert(21, PoseidonItem::new(BlsScalar::from(43), ()));
+    let opening = tree.opening(7).unwrap();
+
+    let trace = opening_gadget_trace(&opening, leaf.hash);
+    assert_eq!(trace.levels().len(), HEIGHT);
+    assert_eq!(trace.levels()[0].height, HEIGHT - 1);
+    assert_eq!(trace.levels()[0].position, 3);
+    assert_eq!(trace.divergence(), None);
+    assert!(trace.is_satisfied());
+
+    // the trace replays the values computed by the gadget
+    let mut composer = Composer::initialized();
+    let witness = composer.append_witness(leaf.hash);
+    let root = opening_gadget(&mut composer, &opening, witness);
+    assert_eq!(composer[root], trace.computed_root());
+
+    let circuit = PoseidonCircuit::new(opening.clone(), leaf);
+    assert_eq!(circuit.trace(), trace);
+
+    // a wrong leaf diverges at the bottom level
+    let trace = opening_gadget_trace(&opening, BlsScalar::from(43));
+    let divergence = trace.divergence().unwrap();
+    assert_eq!(divergence.height, HEIGHT - 1);
+    assert_eq!(divergence.selected, leaf.hash);
+    assert!(!trace.is_satisfied());
+}
+
+/// A circuit proving an opening of a tree with a non-default arity, using the
+/// opening gadget directly.
+#[derive(Debug, Clone)]
+struct ArityCircuit<const H: usize, const A: usize> {
+    opening: dusk_merkle::Opening<PoseidonItem, H, A>,
     leaf: PoseidonItem,
 }
 
-impl Default for OpeningCircuit {
+impl<const H: usize, const A: usize> Default for ArityCircuit<H, A> {
     fn default() -> Self {
-        let empty = Item {
-            hash: BlsScalar::zero(),
-            data: (),
-        };
-        let mut tree = Tree::new();
-        tree.insert(0, empty);
-        let opening = tree.opening(0).expect("There is a leaf at position 0");
-        Self {
-            opening,
-            leaf: empty,
-        }
-    }
-}
+        let leaf = PoseidonItem::new(BlsScalar::zero(), ());
+
+        let mut tree = dusk_merkle::Tree::<PoseidonItem, H, A>::new();
+        tree.insert(0, leaf);
+        let opening = tree.opening(0).unwrap();
 
-impl OpeningCircuit {
-    /// Create a new OpeningCircuit
-    pub fn new(opening: Opening<(), HEIGHT>, leaf: PoseidonItem) -> Self {
         Self { opening, leaf }
     }
 }
 
-impl Circuit for OpeningCircuit {
+impl<const H: usize, const A: usize> Circuit for ArityCircuit<H, A> {
     fn circuit(&self, composer: &mut Composer) -> Result<(), Error> {
-        // append the leaf and opening gadget to the circuit
         let leaf = composer.append_witness(self.leaf.hash);
         let computed_root = opening_gadget(composer, &self.opening, leaf);
 
-        // append the public root as public input to the circuit
-        // and ensure it is equal to the computed root
         let constraint = Constraint::new()
             .left(-BlsScalar::one())
             .a(computed_root)
@@ -71,16 +184,16 @@ impl Circuit for OpeningCircuit {
     }
 }
 
-#[test]
-fn opening() {
-    let label = b"merkle opening";
-    let mut rng = StdRng::seed_from_u64(0xdea1);
+fn opening_with_arity<const H: usize, const A: usize>() {
+    let label = b"merkle opening arity";
+    let mut rng = StdRng::seed_from_u64(0xa417);
     let pp = PublicParameters::setup(1 << CAPACITY, &mut rng).unwrap();
 
-    let (prover, verifier) = Compiler::compile::<OpeningCircuit>(&pp, label)
-        .expect("Circuit should compile successfully");
+    let (prover, verifier) =
+        Compiler::compile::<ArityCircuit<H, A>>(&pp, label)
+            .expect("Circuit should compile successfully");
 
-    let mut tree = Tree::new();
+    let mut tree = dusk_merkle::Tree::<PoseidonItem, H, A>::new();
     let mut leaf = PoseidonItem::new(BlsScalar::zero(), ());
     let mut position = 0;
     for _ in 0..100 {
@@ -88,18 +201,183 @@ fn opening() {
             Hash::digest(Domain::Other, &[BlsScalar::random(&mut rng)])[0];
         position = rng.next_u64() % tree.capacity();
         leaf = PoseidonItem::new(hash, ());
-        tree.insert(position as u64, leaf);
+        tree.insert(position, leaf);
+    }
+    let opening = tree.opening(position).unwrap();
+    assert!(opening.verify(leaf));
+    assert!(opening_gadget_trace(&opening, leaf.hash).is_satisfied());
+
+    let circuit = ArityCircuit { opening, leaf };
+    let (proof, public_inputs) = prover
+        .prove(&mut rng, &circuit)
+        .expect("Proof generation should succeed");
+
+    let root = tree.root().hash;
+    assert_eq!(public_inputs, [root]);
+
+    verifier
+        .verify(&proof, &[root])
+        .expect("Proof verification should succeed");
+    verifier
+        .verify(&proof, &[root + BlsScalar::one()])
+        .expect_err("Proof verification should fail for a different root");
+}
+
+#[test]
+fn opening_arity_2() {
+    opening_with_arity::<16, 2>();
+}
+
+/// A circuit binding the root passed to the opening gadget as a witness to a
+/// public input.
+#[derive(Debug, Clone)]
+struct PrivateRootCircuit {
+    opening: poseidon_merkle::Opening<(), HEIGHT>,
+    leaf: PoseidonItem,
+}
+
+impl Default for PrivateRootCircuit {
+    fn default() -> Self {
+        let leaf = PoseidonItem::new(BlsScalar::zero(), ());
+
+        let mut tree = Tree::<(), HEIGHT>::new();
+        tree.insert(0, leaf);
+        let opening = tree.opening(0).unwrap();
+
+        Self { opening, leaf }
+    }
+}
+
+impl Circuit for PrivateRootCircuit {
+    fn circuit(&self, composer: &mut Composer) -> Result<(), Error> {
+        let leaf = composer.append_witness(self.leaf.hash);
+        let root = composer.append_public(self.opening.root().hash);
+        opening_gadget_private_root(composer, &self.opening, leaf, root);
+        Ok(())
+    }
+}
+
+#[test]
+fn private_root() {
+    let label = b"merkle opening private root";
+    let mut rng = StdRng::seed_from_u64(0x9007);
+    let pp = PublicParameters::setup(1 << CAPACITY, &mut rng).unwrap();
+
+    let (prover, verifier) =
+        Compiler::compile::<PrivateRootCircuit>(&pp, label)
+            .expect("Circuit should compile successfully");
+
+    let leaf = PoseidonItem::new(BlsScalar::from(42), ());
+    let mut tree = Tree::<(), HEIGHT>::new();
+    tree.insert(7, leaf);
+    tree.insert(21, PoseidonItem::new(BlsScalar::from(43), ()));
+    let opening = tree.opening(7).unwrap();
+
+    let circuit = PrivateRootCircuit { opening, leaf };
+    let (proof, public_inputs) = prover
+        .prove(&mut rng, &circuit)
+        .expect("Proof generation should succeed");
+
+    let root = tree.root().hash;
+    assert_eq!(public_inputs, [root]);
+
+    verifier
+        .verify(&proof, &[root])
+        .expect("Proof verification should succeed");
+    verifier
+        .verify(&proof, &[root + BlsScalar::one()])
+        .expect_err("Proof verification should fail for a different root");
+}
+
+#[test]
+fn private_root_with_position() {
+    let leaf = PoseidonItem::new(BlsScalar::from(42), ());
+    let mut tree = Tree::<(), HEIGHT>::new();
+    tree.insert(7, leaf);
+    let opening = tree.opening(7).unwrap();
+
+    let mut composer = Composer::initialized();
+    let leaf = composer.append_witness(leaf.hash);
+    let root = composer.append_witness(tree.root().hash);
+    let bits = opening_gadget_private_root_with_position(
+        &mut composer,
+        &opening,
+        leaf,
+        root,
+    );
+
+    for (level, position) in bits.iter().zip(opening.positions()) {
+        for (i, bit) in level.iter().enumerate() {
+            let expected = BlsScalar::from(u64::from(i == *position));
+            assert_eq!(composer[*bit], expected);
+        }
     }
-    let opening = tree.opening(position as u64).unwrap();
-    assert!(opening.verify(leaf.clone()));
+}
 
-    let circuit = OpeningCircuit::new(opening, leaf);
+/// A circuit proving an opening at a public position, passed to the opening
+/// gadget as a single witness.
+#[derive(Debug, Clone)]
+struct PositionCircuit {
+    opening: poseidon_merkle::Opening<(), HEIGHT>,
+    leaf: PoseidonItem,
+}
 
+impl Default for PositionCircuit {
+    fn default() -> Self {
+        let PrivateRootCircuit { opening, leaf } =
+            PrivateRootCircuit::default();
+        Self { opening, leaf }
+    }
+}
+
+impl Circuit for PositionCircuit {
+    fn circuit(&self, composer: &mut Composer) -> Result<(), Error> {
+        let leaf = composer.append_witness(self.leaf.hash);
+        let position =
+            composer.append_public(BlsScalar::from(self.opening.position()));
+        let root = opening_gadget_with_position(
+            composer,
+            &self.opening,
+            leaf,
+            position,
+        );
+        composer.assert_equal_constant(
+            root,
+            BlsScalar::zero(),
+            Some(self.opening.root().hash),
+        );
+        Ok(())
+    }
+}
+
+#[test]
+fn position_witness() {
+    let label = b"merkle opening position";
+    let mut rng = StdRng::seed_from_u64(0x5051);
+    let pp = PublicParameters::setup(1 << CAPACITY, &mut rng).unwrap();
+
+    let (prover, verifier) = Compiler::compile::<PositionCircuit>(&pp, label)
+        .expect("Circuit should compile successfully");
+
+    let position = 0x1_2345_6789;
+    let leaf = PoseidonItem::new(BlsScalar::from(42), ());
+    let mut tree = Tree::<(), HEIGHT>::new();
+    tree.insert(position, leaf);
+    tree.insert(21, PoseidonItem::new(BlsScalar::from(43), ()));
+    let opening = tree.opening(position).unwrap();
+
+    let circuit = PositionCircuit { opening, leaf };
     let (proof, public_inputs) = prover
         .prove(&mut rng, &circuit)
         .expect("Proof generation should succeed");
 
+    let root = tree.root().hash;
+    assert_eq!(public_inputs, [BlsScalar::from(position), root]);
+
     verifier
-        .verify(&proof, &public_inputs)
+        .verify(&proof, &[BlsScalar::from(position), root])
         .expect("Proof verification should succeed");
+    verifier
+        .verify(&proof, &[BlsScalar::from(position + 1), root])
+        .expect_err("Proof verification should fail for another position");
 }